@@ -0,0 +1,85 @@
+// Замер скорости вычисления ParsedFunction: cargo bench --bench parser
+use gradient_descent_app::ParsedFunction;
+use meval::{Context, Expr};
+use nalgebra::DVector;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const EXPR: &str = "x1^2 + 2*x2^2 + sin(x3)*x4 + exp(-x5^2) + x1*x5";
+const EVALUATIONS: usize = 10_000;
+
+fn points() -> Vec<DVector<f64>> {
+    (0..EVALUATIONS)
+        .map(|k| DVector::from_fn(5, |i, _| ((k * 5 + i) as f64 * 0.37).sin()))
+        .collect()
+}
+
+fn measure(label: &str, mut run: impl FnMut() -> f64) -> Duration {
+    let started = Instant::now();
+    black_box(run());
+    let elapsed = started.elapsed();
+    println!(
+        "{:<28} {:>10.3} мс ({:.1} нс на вычисление)",
+        label,
+        elapsed.as_secs_f64() * 1e3,
+        elapsed.as_nanos() as f64 / EVALUATIONS as f64
+    );
+    elapsed
+}
+
+// Прежний способ: копия выражения и новый контекст на каждый вызов
+fn rebuilt_eval(expr: &Expr, point: &DVector<f64>) -> f64 {
+    let mut ctx = Context::new();
+    for (i, &v) in point.iter().enumerate() {
+        ctx.var(format!("x{}", i + 1), v);
+    }
+    expr.clone().eval_with_context(ctx).unwrap()
+}
+
+// 5 переменных, 10 000 вычислений: скомпилированное замыкание против
+// копирования выражения и сборки контекста на каждый вызов
+fn compiled_eval(points: &[DVector<f64>]) {
+    let func = ParsedFunction::new(EXPR, 5).unwrap();
+    let expr: Expr = EXPR.parse().unwrap();
+    let rebuilt = measure("контекст на каждый вызов", || {
+        points.iter().map(|p| rebuilt_eval(&expr, p)).sum()
+    });
+    let compiled = measure("ParsedFunction::eval", || {
+        points.iter().map(|p| func.eval(p).unwrap()).sum()
+    });
+    println!(
+        "ускорение: {:.1}x",
+        rebuilt.as_secs_f64() / compiled.as_secs_f64()
+    );
+}
+
+// 10 000 точек: eval_batch против поточечного вычисления со сборкой контекста.
+// После перехода eval на скомпилированное замыкание оба вызова делают одну
+// работу, и eval_batch выигрывает только у сборки контекста
+fn batch_eval(points: &[DVector<f64>]) {
+    let func = ParsedFunction::new(EXPR, 5).unwrap();
+    let expr: Expr = EXPR.parse().unwrap();
+    let rebuilt = measure("контекст на каждую точку", || {
+        points.iter().map(|p| rebuilt_eval(&expr, p)).sum()
+    });
+    let single = measure("eval по точкам", || {
+        points.iter().map(|p| func.eval(p).unwrap()).sum()
+    });
+    let batch = measure("eval_batch", || {
+        func.eval_batch(points)
+            .into_iter()
+            .map(Result::unwrap)
+            .sum()
+    });
+    println!(
+        "ускорение: {:.1}x к сборке контекста, {:.1}x к eval",
+        rebuilt.as_secs_f64() / batch.as_secs_f64(),
+        single.as_secs_f64() / batch.as_secs_f64()
+    );
+}
+
+fn main() {
+    let points = points();
+    compiled_eval(&points);
+    batch_eval(&points);
+}
@@ -0,0 +1,283 @@
+use meval::tokenizer::{Operation, Token};
+use meval::Expr;
+use std::fmt;
+
+// Дерево выражения, восстановленное из обратной польской записи meval
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    Num(f64),
+    Var(String),
+    Neg(Box<Node>),
+    Binary(Operation, Box<Node>, Box<Node>),
+    Func(String, Vec<Node>),
+}
+
+impl Node {
+    pub fn from_expr(expr: &Expr) -> Option<Node> {
+        let mut stack: Vec<Node> = Vec::new();
+        for token in expr.iter() {
+            let node = match token {
+                Token::Number(v) => Node::Num(*v),
+                Token::Var(name) => Node::Var(name.clone()),
+                Token::Unary(Operation::Plus) => stack.pop()?,
+                Token::Unary(Operation::Minus) => Node::Neg(Box::new(stack.pop()?)),
+                Token::Unary(_) => return None,
+                Token::Binary(op) => {
+                    let rhs = stack.pop()?;
+                    let lhs = stack.pop()?;
+                    Node::Binary(*op, Box::new(lhs), Box::new(rhs))
+                }
+                Token::Func(name, Some(n)) => {
+                    if stack.len() < *n {
+                        return None;
+                    }
+                    let args = stack.split_off(stack.len() - n);
+                    Node::Func(name.clone(), args)
+                }
+                _ => return None,
+            };
+            stack.push(node);
+        }
+        if stack.len() == 1 {
+            stack.pop()
+        } else {
+            None
+        }
+    }
+
+    pub fn has_vars(&self) -> bool {
+        match self {
+            Node::Num(_) => false,
+            Node::Var(_) => true,
+            Node::Neg(a) => a.has_vars(),
+            Node::Binary(_, a, b) => a.has_vars() || b.has_vars(),
+            Node::Func(_, args) => args.iter().any(Node::has_vars),
+        }
+    }
+
+    // Значение подвыражения без переменных (функции не вычисляются)
+    pub fn constant_value(&self) -> Option<f64> {
+        match self {
+            Node::Num(v) => Some(*v),
+            Node::Neg(a) => a.constant_value().map(|v| -v),
+            Node::Binary(op, a, b) => {
+                let (a, b) = (a.constant_value()?, b.constant_value()?);
+                Some(match op {
+                    Operation::Plus => a + b,
+                    Operation::Minus => a - b,
+                    Operation::Times => a * b,
+                    Operation::Div => a / b,
+                    Operation::Rem => a % b,
+                    Operation::Pow => a.powf(b),
+                })
+            }
+            Node::Var(_) | Node::Func(_, _) => None,
+        }
+    }
+
+    // Обход всех узлов в прямом порядке
+    pub fn visit(&self, f: &mut dyn FnMut(&Node)) {
+        f(self);
+        match self {
+            Node::Num(_) | Node::Var(_) => {}
+            Node::Neg(a) => a.visit(f),
+            Node::Binary(_, a, b) => {
+                a.visit(f);
+                b.visit(f);
+            }
+            Node::Func(_, args) => args.iter().for_each(|a| a.visit(f)),
+        }
+    }
+
+    // Символьная производная по переменной var; Err — неподдерживаемая конструкция
+    pub fn derivative(&self, var: &str) -> Result<Node, String> {
+        if !self.has_vars() {
+            return Ok(Node::Num(0.0));
+        }
+        match self {
+            Node::Num(_) => Ok(Node::Num(0.0)),
+            Node::Var(name) => Ok(Node::Num(if name == var { 1.0 } else { 0.0 })),
+            Node::Neg(a) => Ok(neg(a.derivative(var)?)),
+            Node::Binary(op, a, b) => {
+                let (da, db) = (a.derivative(var)?, b.derivative(var)?);
+                let (a, b) = (a.as_ref().clone(), b.as_ref().clone());
+                match op {
+                    Operation::Plus => Ok(add(da, db)),
+                    Operation::Minus => Ok(sub(da, db)),
+                    Operation::Times => Ok(add(mul(da, b.clone()), mul(a, db))),
+                    // (a/b)' = (a'·b - a·b') / b²
+                    Operation::Div => Ok(div(
+                        sub(mul(da, b.clone()), mul(a, db)),
+                        pow(b, Node::Num(2.0)),
+                    )),
+                    // Для дробного показателя формулы верны при a > 0 (a >= 0 при c > 1),
+                    // для переменного — только при a > 0, где определён ln(a)
+                    Operation::Pow => match (b.constant_value(), a.constant_value()) {
+                        // (a^c)' = c·a^(c-1)·a' для любого числа c
+                        (Some(c), _) => Ok(mul(mul(Node::Num(c), pow(a, Node::Num(c - 1.0))), da)),
+                        // (c^b)' = c^b·ln(c)·b'
+                        (None, Some(c)) if c > 0.0 => {
+                            Ok(mul(mul(self.clone(), Node::Num(c.ln())), db))
+                        }
+                        (None, Some(_)) => {
+                            Err(format!("неположительное основание степени: {}", self))
+                        }
+                        // (a^b)' = a^b·(b·a'/a + ln(a)·b')
+                        (None, None) => Ok(mul(
+                            self.clone(),
+                            add(div(mul(b, da), a.clone()), mul(func("ln", a), db)),
+                        )),
+                    },
+                    Operation::Rem => Err(format!("остаток от деления: {}", self)),
+                }
+            }
+            Node::Func(name, args) if args.len() == 1 => {
+                let u = args[0].clone();
+                let du = u.derivative(var)?;
+                let outer = match name.as_str() {
+                    "sin" => func("cos", u),
+                    "cos" => neg(func("sin", u)),
+                    "tan" => div(Node::Num(1.0), pow(func("cos", u), Node::Num(2.0))),
+                    "exp" => func("exp", u),
+                    "ln" => div(Node::Num(1.0), u),
+                    "sqrt" => div(Node::Num(1.0), mul(Node::Num(2.0), func("sqrt", u))),
+                    _ => return Err(format!("функция {}", name)),
+                };
+                Ok(mul(outer, du))
+            }
+            Node::Func(name, _) => Err(format!("функция {}", name)),
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Node::Binary(Operation::Plus | Operation::Minus, _, _) => 1,
+            Node::Binary(Operation::Times | Operation::Div | Operation::Rem, _, _) => 2,
+            Node::Neg(_) => 3,
+            Node::Binary(Operation::Pow, _, _) => 4,
+            Node::Num(v) if *v < 0.0 => 3,
+            Node::Num(_) | Node::Var(_) | Node::Func(_, _) => 5,
+        }
+    }
+
+    fn fmt_child(&self, f: &mut fmt::Formatter<'_>, min_precedence: u8) -> fmt::Result {
+        if self.precedence() < min_precedence {
+            write!(f, "({})", self)
+        } else {
+            write!(f, "{}", self)
+        }
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Node::Num(v) => write!(f, "{}", v),
+            Node::Var(name) => write!(f, "{}", name),
+            Node::Neg(a) => {
+                write!(f, "-")?;
+                a.fmt_child(f, 3)
+            }
+            Node::Binary(op, a, b) => {
+                let p = self.precedence();
+                let symbol = match op {
+                    Operation::Plus => " + ",
+                    Operation::Minus => " - ",
+                    Operation::Times => "*",
+                    Operation::Div => "/",
+                    Operation::Rem => " % ",
+                    Operation::Pow => "^",
+                };
+                // Степень правоассоциативна, остальные операции — левоассоциативны
+                if *op == Operation::Pow {
+                    a.fmt_child(f, p + 1)?;
+                    write!(f, "{}", symbol)?;
+                    b.fmt_child(f, p)
+                } else {
+                    a.fmt_child(f, p)?;
+                    write!(f, "{}", symbol)?;
+                    b.fmt_child(f, p + 1)
+                }
+            }
+            Node::Func(name, args) => {
+                write!(f, "{}(", name)?;
+                for (k, arg) in args.iter().enumerate() {
+                    if k > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arg)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+// Конструкторы с упрощением тривиальных случаев (0 + a, 1·a, a^1 ...),
+// чтобы производные не разрастались
+fn neg(a: Node) -> Node {
+    match a {
+        Node::Num(v) => Node::Num(-v),
+        Node::Neg(inner) => *inner,
+        a => Node::Neg(Box::new(a)),
+    }
+}
+
+fn add(a: Node, b: Node) -> Node {
+    match (a, b) {
+        (Node::Num(x), Node::Num(y)) => Node::Num(x + y),
+        (Node::Num(0.0), b) => b,
+        (a, Node::Num(0.0)) => a,
+        (a, Node::Neg(b)) => Node::Binary(Operation::Minus, Box::new(a), b),
+        (a, Node::Num(c)) if c < 0.0 => sub(a, Node::Num(-c)),
+        (a, Node::Binary(Operation::Times, c, b)) if matches!(*c, Node::Num(v) if v < 0.0) => {
+            sub(a, mul(neg(*c), *b))
+        }
+        (a, b) => Node::Binary(Operation::Plus, Box::new(a), Box::new(b)),
+    }
+}
+
+fn sub(a: Node, b: Node) -> Node {
+    match (a, b) {
+        (Node::Num(x), Node::Num(y)) => Node::Num(x - y),
+        (a, Node::Num(0.0)) => a,
+        (Node::Num(0.0), b) => neg(b),
+        (a, b) => Node::Binary(Operation::Minus, Box::new(a), Box::new(b)),
+    }
+}
+
+fn mul(a: Node, b: Node) -> Node {
+    match (a, b) {
+        (Node::Num(x), Node::Num(y)) => Node::Num(x * y),
+        (Node::Num(0.0), _) | (_, Node::Num(0.0)) => Node::Num(0.0),
+        (Node::Num(1.0), b) => b,
+        (a, Node::Num(1.0)) => a,
+        (Node::Num(-1.0), b) => neg(b),
+        (a, Node::Num(-1.0)) => neg(a),
+        (Node::Neg(a), b) => neg(mul(*a, b)),
+        (a, Node::Neg(b)) => neg(mul(a, *b)),
+        // Числовой множитель выносится вперёд
+        (a, Node::Num(c)) => Node::Binary(Operation::Times, Box::new(Node::Num(c)), Box::new(a)),
+        (a, b) => Node::Binary(Operation::Times, Box::new(a), Box::new(b)),
+    }
+}
+
+fn div(a: Node, b: Node) -> Node {
+    match (a, b) {
+        (Node::Num(0.0), _) => Node::Num(0.0),
+        (a, Node::Num(1.0)) => a,
+        (a, b) => Node::Binary(Operation::Div, Box::new(a), Box::new(b)),
+    }
+}
+
+fn pow(a: Node, b: Node) -> Node {
+    match b {
+        Node::Num(0.0) => Node::Num(1.0),
+        Node::Num(1.0) => a,
+        b => Node::Binary(Operation::Pow, Box::new(a), Box::new(b)),
+    }
+}
+
+fn func(name: &str, arg: Node) -> Node {
+    Node::Func(name.to_string(), vec![arg])
+}
@@ -0,0 +1,230 @@
+// Запуск оптимизации из командной строки без окна:
+// gradient_descent_app --func "x1^2+x2^2" --start "3,3" --algo gradient --tol 1e-8
+use clap::{Parser, ValueEnum};
+use gradient_descent_app::export;
+use gradient_descent_app::line_search::BacktrackingArmijo;
+use gradient_descent_app::optimizer::{
+    self, AdamParams, AnnealingParams, DescentParams, MomentumParams, NelderMeadParams,
+};
+use gradient_descent_app::optimizer::{
+    BbVariant, OptimizerResult, RmsPropParams, TrustRegionParams,
+};
+use gradient_descent_app::parser::{GradientMode, ParsedFunction};
+use nalgebra::DVector;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+#[derive(Parser)]
+#[command(about = "Методы оптимизации без графического интерфейса")]
+struct Args {
+    #[arg(
+        long,
+        help = "Целевая функция от x1..xn (для n <= 3 допустимы x, y, z)"
+    )]
+    func: String,
+    #[arg(
+        long,
+        allow_hyphen_values = true,
+        help = "Начальная точка через запятую; её длина задаёт размерность"
+    )]
+    start: String,
+    #[arg(long, value_enum, default_value_t = Algo::Gradient)]
+    algo: Algo,
+    #[arg(long, value_enum, default_value_t = Gradient::Central)]
+    gradient: Gradient,
+    #[arg(long, default_value_t = 1e-6, help = "Точность по норме градиента")]
+    tol: f64,
+    #[arg(long, default_value_t = 1000)]
+    max_iter: usize,
+    #[arg(
+        long,
+        default_value_t = 0,
+        help = "Наибольшее число точек траектории, 0 — все"
+    )]
+    max_history: usize,
+    #[arg(long, default_value_t = 1.0, help = "Начальный шаг")]
+    step: f64,
+    #[arg(
+        long,
+        default_value_t = 20,
+        help = "Наибольшее число дроблений шага за итерацию"
+    )]
+    max_backtracks: usize,
+    #[arg(
+        long,
+        default_value_t = 100.0,
+        help = "Граница отрезка для точного шага (--algo steepest)"
+    )]
+    step_max: f64,
+    #[arg(long, default_value_t = 10, help = "Число пар (s, y) для --algo lbfgs")]
+    memory: usize,
+    #[arg(long, help = "Файл для траектории в CSV")]
+    csv: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Algo {
+    Gradient,
+    Steepest,
+    Bb,
+    Momentum,
+    Adam,
+    Rmsprop,
+    Cg,
+    Newton,
+    Bfgs,
+    Lbfgs,
+    TrustRegion,
+    Dogleg,
+    NelderMead,
+    Annealing,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Gradient {
+    Forward,
+    Central,
+    Analytic,
+}
+
+impl From<Gradient> for GradientMode {
+    fn from(mode: Gradient) -> Self {
+        match mode {
+            Gradient::Forward => GradientMode::Forward,
+            Gradient::Central => GradientMode::Central,
+            Gradient::Analytic => GradientMode::Analytic,
+        }
+    }
+}
+
+// Код завершения процесса: 0 — успех, 1 — ошибка во входных данных
+pub fn run() -> i32 {
+    let args = Args::parse();
+    match solve(&args) {
+        Ok(result) => {
+            print_result(&result);
+            if let Some(path) = &args.csv {
+                let names: Vec<String> = (1..=result.x.len()).map(|i| format!("x{}", i)).collect();
+                if let Err(e) = std::fs::write(path, export::trajectory_csv(&result, &names)) {
+                    eprintln!("Не удалось сохранить {}: {}", path.display(), e);
+                    return 1;
+                }
+            }
+            0
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    }
+}
+
+fn solve(args: &Args) -> Result<OptimizerResult, String> {
+    let start = args
+        .start
+        .split(',')
+        .map(|part| {
+            part.trim().parse::<f64>().map_err(|_| {
+                format!(
+                    "Ошибка в начальной точке: не удалось разобрать '{}'",
+                    part.trim()
+                )
+            })
+        })
+        .collect::<Result<Vec<f64>, String>>()?;
+    let func = ParsedFunction::new(&args.func, start.len())
+        .map_err(|e| format!("Ошибка в функции: {}", e))?;
+
+    let params = DescentParams {
+        initial_step: args.step,
+        tolerance: args.tol,
+        max_iterations: args.max_iter,
+        max_history_points: args.max_history,
+        max_backtracks: args.max_backtracks,
+        ..Default::default()
+    };
+    params
+        .validate()
+        .map_err(|e| format!("Ошибка в параметрах: {}", e))?;
+
+    let mode = GradientMode::from(args.gradient);
+    let n = start.len();
+    let f = |x: &DVector<f64>| func.eval(x).unwrap_or(f64::NAN);
+    let grad = |x: &DVector<f64>| {
+        func.gradient(x, 1e-6, mode)
+            .unwrap_or_else(|_| DVector::from_element(n, f64::NAN))
+    };
+    // Аналитическая матрица Гессе, если выражение удалось продифференцировать дважды
+    let hess = |x: &DVector<f64>| {
+        let h = if func.has_analytic_hessian() {
+            func.eval_hessian(x)
+        } else {
+            func.hessian(x, 1e-4)
+        };
+        h.unwrap_or_else(|_| nalgebra::DMatrix::from_element(n, n, f64::NAN))
+    };
+    let start = DVector::from_vec(start);
+    let stop = Arc::new(AtomicBool::new(false));
+
+    Ok(match args.algo {
+        Algo::Gradient => {
+            let line_search =
+                BacktrackingArmijo::new(params.initial_step, params.armijo_c1, params.step_decay)
+                    .with_growth(params.step_increase, params.max_step)
+                    .with_max_backtracks(params.max_backtracks);
+            optimizer::gradient_descent(start, &f, &grad, &params, &line_search, stop)
+        }
+        Algo::Steepest => {
+            optimizer::steepest_descent_exact(start, &f, &grad, &params, args.step_max, stop)
+        }
+        Algo::Bb => optimizer::bb_gradient_descent(start, &f, &grad, &params, BbVariant::Bb1, stop),
+        Algo::Momentum => {
+            optimizer::momentum_descent(start, &f, &grad, &params, &MomentumParams::default(), stop)
+        }
+        Algo::Adam => optimizer::adam(start, &f, &grad, &params, &AdamParams::default(), stop),
+        Algo::Rmsprop => {
+            optimizer::rmsprop(start, &f, &grad, &params, &RmsPropParams::default(), stop)
+        }
+        Algo::Cg => optimizer::conjugate_gradient(start, &f, &grad, &params, None, stop),
+        Algo::Newton => optimizer::newton_descent(start, &f, &grad, &hess, &params, stop),
+        Algo::Bfgs => optimizer::bfgs(start, &f, &grad, &params, stop),
+        Algo::Lbfgs => optimizer::lbfgs(start, &f, &grad, &params, args.memory, stop),
+        Algo::TrustRegion => optimizer::trust_region_cg(
+            start,
+            &f,
+            &grad,
+            &params,
+            &TrustRegionParams::default(),
+            stop,
+        ),
+        Algo::Dogleg => optimizer::trust_region_dogleg(
+            start,
+            &f,
+            &grad,
+            &hess,
+            &params,
+            &TrustRegionParams::default(),
+            stop,
+        ),
+        Algo::NelderMead => {
+            optimizer::nelder_mead(start, &f, &params, &NelderMeadParams::default(), stop)
+        }
+        Algo::Annealing => {
+            optimizer::simulated_annealing(start, &f, &params, &AnnealingParams::default(), stop)
+        }
+    })
+}
+
+fn print_result(result: &OptimizerResult) {
+    let x: Vec<String> = result.x.iter().map(|v| v.to_string()).collect();
+    println!("x* = [{}]", x.join(", "));
+    println!("f(x*) = {}", result.f_x);
+    println!("итераций: {}", result.iterations);
+    println!("останов: {:?}", result.termination);
+    println!(
+        "вычислений f: {}, градиента: {}",
+        result.f_evals, result.grad_evals
+    );
+    println!("время: {:.3} мс", result.elapsed.as_secs_f64() * 1000.0);
+}
@@ -0,0 +1,127 @@
+// Линии уровня функции двух переменных методом marching squares
+
+pub type Segment = [[f64; 2]; 2];
+
+// Значения функции в узлах равномерной сетки n×n, построчно по y;
+// None — функция не определена в узле
+pub struct Grid {
+    pub n: usize,
+    pub x_range: (f64, f64),
+    pub y_range: (f64, f64),
+    pub values: Vec<Option<f64>>,
+}
+
+impl Grid {
+    pub fn sample(
+        n: usize,
+        x_range: (f64, f64),
+        y_range: (f64, f64),
+        f: impl Fn(f64, f64) -> Option<f64>,
+    ) -> Self {
+        Self::sample_batch(n, x_range, y_range, |nodes| {
+            nodes.iter().map(|&(x, y)| f(x, y)).collect()
+        })
+    }
+
+    // То же для функции, вычисляемой сразу во всех узлах (в порядке values)
+    pub fn sample_batch(
+        n: usize,
+        x_range: (f64, f64),
+        y_range: (f64, f64),
+        f: impl FnOnce(&[(f64, f64)]) -> Vec<Option<f64>>,
+    ) -> Self {
+        let mut grid = Self {
+            n,
+            x_range,
+            y_range,
+            values: Vec::new(),
+        };
+        let nodes: Vec<(f64, f64)> = (0..n)
+            .flat_map(|j| (0..n).map(move |i| (i, j)))
+            .map(|(i, j)| grid.node(i, j))
+            .collect();
+        grid.values = f(&nodes)
+            .into_iter()
+            .map(|v| v.filter(|v| v.is_finite()))
+            .collect();
+        grid
+    }
+
+    pub fn node(&self, i: usize, j: usize) -> (f64, f64) {
+        let t = |k: usize| k as f64 / (self.n - 1) as f64;
+        (
+            self.x_range.0 + t(i) * (self.x_range.1 - self.x_range.0),
+            self.y_range.0 + t(j) * (self.y_range.1 - self.y_range.0),
+        )
+    }
+
+    fn value(&self, i: usize, j: usize) -> Option<f64> {
+        self.values[j * self.n + i]
+    }
+
+    // Уровни равномерны по квантилям значений, а не по диапазону: иначе
+    // у функций с крутыми стенками почти все линии уходят к краям сетки
+    pub fn levels(&self, count: usize) -> Vec<f64> {
+        let mut sorted: Vec<f64> = self.values.iter().flatten().copied().collect();
+        if sorted.is_empty() || count == 0 {
+            return Vec::new();
+        }
+        sorted.sort_by(f64::total_cmp);
+        let mut levels: Vec<f64> = (1..=count)
+            .map(|k| sorted[(k * (sorted.len() - 1)) / (count + 1)])
+            .collect();
+        levels.dedup();
+        levels
+    }
+
+    // Отрезки линии уровня f = level; ячейки, в углах которых функция
+    // не определена, пропускаются
+    pub fn contour(&self, level: f64) -> Vec<Segment> {
+        let mut segments = Vec::new();
+        for j in 0..self.n.saturating_sub(1) {
+            for i in 0..self.n - 1 {
+                let corners = [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)];
+                let mut values = [0.0; 4];
+                let mut defined = true;
+                for (k, &(ci, cj)) in corners.iter().enumerate() {
+                    match self.value(ci, cj) {
+                        Some(v) => values[k] = v,
+                        None => defined = false,
+                    }
+                }
+                if !defined {
+                    continue;
+                }
+
+                // Точки пересечения уровня с рёбрами ячейки
+                let mut crossings = Vec::with_capacity(4);
+                for k in 0..4 {
+                    let (a, b) = (values[k], values[(k + 1) % 4]);
+                    if (a < level) != (b < level) {
+                        let t = (level - a) / (b - a);
+                        let (xa, ya) = self.node(corners[k].0, corners[k].1);
+                        let (xb, yb) = self.node(corners[(k + 1) % 4].0, corners[(k + 1) % 4].1);
+                        crossings.push([xa + t * (xb - xa), ya + t * (yb - ya)]);
+                    }
+                }
+
+                match crossings.len() {
+                    2 => segments.push([crossings[0], crossings[1]]),
+                    // Седловая ячейка: пара отрезков выбирается по значению в центре
+                    4 => {
+                        let center = values.iter().sum::<f64>() / 4.0;
+                        if (center < level) == (values[0] < level) {
+                            segments.push([crossings[0], crossings[1]]);
+                            segments.push([crossings[2], crossings[3]]);
+                        } else {
+                            segments.push([crossings[0], crossings[3]]);
+                            segments.push([crossings[1], crossings[2]]);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        segments
+    }
+}
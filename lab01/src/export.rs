@@ -0,0 +1,56 @@
+use crate::optimizer::OptimizerResult;
+
+// Траектория спуска в CSV: iteration, переменные, f_x, grad_norm.
+// Норма градиента пуста, если метод её не записывает
+pub fn trajectory_csv(result: &OptimizerResult, var_names: &[String]) -> String {
+    let mut csv = trajectory_header(var_names);
+    csv.push('\n');
+    for (k, (x, f_x)) in result.history.iter().enumerate() {
+        csv.push_str(&trajectory_row(
+            result.iteration_at(k),
+            x.as_slice(),
+            *f_x,
+            result.grad_norms.get(k).copied(),
+        ));
+        csv.push('\n');
+    }
+    csv
+}
+
+pub fn trajectory_header(var_names: &[String]) -> String {
+    let mut columns = vec!["iteration".to_string()];
+    columns.extend(var_names.iter().cloned());
+    columns.push("f_x".to_string());
+    columns.push("grad_norm".to_string());
+    columns.join(",")
+}
+
+pub fn trajectory_row(iteration: usize, x: &[f64], f_x: f64, grad_norm: Option<f64>) -> String {
+    let mut fields = vec![iteration.to_string()];
+    fields.extend(x.iter().map(|v| v.to_string()));
+    fields.push(f_x.to_string());
+    fields.push(grad_norm.map(|g| g.to_string()).unwrap_or_default());
+    fields.join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn row_has_one_column_per_variable() {
+        let names: Vec<String> = (1..=3).map(|i| format!("x{}", i)).collect();
+        assert_eq!(
+            trajectory_header(&names),
+            "iteration,x1,x2,x3,f_x,grad_norm"
+        );
+        assert_eq!(
+            trajectory_row(7, &[1.0, -0.5, 2.25], 0.125, Some(1e-3)),
+            "7,1,-0.5,2.25,0.125,0.001"
+        );
+        // Без нормы градиента последнее поле пустое, число столбцов то же
+        let row = trajectory_row(0, &[1.0, 2.0, 3.0], 4.0, None);
+        assert_eq!(row, "0,1,2,3,4,");
+        assert_eq!(row.split(',').count(), names.len() + 3);
+    }
+}
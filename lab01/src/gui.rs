@@ -1,8 +1,24 @@
-use crate::optimizer::{self, OptimizerResult};
-use crate::parser::ParsedFunction;
+use crate::i18n::Lang;
 use eframe::egui;
-use egui_plot::{Line, Plot, PlotPoints};
-use nalgebra::DVector;
+use egui_plot::{Legend, Line, Plot, PlotBounds, PlotPoint, PlotPoints, Points, Polygon};
+use gradient_descent_app::contour::{Grid, Segment};
+use gradient_descent_app::export;
+use gradient_descent_app::least_squares::LeastSquares;
+use gradient_descent_app::line_search::{
+    AdaptiveHalving, BacktrackingArmijo, GoldenSection, LineSearch,
+};
+use gradient_descent_app::optimizer::{
+    self, AdamParams, AnnealingParams, BbVariant, Bounds, DescentParams, MomentumParams,
+    NelderMeadParams, OptimizerResult, ParetoPoint, PenaltyParams, PenaltyResult, PerturbParams,
+    Progress, RestartParams, RestartReport, RmsPropParams, SensitivityMap, TerminationReason,
+    TrustRegionParams, TuneReport,
+};
+use gradient_descent_app::parser::{self, GradientMode, ParsedFunction, ParserError};
+use gradient_descent_app::presets::{Preset, PRESETS};
+use gradient_descent_app::surface::{Surface, View};
+use nalgebra::{DMatrix, DVector};
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::Arc;
@@ -15,284 +31,5047 @@ enum OptimizerState {
     Stopping,
 }
 
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+enum Algorithm {
+    GradientDescent,
+    BarzilaiBorwein,
+    Momentum,
+    Adam,
+    RmsProp,
+    ConjugateGradient,
+    Newton,
+    Bfgs,
+    Lbfgs,
+    TrustRegionCg,
+    TrustRegionDogleg,
+    NelderMead,
+    Annealing,
+    GaussNewton,
+}
+
+impl Algorithm {
+    // Методы, проецирующие итерации на прямоугольные ограничения
+    fn supports_bounds(self) -> bool {
+        matches!(
+            self,
+            Algorithm::GradientDescent
+                | Algorithm::BarzilaiBorwein
+                | Algorithm::Momentum
+                | Algorithm::Adam
+                | Algorithm::RmsProp
+                | Algorithm::Annealing
+        )
+    }
+
+    const ALL: [Algorithm; 14] = [
+        Algorithm::GradientDescent,
+        Algorithm::BarzilaiBorwein,
+        Algorithm::Momentum,
+        Algorithm::Adam,
+        Algorithm::RmsProp,
+        Algorithm::ConjugateGradient,
+        Algorithm::Newton,
+        Algorithm::Bfgs,
+        Algorithm::Lbfgs,
+        Algorithm::TrustRegionCg,
+        Algorithm::TrustRegionDogleg,
+        Algorithm::NelderMead,
+        Algorithm::Annealing,
+        Algorithm::GaussNewton,
+    ];
+
+    // Ключ строки интерфейса в i18n
+    fn label(self) -> &'static str {
+        match self {
+            Algorithm::GradientDescent => "alg_gradient_descent",
+            Algorithm::BarzilaiBorwein => "alg_barzilai_borwein",
+            Algorithm::Momentum => "alg_momentum",
+            Algorithm::Adam => "alg_adam",
+            Algorithm::RmsProp => "alg_rmsprop",
+            Algorithm::ConjugateGradient => "alg_conjugate_gradient",
+            Algorithm::Newton => "alg_newton",
+            Algorithm::Bfgs => "alg_bfgs",
+            Algorithm::Lbfgs => "alg_lbfgs",
+            Algorithm::TrustRegionCg => "alg_trust_region",
+            Algorithm::TrustRegionDogleg => "alg_trust_region_dogleg",
+            Algorithm::NelderMead => "alg_nelder_mead",
+            Algorithm::Annealing => "alg_annealing",
+            Algorithm::GaussNewton => "alg_gauss_newton",
+        }
+    }
+}
+
+// Тема оформления; System следует настройке операционной системы
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+enum Theme {
+    System,
+    Light,
+    Dark,
+}
+
+impl Theme {
+    const ALL: [Theme; 3] = [Theme::System, Theme::Light, Theme::Dark];
+
+    fn label(self) -> &'static str {
+        match self {
+            Theme::System => "theme_system",
+            Theme::Light => "theme_light",
+            Theme::Dark => "theme_dark",
+        }
+    }
+
+    fn preference(self) -> egui::ThemePreference {
+        match self {
+            Theme::System => egui::ThemePreference::System,
+            Theme::Light => egui::ThemePreference::Light,
+            Theme::Dark => egui::ThemePreference::Dark,
+        }
+    }
+}
+
+// Цвета отметок на графиках и предупреждений, различимые на фоне текущей темы
+#[derive(Clone, Copy)]
+struct Palette {
+    good: egui::Color32,
+    warn: egui::Color32,
+    bad: egui::Color32,
+    // Путь поверх каркаса поверхности
+    path: egui::Color32,
+    current: egui::Color32,
+    finals: egui::Color32,
+}
+
+impl Palette {
+    fn new(visuals: &egui::Visuals) -> Self {
+        if visuals.dark_mode {
+            Self {
+                good: egui::Color32::GREEN,
+                warn: egui::Color32::YELLOW,
+                bad: egui::Color32::RED,
+                path: egui::Color32::WHITE,
+                current: egui::Color32::YELLOW,
+                finals: egui::Color32::LIGHT_BLUE,
+            }
+        } else {
+            Self {
+                good: egui::Color32::DARK_GREEN,
+                warn: egui::Color32::from_rgb(170, 110, 0),
+                bad: egui::Color32::from_rgb(200, 0, 0),
+                path: egui::Color32::BLACK,
+                current: egui::Color32::from_rgb(230, 120, 0),
+                finals: egui::Color32::from_rgb(0, 90, 200),
+            }
+        }
+    }
+}
+
+// Способ выбора шага вдоль антиградиента в градиентном спуске
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+enum LineSearchKind {
+    AdaptiveHalving,
+    Armijo,
+    GoldenSection,
+}
+
+impl LineSearchKind {
+    const ALL: [LineSearchKind; 3] = [
+        LineSearchKind::AdaptiveHalving,
+        LineSearchKind::Armijo,
+        LineSearchKind::GoldenSection,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            LineSearchKind::AdaptiveHalving => "ls_halving",
+            LineSearchKind::Armijo => "ls_armijo",
+            LineSearchKind::GoldenSection => "ls_golden",
+        }
+    }
+
+    // Дробление хранит текущий шаг между итерациями, поэтому
+    // объект создаётся заново для каждого запуска
+    fn build(self, params: &DescentParams) -> Box<dyn LineSearch> {
+        match self {
+            LineSearchKind::AdaptiveHalving => Box::new(
+                AdaptiveHalving::new(
+                    params.initial_step,
+                    params.step_decay,
+                    params.step_increase,
+                    params.max_step,
+                )
+                .with_max_backtracks(params.max_backtracks),
+            ),
+            LineSearchKind::Armijo => Box::new(
+                BacktrackingArmijo::new(params.initial_step, params.armijo_c1, params.step_decay)
+                    .with_growth(params.step_increase, params.max_step)
+                    .with_max_backtracks(params.max_backtracks),
+            ),
+            LineSearchKind::GoldenSection => Box::new(GoldenSection {
+                initial_step: params.initial_step,
+                tol: 1e-6,
+                max_step: f64::INFINITY,
+            }),
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+enum CoordinateSystem {
+    Cartesian,
+    Polar,
+    Spherical,
+}
+
+impl CoordinateSystem {
+    const ALL: [CoordinateSystem; 3] = [
+        CoordinateSystem::Cartesian,
+        CoordinateSystem::Polar,
+        CoordinateSystem::Spherical,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            CoordinateSystem::Cartesian => "coords_cartesian",
+            CoordinateSystem::Polar => "coords_polar",
+            CoordinateSystem::Spherical => "coords_spherical",
+        }
+    }
+
+    fn dimension(self) -> Option<usize> {
+        match self {
+            CoordinateSystem::Cartesian => None,
+            CoordinateSystem::Polar => Some(2),
+            CoordinateSystem::Spherical => Some(3),
+        }
+    }
+
+    fn var_names(self, num_vars: usize) -> Vec<String> {
+        match self {
+            CoordinateSystem::Cartesian => (1..=num_vars).map(|i| format!("x{}", i)).collect(),
+            CoordinateSystem::Polar => vec!["r".to_string(), "theta".to_string()],
+            CoordinateSystem::Spherical => {
+                vec!["r".to_string(), "theta".to_string(), "phi".to_string()]
+            }
+        }
+    }
+
+    // Индексы угловых координат
+    fn periodic(self) -> Vec<usize> {
+        match self {
+            CoordinateSystem::Cartesian => Vec::new(),
+            CoordinateSystem::Polar => vec![1],
+            CoordinateSystem::Spherical => vec![1, 2],
+        }
+    }
+
+    fn cartesian_to_native(self, p: &[f64]) -> Vec<f64> {
+        match self {
+            CoordinateSystem::Cartesian => p.to_vec(),
+            CoordinateSystem::Polar => vec![p[0].hypot(p[1]), p[1].atan2(p[0])],
+            CoordinateSystem::Spherical => {
+                let r = (p[0] * p[0] + p[1] * p[1] + p[2] * p[2]).sqrt();
+                let theta = if r > 0.0 { (p[2] / r).acos() } else { 0.0 };
+                vec![r, theta, p[1].atan2(p[0])]
+            }
+        }
+    }
+
+    // theta в сферической системе отсчитывается от оси z, phi — азимут
+    fn to_cartesian(self, p: &[f64]) -> Vec<f64> {
+        match self {
+            CoordinateSystem::Cartesian => p.to_vec(),
+            CoordinateSystem::Polar => vec![p[0] * p[1].cos(), p[0] * p[1].sin()],
+            CoordinateSystem::Spherical => vec![
+                p[0] * p[1].sin() * p[2].cos(),
+                p[0] * p[1].sin() * p[2].sin(),
+                p[0] * p[1].cos(),
+            ],
+        }
+    }
+}
+
+// Выбранный метод вместе с его параметрами; используется для всех запусков
+// из рабочего потока, включая серии запусков
+struct MethodConfig {
+    algorithm: Algorithm,
+    params: DescentParams,
+    line_search: LineSearchKind,
+    tr_params: TrustRegionParams,
+    momentum_params: MomentumParams,
+    adam_params: AdamParams,
+    rmsprop_params: RmsPropParams,
+    nm_params: NelderMeadParams,
+    sa_params: AnnealingParams,
+    bb_variant: BbVariant,
+    lbfgs_memory: usize,
+    powell_threshold: Option<f64>,
+}
+
+impl MethodConfig {
+    fn run(
+        &self,
+        start: DVector<f64>,
+        objective: &Objective,
+        stop_flag: Arc<AtomicBool>,
+    ) -> OptimizerResult {
+        self.run_counted(start, objective, &|| {}, stop_flag)
+    }
+
+    // Запуск с пределом budget на число вычислений f и ∇f (для Гаусса — Ньютона —
+    // невязок и якобиана; 0 — без предела). По исчерпании бюджета метод
+    // останавливается, как по кнопке «Стоп»; матрица Гессе в бюджет не входит.
+    // Возвращает результат и число израсходованных вычислений
+    fn run_with_budget(
+        &self,
+        start: DVector<f64>,
+        objective: &Objective,
+        budget: usize,
+        stop_flag: Arc<AtomicBool>,
+    ) -> (OptimizerResult, usize) {
+        let used = Cell::new(0);
+        let method_stop = Arc::new(AtomicBool::new(false));
+        let charge = || {
+            used.set(used.get() + 1);
+            if (budget > 0 && used.get() >= budget) || stop_flag.load(Ordering::SeqCst) {
+                method_stop.store(true, Ordering::SeqCst);
+            }
+        };
+        let result = self.run_counted(start, objective, &charge, method_stop.clone());
+        (result, used.get())
+    }
+
+    fn run_counted(
+        &self,
+        start: DVector<f64>,
+        objective: &Objective,
+        charge: &dyn Fn(),
+        stop_flag: Arc<AtomicBool>,
+    ) -> OptimizerResult {
+        let f = |x: &DVector<f64>| {
+            charge();
+            objective.eval(x)
+        };
+        let grad = |x: &DVector<f64>| {
+            charge();
+            objective.gradient(x)
+        };
+        let mut result = match self.algorithm {
+            Algorithm::GradientDescent => {
+                let line_search = self.line_search.build(&self.params);
+                optimizer::gradient_descent(
+                    start,
+                    &f,
+                    &grad,
+                    &self.params,
+                    line_search.as_ref(),
+                    stop_flag,
+                )
+            }
+            Algorithm::BarzilaiBorwein => optimizer::bb_gradient_descent(
+                start,
+                &f,
+                &grad,
+                &self.params,
+                self.bb_variant,
+                stop_flag,
+            ),
+            Algorithm::Momentum => optimizer::momentum_descent(
+                start,
+                &f,
+                &grad,
+                &self.params,
+                &self.momentum_params,
+                stop_flag,
+            ),
+            Algorithm::Adam => {
+                optimizer::adam(start, &f, &grad, &self.params, &self.adam_params, stop_flag)
+            }
+            Algorithm::RmsProp => optimizer::rmsprop(
+                start,
+                &f,
+                &grad,
+                &self.params,
+                &self.rmsprop_params,
+                stop_flag,
+            ),
+            Algorithm::ConjugateGradient => optimizer::conjugate_gradient(
+                start,
+                &f,
+                &grad,
+                &self.params,
+                self.powell_threshold,
+                stop_flag,
+            ),
+            Algorithm::Newton => {
+                let hess = |x: &DVector<f64>| objective.hessian(x);
+                optimizer::newton_descent(start, &f, &grad, &hess, &self.params, stop_flag)
+            }
+            Algorithm::NelderMead => {
+                optimizer::nelder_mead(start, &f, &self.params, &self.nm_params, stop_flag)
+            }
+            Algorithm::Annealing => {
+                optimizer::simulated_annealing(start, &f, &self.params, &self.sa_params, stop_flag)
+            }
+            Algorithm::Bfgs => optimizer::bfgs(start, &f, &grad, &self.params, stop_flag),
+            Algorithm::Lbfgs => {
+                optimizer::lbfgs(start, &f, &grad, &self.params, self.lbfgs_memory, stop_flag)
+            }
+            Algorithm::TrustRegionCg => optimizer::trust_region_cg(
+                start,
+                &f,
+                &grad,
+                &self.params,
+                &self.tr_params,
+                stop_flag,
+            ),
+            Algorithm::TrustRegionDogleg => {
+                let hess = |x: &DVector<f64>| objective.hessian(x);
+                optimizer::trust_region_dogleg(
+                    start,
+                    &f,
+                    &grad,
+                    &hess,
+                    &self.params,
+                    &self.tr_params,
+                    stop_flag,
+                )
+            }
+            // Без невязок сумма квадратов не выделена, и вместо приближения
+            // JᵀJ используется полная матрица Гессе, как в методе Ньютона
+            Algorithm::GaussNewton => match &objective.residuals {
+                Some(ls) if !objective.maximize && objective.second.is_none() => ls.gauss_newton(
+                    start,
+                    objective.eps,
+                    objective.mode,
+                    &self.params,
+                    charge,
+                    stop_flag,
+                ),
+                _ => {
+                    let hess = |x: &DVector<f64>| objective.hessian(x);
+                    optimizer::newton_descent(start, &f, &grad, &hess, &self.params, stop_flag)
+                }
+            },
+        };
+        if objective.maximize {
+            result.negate_objective();
+        }
+        result.cap_log(MAX_LOG_LINES);
+        result
+    }
+
+    // Ограничения-равенства учитываются штрафом; внутренние задачи решает
+    // градиентный спуск с выбранным одномерным поиском
+    fn run_penalty(
+        &self,
+        start: DVector<f64>,
+        objective: &Objective,
+        constraints: &[ParsedFunction],
+        penalty: &PenaltyParams,
+        stop_flag: Arc<AtomicBool>,
+    ) -> PenaltyResult {
+        let f = |x: &DVector<f64>| objective.eval(x);
+        let grad = |x: &DVector<f64>| objective.gradient(x);
+        let (mode, eps) = (objective.mode, objective.eps);
+        let values: Vec<_> = constraints
+            .iter()
+            .map(|c| move |x: &DVector<f64>| c.eval(x).unwrap_or(f64::NAN))
+            .collect();
+        let gradients: Vec<_> = constraints
+            .iter()
+            .map(|c| {
+                move |x: &DVector<f64>| {
+                    c.gradient(x, eps, mode)
+                        .unwrap_or_else(|_| DVector::from_element(x.len(), f64::NAN))
+                }
+            })
+            .collect();
+        let pairs: Vec<(&optimizer::ObjectiveFn, &optimizer::GradientFn)> = values
+            .iter()
+            .zip(&gradients)
+            .map(|(v, g)| (v as &optimizer::ObjectiveFn, g as &optimizer::GradientFn))
+            .collect();
+        let line_search = self.line_search.build(&self.params);
+        let mut output = optimizer::penalty_minimize(
+            start,
+            &f,
+            &grad,
+            &pairs,
+            &self.params,
+            line_search.as_ref(),
+            penalty,
+            stop_flag,
+        );
+        if objective.maximize {
+            output.result.negate_objective();
+        }
+        output
+    }
+}
+
+// Целевая функция; при двух критериях — их взвешенная сумма.
+// При максимизации методы минимизируют -f
+#[derive(Clone)]
+struct Objective {
+    main: ParsedFunction,
+    second: Option<(ParsedFunction, f64)>,
+    // Невязки, если функция задана как сумма их квадратов
+    residuals: Option<LeastSquares>,
+    mode: GradientMode,
+    // Шаг конечных разностей для численного градиента
+    eps: f64,
+    maximize: bool,
+}
+
+impl Objective {
+    fn sign(&self) -> f64 {
+        if self.maximize {
+            -1.0
+        } else {
+            1.0
+        }
+    }
+
+    // Ошибка вычисления превращается в NaN: методы останавливаются на нём
+    // сами, а паника в рабочем потоке оставила бы интерфейс без результата
+    fn eval(&self, x: &DVector<f64>) -> f64 {
+        let eval = |func: &ParsedFunction| func.eval(x).unwrap_or(f64::NAN);
+        let value = match &self.second {
+            Some((f2, w)) => optimizer::weighted_sum(*w, || eval(&self.main), || eval(f2)),
+            None => eval(&self.main),
+        };
+        self.sign() * value
+    }
+
+    fn gradient(&self, x: &DVector<f64>) -> DVector<f64> {
+        let gradient = |func: &ParsedFunction| {
+            func.gradient(x, self.eps, self.mode)
+                .unwrap_or_else(|_| DVector::from_element(x.len(), f64::NAN))
+        };
+        let g = match &self.second {
+            Some((f2, w)) => optimizer::weighted_sum(*w, || gradient(&self.main), || gradient(f2)),
+            None => gradient(&self.main),
+        };
+        self.sign() * g
+    }
+
+    // Аналитическая матрица Гессе, если все слагаемые удалось продифференцировать дважды
+    fn analytic_hessian(&self) -> bool {
+        self.main.has_analytic_hessian()
+            && self
+                .second
+                .as_ref()
+                .is_none_or(|(f2, _)| f2.has_analytic_hessian())
+    }
+
+    // Шаг 1e-4: при меньшем шаге вторые разности тонут в ошибках округления
+    fn hessian(&self, x: &DVector<f64>) -> DMatrix<f64> {
+        let analytic = self.analytic_hessian();
+        let hessian = |func: &ParsedFunction| {
+            let h = if analytic {
+                func.eval_hessian(x)
+            } else {
+                func.hessian(x, 1e-4)
+            };
+            h.unwrap_or_else(|_| DMatrix::from_element(x.len(), x.len(), f64::NAN))
+        };
+        let h = match &self.second {
+            Some((f2, w)) => optimizer::weighted_sum(*w, || hessian(&self.main), || hessian(f2)),
+            None => hessian(&self.main),
+        };
+        self.sign() * h
+    }
+}
+
+// Линии уровня, построенные для текущего пути спуска
+struct ContourCache {
+    resolution: usize,
+    // x_min, x_max, y_min, y_max
+    bounds: [f64; 4],
+    lines: Vec<(f64, Vec<Segment>)>,
+}
+
+// Сетка значений для вида поверхности, пересчитывается так же, как линии уровня
+struct SurfaceCache {
+    resolution: usize,
+    bounds: [f64; 4],
+    surface: Surface,
+}
+
+// Зафиксированный прогон для сравнения с последующими
+#[derive(Clone, Serialize, Deserialize)]
+struct ComparisonRun {
+    name: String,
+    note: String,
+    method: Algorithm,
+    result: OptimizerResult,
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum HeatmapMetric {
+    FinalValue,
+    Iterations,
+}
+
+// Одиночный запуск присылает промежуточную точку раз в столько итераций
+const PROGRESS_EVERY: usize = 10;
+// Наибольшее число строк подробного лога в результате
+const MAX_LOG_LINES: usize = 5000;
+// Ключ хранилища eframe для выбранной темы
+const THEME_KEY: &str = "theme";
+
+// Строка рейтинга автоматического решения
+struct RankedRun {
+    method: Algorithm,
+    f_x: f64,
+    iterations: usize,
+    evaluations: usize,
+}
+
+// Сообщения, которые рабочий поток отправляет в GUI
+enum WorkerOutput {
+    // Промежуточная точка одиночного запуска; остальные сообщения завершают запуск
+    Progress {
+        iter: usize,
+        point: DVector<f64>,
+        f_x: f64,
+    },
+    Single(OptimizerResult),
+    Pareto(Vec<ParetoPoint>),
+    Sensitivity(SensitivityMap),
+    Tuning(Option<(TuneReport, OptimizerResult)>),
+    // Метод, результат и число израсходованных вычислений
+    Portfolio(Vec<(Algorithm, OptimizerResult, usize)>),
+    Pair(Box<[(Algorithm, OptimizerResult); 2]>),
+    MultiStart(Vec<OptimizerResult>),
+    Penalty(PenaltyResult),
+    Restarts(Option<RestartReport>),
+}
+
+// Входные параметры задачи и метода; сохраняются в файл запуска вместе с результатом.
+// Отсутствующие в файле поля берутся по умолчанию
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+struct RunConfig {
+    algorithm: Algorithm,
+    coords: CoordinateSystem,
+    func_str: String,
+    least_squares_mode: bool,
+    residuals_str: String,
+    maximize: bool,
+    gradient_mode: GradientMode,
+    grad_eps: f64,
+    line_search: LineSearchKind,
+    num_vars: usize,
+    initial_point_str: String,
+    initial_step: f64,
+    // Начальный шаг оценивается по f и ∇f в начальной точке
+    auto_step: bool,
+    step_decay: f64,
+    max_backtracks: usize,
+    step_increase: f64,
+    max_step: f64,
+    armijo_c1: f64,
+    tolerance: f64,
+    ftol: f64,
+    xtol: f64,
+    restart_period: usize,
+    restart_doubling: bool,
+    max_grad_norm: f64,
+    max_iterations: usize,
+    max_history_points: usize,
+    verbose_log: bool,
+    detect_flat: bool,
+    scale_tolerance: bool,
+    rel_grad: bool,
+    normalize_objective: bool,
+    trust_radius: f64,
+    max_trust_radius: f64,
+    momentum: f64,
+    nesterov: bool,
+    adam: AdamParams,
+    rmsprop: RmsPropParams,
+    nelder_mead: NelderMeadParams,
+    annealing: AnnealingParams,
+    restarts: RestartParams,
+    bb_variant: BbVariant,
+    lbfgs_memory: usize,
+    powell_restart: bool,
+    powell_threshold: f64,
+    perturb_enabled: bool,
+    fixed_seed: bool,
+    seed: u64,
+    // Стандартное отклонение сдвига начальной точки для «Сдвинуть и запустить»
+    perturb_sigma: f64,
+    perturb: PerturbParams,
+    lower_bounds_str: String,
+    upper_bounds_str: String,
+    known_optimum_str: String,
+    multi_objective: bool,
+    func2_str: String,
+    weight: f64,
+    constraints_str: String,
+    penalty: PenaltyParams,
+}
+
+impl Default for RunConfig {
+    fn default() -> Self {
+        GradientDescentApp::default().run_config()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedRun {
+    config: RunConfig,
+    result: Option<OptimizerResult>,
+    // В файлах старых запусков список сравнения отсутствует
+    #[serde(default)]
+    comparison: Vec<ComparisonRun>,
+}
+
 pub struct GradientDescentApp {
     // Входные данные
+    algorithm: Algorithm,
+    // Второй метод для парного сравнения
+    compare_algorithm: Algorithm,
+    coords: CoordinateSystem,
     func_str: String,
+    least_squares_mode: bool,
+    residuals_str: String,
+    maximize: bool,
+    gradient_mode: GradientMode,
+    grad_eps: f64,
+    line_search: LineSearchKind,
     num_vars: usize,
     initial_point_str: String,
     initial_step: f64,
+    // Начальный шаг оценивается по f и ∇f в начальной точке
+    auto_step: bool,
     step_decay: f64,
+    max_backtracks: usize,
     step_increase: f64,
+    max_step: f64,
+    armijo_c1: f64,
     tolerance: f64,
+    ftol: f64,
+    xtol: f64,
+    restart_period: usize,
+    restart_doubling: bool,
+    max_grad_norm: f64,
     max_iterations: usize,
+    max_history_points: usize,
+    verbose_log: bool,
+    detect_flat: bool,
+    scale_tolerance: bool,
+    rel_grad: bool,
+    normalize_objective: bool,
+    trust_radius: f64,
+    max_trust_radius: f64,
+    momentum: f64,
+    nesterov: bool,
+    adam: AdamParams,
+    rmsprop: RmsPropParams,
+    nelder_mead: NelderMeadParams,
+    annealing: AnnealingParams,
+    restarts: RestartParams,
+    bb_variant: BbVariant,
+    lbfgs_memory: usize,
+    powell_restart: bool,
+    powell_threshold: f64,
+    perturb_enabled: bool,
+    fixed_seed: bool,
+    seed: u64,
+    // Стандартное отклонение сдвига начальной точки для «Сдвинуть и запустить»
+    perturb_sigma: f64,
+    perturb: PerturbParams,
+    lower_bounds_str: String,
+    upper_bounds_str: String,
+    known_optimum_str: String,
+
+    // Многокритериальная задача
+    multi_objective: bool,
+    func2_str: String,
+    weight: f64,
+    pareto_weights: usize,
+
+    // Ограничения-равенства g(x) = 0, по одному в строке
+    constraints_str: String,
+    penalty: PenaltyParams,
+    constraint_violations: Vec<f64>,
+
+    // Карта чувствительности к начальной точке: x_min, x_max, y_min, y_max
+    heatmap_bounds: [f64; 4],
+    heatmap_resolution: usize,
+    heatmap_metric: HeatmapMetric,
+
+    // Отображение
+    theme: Theme,
+    equal_axes: bool,
+    plot_aspect: f32,
+    // Неизменная рамка графика пути x_min, x_max, y_min, y_max для сравнения запусков
+    fixed_frame: bool,
+    frame_bounds: [f64; 4],
+    // Вместо пути спуска показывается f(x) по итерациям
+    convergence_plot: bool,
+    log_scale_f: bool,
+    show_contours: bool,
+    contour_resolution: usize,
+    // Вместо плоского пути — поверхность f(x1, x2) с траекторией поверх неё
+    surface_view: bool,
+    surface_resolution: usize,
+    surface_yaw: f64,
+    surface_pitch: f64,
+    // Пошаговый показ пути: индекс последней видимой точки history
+    // (None — путь целиком) и скорость в итерациях в секунду
+    animation_frame: Option<usize>,
+    animation_playing: bool,
+    animation_speed: f64,
+    animation_clock: f64,
+
+    lang: Lang,
 
     // Состояние
     state: OptimizerState,
     result: Option<OptimizerResult>,
+    pareto_front: Option<Vec<ParetoPoint>>,
+    // Результаты запусков из нескольких начальных точек
+    multi_start: Vec<OptimizerResult>,
+    // Итоговые точки случайных перезапусков; лучший запуск хранится в result
+    restart_finals: Vec<(DVector<f64>, f64)>,
+    contours: Option<ContourCache>,
+    surface: Option<SurfaceCache>,
+    sensitivity: Option<SensitivityMap>,
+    tuning: Option<TuneReport>,
+    ranking: Vec<RankedRun>,
+    // Общий предел вычислений f и ∇f на метод при автоматическом решении
+    // и предел, с которым получен текущий рейтинг; 0 — без предела
+    portfolio_budget: usize,
+    ranking_budget: usize,
+    comparison: Vec<ComparisonRun>,
+    // Точки, присланные рабочим потоком до получения результата
+    live_path: Vec<(usize, DVector<f64>, f64)>,
+    // Результат, который продолжается текущим запуском
+    resume_from: Option<OptimizerResult>,
+    // Контекст для перерисовки окна из рабочего потока
+    repaint_ctx: Option<egui::Context>,
+    // Метод, которым получен текущий результат
+    last_method: Algorithm,
+    // Способ вычисления матрицы Гессе для методов, которые её используют
+    hessian_source: Option<&'static str>,
+    // Зерно сдвига начальной точки для следующего запуска и подпись
+    // траектории, полученной из сдвинутой точки
+    perturb_seed: Option<u64>,
+    path_label: Option<String>,
+    // Шаг, выбранный автоматически для последнего запуска
+    estimated_step: Option<f64>,
+    // Столбец, по которому упорядочена таблица итераций, и порядок по убыванию
+    iterates_sort: (usize, bool),
     error_message: Option<String>,
+    // Предупреждение о параметрах, не мешающее запуску
+    warning_message: Option<String>,
+    // Файл для снимка графика пути и область графика на экране в кадре снимка
+    png_path: Option<std::path::PathBuf>,
+    png_rect: Option<egui::Rect>,
     stop_flag: Arc<AtomicBool>,
 
     // Канал для получения результата из потока
-    result_receiver: Option<Receiver<OptimizerResult>>,
-    result_sender: Option<Sender<OptimizerResult>>,
+    result_receiver: Option<Receiver<WorkerOutput>>,
+    result_sender: Option<Sender<WorkerOutput>>,
 
     // Парсер
     parsed_func: Option<ParsedFunction>,
+    active_bounds: Option<Bounds>,
+    // Расхождение аналитического градиента с численным в начальной точке
+    gradient_check: Option<Result<f64, String>>,
 }
 
 impl Default for GradientDescentApp {
     fn default() -> Self {
         let (tx, rx) = mpsc::channel();
         Self {
+            algorithm: Algorithm::GradientDescent,
+            compare_algorithm: Algorithm::Bfgs,
+            coords: CoordinateSystem::Cartesian,
             func_str: "x1^2 + x2^2".to_string(),
+            least_squares_mode: false,
+            residuals_str: "x1 - 1\nx2 - 2".to_string(),
+            maximize: false,
+            gradient_mode: GradientMode::Central,
+            grad_eps: 1e-6,
+            line_search: LineSearchKind::Armijo,
             num_vars: 2,
             initial_point_str: "2, 2".to_string(),
             initial_step: 1.0,
+            auto_step: false,
             step_decay: 0.5,
+            max_backtracks: 20,
             step_increase: 1.2,
+            max_step: 1.0,
+            armijo_c1: 1e-4,
             tolerance: 1e-6,
+            ftol: 0.0,
+            xtol: 0.0,
+            restart_period: 0,
+            restart_doubling: false,
+            max_grad_norm: 0.0,
             max_iterations: 1000,
+            max_history_points: 0,
+            verbose_log: false,
+            detect_flat: false,
+            scale_tolerance: false,
+            rel_grad: false,
+            normalize_objective: false,
+            trust_radius: 1.0,
+            max_trust_radius: 100.0,
+            momentum: 0.9,
+            nesterov: false,
+            adam: AdamParams::default(),
+            rmsprop: RmsPropParams::default(),
+            nelder_mead: NelderMeadParams::default(),
+            annealing: AnnealingParams::default(),
+            restarts: RestartParams::default(),
+            bb_variant: BbVariant::Bb1,
+            lbfgs_memory: 10,
+            powell_restart: false,
+            powell_threshold: 0.2,
+            perturb_enabled: false,
+            fixed_seed: true,
+            seed: 42,
+            perturb_sigma: 0.1,
+            perturb: PerturbParams::default(),
+            lower_bounds_str: String::new(),
+            upper_bounds_str: String::new(),
+            known_optimum_str: String::new(),
+            multi_objective: false,
+            func2_str: "(x1 - 2)^2 + (x2 - 1)^2".to_string(),
+            weight: 0.5,
+            pareto_weights: 11,
+            constraints_str: String::new(),
+            penalty: PenaltyParams::default(),
+            constraint_violations: Vec::new(),
+            heatmap_bounds: [-3.0, 3.0, -3.0, 3.0],
+            heatmap_resolution: 15,
+            heatmap_metric: HeatmapMetric::FinalValue,
+            theme: Theme::System,
+            equal_axes: true,
+            plot_aspect: 1.0,
+            fixed_frame: false,
+            frame_bounds: [-3.0, 3.0, -3.0, 3.0],
+            convergence_plot: false,
+            log_scale_f: false,
+            show_contours: true,
+            contour_resolution: 60,
+            surface_view: false,
+            surface_resolution: 40,
+            surface_yaw: 35.0,
+            surface_pitch: 35.0,
+            animation_frame: None,
+            animation_playing: false,
+            animation_speed: 5.0,
+            animation_clock: 0.0,
+            lang: Lang::default(),
             state: OptimizerState::Idle,
             result: None,
+            pareto_front: None,
+            multi_start: Vec::new(),
+            restart_finals: Vec::new(),
+            contours: None,
+            surface: None,
+            sensitivity: None,
+            tuning: None,
+            ranking: Vec::new(),
+            portfolio_budget: 10_000,
+            ranking_budget: 0,
+            comparison: Vec::new(),
+            live_path: Vec::new(),
+            resume_from: None,
+            repaint_ctx: None,
+            last_method: Algorithm::GradientDescent,
+            hessian_source: None,
+            perturb_seed: None,
+            path_label: None,
+            estimated_step: None,
+            iterates_sort: (0, false),
             error_message: None,
+            warning_message: None,
+            png_path: None,
+            png_rect: None,
             stop_flag: Arc::new(AtomicBool::new(false)),
             result_receiver: Some(rx),
             result_sender: Some(tx),
             parsed_func: None,
+            gradient_check: None,
+            active_bounds: None,
         }
     }
 }
 
 impl GradientDescentApp {
-    fn parse_initial_point(&self) -> Option<DVector<f64>> {
-        let parts: Vec<&str> = self.initial_point_str.split(',').collect();
+    // Начальные точки по одной на строку; для одиночных режимов берётся первая
+    fn parse_initial_point(&self) -> Result<DVector<f64>, String> {
+        Ok(self.parse_start_points()?.swap_remove(0))
+    }
+
+    // При нескольких строках в сообщении об ошибке указывается номер строки
+    fn parse_start_points(&self) -> Result<Vec<DVector<f64>>, String> {
+        let lang = self.lang;
+        // Всё после '#' считается комментарием
+        let lines: Vec<&str> = self
+            .initial_point_str
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or_default())
+            .filter(|line| !line.trim().is_empty())
+            .collect();
+        if lines.is_empty() {
+            return Err(lang.t("err_no_start_point").to_string());
+        }
+        lines
+            .iter()
+            .enumerate()
+            .map(|(k, line)| {
+                self.parse_point(line).map_err(|e| {
+                    if lines.len() > 1 {
+                        lang.tf("err_line", &[&(k + 1), &e])
+                    } else {
+                        e
+                    }
+                })
+            })
+            .collect()
+    }
+
+    // Допускаются записи вида "2, 2", "2; 2", "[2, 2]" и "( 2 , 2 )"
+    fn parse_point(&self, text: &str) -> Result<DVector<f64>, String> {
+        let lang = self.lang;
+        let mut text = text.trim();
+        for (open, close) in [('[', ']'), ('(', ')')] {
+            if let Some(inner) = text.strip_prefix(open).and_then(|t| t.strip_suffix(close)) {
+                text = inner;
+                break;
+            }
+        }
+        let parts: Vec<&str> = text.split([',', ';']).map(str::trim).collect();
+        if let Some(k) = parts.iter().position(|part| part.is_empty()) {
+            return Err(lang.tf("err_empty_value", &[&(k + 1)]));
+        }
         if parts.len() != self.num_vars {
-            return None;
+            return Err(lang.tf("err_value_count", &[&self.num_vars, &parts.len()]));
         }
         let mut vec = Vec::with_capacity(self.num_vars);
         for part in parts {
-            match part.trim().parse::<f64>() {
+            match part.parse::<f64>() {
                 Ok(val) => vec.push(val),
-                Err(_) => return None,
+                Err(_) => return Err(lang.tf("err_parse_value", &[&part])),
             }
         }
-        Some(DVector::from_vec(vec))
+        Ok(DVector::from_vec(vec))
     }
 
-    fn start_optimization(&mut self) {
+    fn descent_params(&self) -> DescentParams {
+        self.descent_params_for(self.algorithm)
+    }
+
+    fn descent_params_for(&self, algorithm: Algorithm) -> DescentParams {
+        DescentParams {
+            initial_step: self.initial_step,
+            step_decay: self.step_decay,
+            max_backtracks: self.max_backtracks,
+            step_increase: self.step_increase,
+            max_step: self.max_step,
+            armijo_c1: self.armijo_c1,
+            tolerance: self.tolerance,
+            ftol: self.ftol,
+            xtol: self.xtol,
+            restart_period: self.restart_period,
+            restart_doubling: self.restart_doubling,
+            max_grad_norm: self.max_grad_norm,
+            max_iterations: self.max_iterations,
+            max_history_points: self.max_history_points,
+            verbose: self.verbose_log,
+            detect_flat: self.detect_flat,
+            scale_tolerance: self.scale_tolerance,
+            rel_grad: self.rel_grad,
+            normalize_objective: self.normalize_objective,
+            fd_eps: self.grad_eps,
+            periodic: self.coords.periodic(),
+            perturb: (self.perturb_enabled && algorithm == Algorithm::GradientDescent).then(|| {
+                PerturbParams {
+                    seed: self.run_seed(),
+                    ..self.perturb.clone()
+                }
+            }),
+            bounds: if algorithm.supports_bounds() {
+                self.active_bounds.clone()
+            } else {
+                None
+            },
+            progress: None,
+        }
+    }
+
+    // Пустая строка означает отсутствие ограничения по всем переменным
+    fn parse_bound_list(&self, text: &str, default: f64) -> Result<Vec<f64>, String> {
+        let lang = self.lang;
+        if text.trim().is_empty() {
+            return Ok(vec![default; self.num_vars]);
+        }
+        let values = text
+            .split(',')
+            .map(|part| {
+                part.trim()
+                    .parse::<f64>()
+                    .map_err(|_| lang.tf("err_parse_value", &[&part.trim()]))
+            })
+            .collect::<Result<Vec<f64>, String>>()?;
+        if values.len() != self.num_vars {
+            return Err(lang.tf("err_value_count", &[&self.num_vars, &values.len()]));
+        }
+        Ok(values)
+    }
+
+    // Разбор ограничений из полей ввода; None, если все границы бесконечны
+    fn parse_bounds(&self) -> Result<Option<Bounds>, String> {
+        let lang = self.lang;
+        let lower = self.parse_bound_list(&self.lower_bounds_str, f64::NEG_INFINITY)?;
+        let upper = self.parse_bound_list(&self.upper_bounds_str, f64::INFINITY)?;
+        for i in 0..self.num_vars {
+            if lower[i] > upper[i] {
+                return Err(lang.tf("err_bound_order", &[&(i + 1)]));
+            }
+        }
+        if lower.iter().chain(upper.iter()).all(|v| v.is_infinite()) {
+            return Ok(None);
+        }
+        Ok(Some(Bounds {
+            lower: DVector::from_vec(lower),
+            upper: DVector::from_vec(upper),
+        }))
+    }
+
+    // Перенос ограничений из строки функции в поля границ
+    fn apply_inline_bounds(&mut self, bounds: &[parser::InlineBound]) -> Result<(), String> {
+        if bounds.is_empty() {
+            return Ok(());
+        }
+        let mut lower = self.parse_bound_list(&self.lower_bounds_str, f64::NEG_INFINITY)?;
+        let mut upper = self.parse_bound_list(&self.upper_bounds_str, f64::INFINITY)?;
+        for bound in bounds {
+            if let Some(lo) = bound.lower {
+                lower[bound.var_index] = lo;
+            }
+            if let Some(hi) = bound.upper {
+                upper[bound.var_index] = hi;
+            }
+        }
+        let join = |values: &[f64]| {
+            values
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        self.lower_bounds_str = join(&lower);
+        self.upper_bounds_str = join(&upper);
+        Ok(())
+    }
+
+    // Зерно для случайных методов: заданное пользователем или новое при каждом запуске
+    fn run_seed(&self) -> u64 {
+        if self.fixed_seed {
+            self.seed
+        } else {
+            rand::random()
+        }
+    }
+
+    fn method_config(&self) -> MethodConfig {
+        self.method_config_for(self.algorithm)
+    }
+
+    fn method_config_for(&self, algorithm: Algorithm) -> MethodConfig {
+        MethodConfig {
+            algorithm,
+            params: self.descent_params_for(algorithm),
+            line_search: self.line_search,
+            tr_params: TrustRegionParams {
+                initial_radius: self.trust_radius,
+                max_radius: self.max_trust_radius,
+                ..Default::default()
+            },
+            momentum_params: MomentumParams {
+                momentum: self.momentum,
+                nesterov: self.nesterov,
+            },
+            adam_params: self.adam.clone(),
+            rmsprop_params: self.rmsprop.clone(),
+            nm_params: self.nelder_mead.clone(),
+            sa_params: AnnealingParams {
+                seed: self.run_seed(),
+                ..self.annealing.clone()
+            },
+            bb_variant: self.bb_variant,
+            lbfgs_memory: self.lbfgs_memory,
+            powell_threshold: self.powell_restart.then_some(self.powell_threshold),
+        }
+    }
+
+    // Сравнение символьного градиента с центральной разностью в начальной точке,
+    // без запуска оптимизации
+    fn check_gradient(&self) -> Result<f64, String> {
+        let lang = self.lang;
+        let names = self.coords.var_names(self.num_vars);
+        let (objective, _, _) = parser::split_inline_bounds(&self.func_str, &names);
+        let func = self
+            .parse_function(&objective)
+            .map_err(|e| lang.tf("err_function", &[&e]))?;
+        let x = self
+            .parse_initial_point()
+            .map_err(|e| lang.tf("err_start_point", &[&e]))?;
+        func.check_gradient(&x, self.grad_eps)
+            .map_err(|e| e.to_string())
+    }
+
+    // Гаусс — Ньютон доступен только в режиме наименьших квадратов
+    fn algorithms(&self) -> Vec<Algorithm> {
+        Algorithm::ALL
+            .into_iter()
+            .filter(|&a| a != Algorithm::GaussNewton || self.least_squares_mode)
+            .collect()
+    }
+
+    // Невязки режима наименьших квадратов; при ошибке разбора None, а саму
+    // ошибку покажет разбор суммы их квадратов
+    fn least_squares(&self) -> Option<LeastSquares> {
+        if !self.least_squares_mode {
+            return None;
+        }
+        LeastSquares::lines(&self.residuals_str)
+            .map(|line| self.parse_function(line))
+            .collect::<Result<Vec<_>, _>>()
+            .ok()
+            .map(LeastSquares::new)
+    }
+
+    // Компоненты градиента в начальной точке; ошибка — ключ строки интерфейса
+    fn partial_derivatives(&self) -> Result<Vec<f64>, &'static str> {
+        let names = self.coords.var_names(self.num_vars);
+        let (objective, _, _) = parser::split_inline_bounds(&self.func_str, &names);
+        let func = self
+            .parse_function(&objective)
+            .map_err(|_| "derivs_parse_error")?;
+        let x = self
+            .parse_initial_point()
+            .map_err(|_| "derivs_point_error")?;
+        let g = func
+            .gradient(&x, self.grad_eps, self.gradient_mode)
+            .map_err(|_| "derivs_eval_error")?;
+        Ok(g.iter().copied().collect())
+    }
+
+    fn parse_function(&self, expr: &str) -> Result<ParsedFunction, ParserError> {
+        if self.coords == CoordinateSystem::Cartesian {
+            return ParsedFunction::new(expr, self.num_vars);
+        }
+        let names = self.coords.var_names(self.num_vars);
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        ParsedFunction::with_vars(expr, &names)
+    }
+
+    // Разбор функции и начальной точки; при ошибке выставляет сообщение
+    fn prepare_run(&mut self) -> Option<(ParsedFunction, DVector<f64>)> {
+        let lang = self.lang;
         self.error_message = None;
         self.stop_flag.store(false, Ordering::SeqCst);
         self.result = None;
+        self.hessian_source = None;
+        self.path_label = None;
+        self.estimated_step = None;
+        self.live_path.clear();
+        self.constraint_violations.clear();
+        self.pareto_front = None;
+        self.multi_start.clear();
+        self.restart_finals.clear();
+        self.sensitivity = None;
+        self.tuning = None;
+        self.ranking.clear();
+        self.stop_animation();
+
+        // Ограничения могут быть записаны в строке функции через ';'
+        let names = self.coords.var_names(self.num_vars);
+        let (objective, inline_bounds, errors) =
+            parser::split_inline_bounds(&self.func_str, &names);
+        if !errors.is_empty() {
+            self.error_message = Some(lang.tf("err_constraints", &[&errors.join("; ")]));
+            self.state = OptimizerState::Idle;
+            return None;
+        }
+        if let Err(e) = self.apply_inline_bounds(&inline_bounds) {
+            self.error_message = Some(lang.tf("err_constraints", &[&e]));
+            self.state = OptimizerState::Idle;
+            return None;
+        }
+        self.active_bounds = match self.parse_bounds() {
+            Ok(bounds) => bounds,
+            Err(e) => {
+                self.error_message = Some(lang.tf("err_constraints", &[&e]));
+                self.state = OptimizerState::Idle;
+                return None;
+            }
+        };
 
         // Парсим функцию
-        let parsed = match ParsedFunction::new(&self.func_str, self.num_vars) {
+        let parsed = match self.parse_function(&objective) {
             Ok(func) => func,
             Err(e) => {
-                self.error_message = Some(format!("Ошибка в функции: {}", e));
+                self.error_message = Some(lang.tf("err_function", &[&e]));
                 self.state = OptimizerState::Idle;
-                return;
+                return None;
             }
         };
 
         self.parsed_func = Some(parsed.clone());
+        self.contours = None;
 
         let start_point = match self.parse_initial_point() {
-            Some(p) => p,
-            None => {
-                self.error_message =
-                    Some("Ошибка в начальной точке. Используйте формат 'x1, x2'".to_string());
+            Ok(p) => p,
+            Err(e) => {
+                self.error_message = Some(lang.tf("err_start_point", &[&e]));
                 self.state = OptimizerState::Idle;
-                return;
+                return None;
             }
         };
+        // Иначе метод остановился бы на первой же итерации без понятной причины
+        if start_value(&parsed, &start_point).is_none() {
+            self.error_message = Some(lang.t("err_start_not_finite").to_string());
+            self.state = OptimizerState::Idle;
+            return None;
+        }
 
-        let sender = self.result_sender.take().expect("Sender already taken");
-        let stop_flag_clone = self.stop_flag.clone();
-
-        let initial_step = self.initial_step;
-        let step_decay = self.step_decay;
-        let step_increase = self.step_increase;
-        let tolerance = self.tolerance;
-        let max_iterations = self.max_iterations;
-
-        self.state = OptimizerState::Running;
-
-        let parsed_for_f = parsed.clone();
-        let parsed_for_grad = parsed.clone();
-
-        std::thread::spawn(move || {
-            let f = move |x: &DVector<f64>| parsed_for_f.eval(x).unwrap();
-
-            let grad = move |x: &DVector<f64>| parsed_for_grad.gradient(x, 1e-6).unwrap();
-
-            let result = optimizer::gradient_descent(
-                start_point,
-                &f,
-                &grad,
-                initial_step,
-                step_decay,
-                step_increase,
-                tolerance,
-                max_iterations,
-                stop_flag_clone,
-            );
-
-            let _ = sender.send(result);
-        });
-    }
+        if let Err(e) = self.descent_params().validate() {
+            self.error_message = Some(lang.tf("err_params", &[&e]));
+            self.state = OptimizerState::Idle;
+            return None;
+        }
+        // Без роста шаг после дробления остаётся малым до конца спуска
+        self.warning_message = (self.step_increase <= 1.0)
+            .then(|| lang.tf("warn_step_increase", &[&self.step_increase]));
 
-    fn stop_optimization(&mut self) {
-        self.stop_flag.store(true, Ordering::SeqCst);
-        self.state = OptimizerState::Stopping;
+        Some((parsed, start_point))
     }
 
-    fn check_for_result(&mut self) {
-        if let Some(rx) = &self.result_receiver {
-            if let Ok(res) = rx.try_recv() {
-                self.result = Some(res);
-                self.state = OptimizerState::Finished;
-                let (tx, new_rx) = mpsc::channel();
-                self.result_sender = Some(tx);
-                self.result_receiver = Some(new_rx);
+    fn parse_second_function(&mut self) -> Option<ParsedFunction> {
+        let lang = self.lang;
+        match self.parse_function(&self.func2_str) {
+            Ok(func) => Some(func),
+            Err(e) => {
+                self.error_message = Some(lang.tf("err_second_function", &[&e]));
+                self.state = OptimizerState::Idle;
+                None
             }
         }
     }
-}
 
-impl eframe::App for GradientDescentApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+    // Пустые строки пропускаются
+    fn parse_constraints(&self) -> Result<Vec<ParsedFunction>, String> {
+        let lang = self.lang;
+        self.constraints_str
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(k, line)| {
+                self.parse_function(line.trim())
+                    .map_err(|e| lang.tf("err_constraint", &[&(k + 1), &e]))
+            })
+            .collect()
+    }
+
+    fn start_optimization(&mut self) {
+        let lang = self.lang;
+        let perturb_seed = self.perturb_seed.take();
+        let Some((parsed, start_point)) = self.prepare_run() else {
+            return;
+        };
+        let mut starts = match self.parse_start_points() {
+            Ok(starts) => starts,
+            Err(e) => {
+                self.error_message = Some(lang.tf("err_start_point", &[&e]));
+                self.state = OptimizerState::Idle;
+                return;
+            }
+        };
+        let mut start_point = start_point;
+        match &self.resume_from {
+            Some(previous) if previous.x.len() == start_point.len() => {
+                start_point = previous.x.clone();
+                starts = vec![start_point.clone()];
+            }
+            _ => self.resume_from = None,
+        }
+
+        let second = if self.multi_objective {
+            match self.parse_second_function() {
+                Some(func) => Some((func, self.weight)),
+                None => return,
+            }
+        } else {
+            None
+        };
+        let constraints = match self.parse_constraints() {
+            Ok(constraints) => constraints,
+            Err(e) => {
+                self.error_message = Some(e);
+                self.state = OptimizerState::Idle;
+                return;
+            }
+        };
+
+        let sender = self.result_sender.take().expect("Sender already taken");
+        let stop_flag_clone = self.stop_flag.clone();
+        let mut method = self.method_config();
+        if let Some(seed) = perturb_seed {
+            start_point = optimizer::perturb_point(
+                &start_point,
+                self.perturb_sigma,
+                seed,
+                self.active_bounds.as_ref(),
+            );
+            starts = vec![start_point.clone()];
+            let point = start_point
+                .iter()
+                .map(|v| format!("{:.4}", v))
+                .collect::<Vec<_>>()
+                .join(", ");
+            self.path_label = Some(lang.tf("perturbed_run", &[&self.perturb_sigma, &point]));
+        }
+        let objective = Objective {
+            main: parsed,
+            second,
+            residuals: self.least_squares(),
+            mode: self.gradient_mode,
+            eps: self.grad_eps,
+            maximize: self.maximize,
+        };
+        if self.auto_step {
+            let f = |x: &DVector<f64>| objective.eval(x);
+            let grad = |x: &DVector<f64>| objective.gradient(x);
+            if let Some(step) = optimizer::estimate_initial_step(&start_point, &f, &grad) {
+                // Иначе дробление сразу урезало бы оценку до прежней границы
+                method.params.initial_step = step;
+                method.params.max_step = method.params.max_step.max(step);
+                self.estimated_step = Some(step);
+            }
+        }
+        let repaint_ctx = self.repaint_ctx.clone();
+        if starts.len() == 1 && constraints.is_empty() {
+            let sender = sender.clone();
+            let repaint_ctx = repaint_ctx.clone();
+            method.params.progress = Some(Progress {
+                every: PROGRESS_EVERY,
+                callback: Arc::new(move |iter, point, f_x| {
+                    let point = point.clone();
+                    let _ = sender.send(WorkerOutput::Progress { iter, point, f_x });
+                    if let Some(ctx) = &repaint_ctx {
+                        ctx.request_repaint();
+                    }
+                }),
+            });
+        }
+        let penalty = self.penalty.clone();
+        self.last_method = if constraints.is_empty() {
+            self.algorithm
+        } else {
+            Algorithm::GradientDescent
+        };
+        let uses_hessian = match self.algorithm {
+            Algorithm::Newton | Algorithm::TrustRegionDogleg => true,
+            Algorithm::GaussNewton => objective.residuals.is_none(),
+            _ => false,
+        };
+        self.hessian_source = (constraints.is_empty() && uses_hessian).then(|| {
+            if objective.analytic_hessian() {
+                "hessian_analytic"
+            } else {
+                "hessian_numeric"
+            }
+        });
+
+        self.state = OptimizerState::Running;
+
+        std::thread::spawn(move || {
+            if !constraints.is_empty() {
+                let output = method.run_penalty(
+                    start_point,
+                    &objective,
+                    &constraints,
+                    &penalty,
+                    stop_flag_clone,
+                );
+                let _ = sender.send(WorkerOutput::Penalty(output));
+                return;
+            }
+            if starts.len() == 1 {
+                let result = method.run(start_point, &objective, stop_flag_clone);
+                let _ = sender.send(WorkerOutput::Single(result));
+                if let Some(ctx) = &repaint_ctx {
+                    ctx.request_repaint();
+                }
+                return;
+            }
+
+            let results = optimizer::multi_start(
+                starts,
+                || objective.clone(),
+                |objective, start| method.run(start, objective, stop_flag_clone.clone()),
+                &stop_flag_clone,
+            );
+            let _ = sender.send(WorkerOutput::MultiStart(results));
+        });
+    }
+
+    // Новый запуск из x* предыдущего с тем же лимитом итераций; история
+    // и счётчики добавляются к прежним
+    fn continue_optimization(&mut self) {
+        self.resume_from = self.result.take();
+        self.start_optimization();
+        if self.state != OptimizerState::Running {
+            self.result = self.resume_from.take();
+        }
+    }
+
+    // Случайные перезапуски выбранного метода из точек в [lower, upper]^n
+    fn start_random_restarts(&mut self) {
+        let Some((parsed, start_point)) = self.prepare_run() else {
+            return;
+        };
+
+        let second = if self.multi_objective {
+            match self.parse_second_function() {
+                Some(func) => Some((func, self.weight)),
+                None => return,
+            }
+        } else {
+            None
+        };
+
+        let sender = self.result_sender.take().expect("Sender already taken");
+        let stop_flag_clone = self.stop_flag.clone();
+        let method = self.method_config();
+        let objective = Objective {
+            main: parsed,
+            second,
+            residuals: self.least_squares(),
+            mode: self.gradient_mode,
+            eps: self.grad_eps,
+            maximize: self.maximize,
+        };
+        let restarts = RestartParams {
+            seed: self.run_seed(),
+            ..self.restarts.clone()
+        };
+        self.last_method = self.algorithm;
+
+        self.state = OptimizerState::Running;
+
+        std::thread::spawn(move || {
+            // Выбор лучшего идёт по минимизируемой функции, т. е. по -f при максимизации
+            let maximize = objective.maximize;
+            let mut report = optimizer::random_restart(
+                start_point.len(),
+                &restarts,
+                || objective.clone(),
+                |objective, start| {
+                    let mut result = method.run(start, objective, stop_flag_clone.clone());
+                    if maximize {
+                        result.negate_objective();
+                    }
+                    result
+                },
+                &stop_flag_clone,
+            );
+            if let Some(report) = report.as_mut().filter(|_| maximize) {
+                report.best.negate_objective();
+                for (_, f_x) in &mut report.finals {
+                    *f_x = -*f_x;
+                }
+            }
+            let _ = sender.send(WorkerOutput::Restarts(report));
+        });
+    }
+
+    fn start_sensitivity_map(&mut self) {
+        let Some((parsed, _)) = self.prepare_run() else {
+            return;
+        };
+
+        let second = if self.multi_objective {
+            match self.parse_second_function() {
+                Some(func) => Some((func, self.weight)),
+                None => return,
+            }
+        } else {
+            None
+        };
+
+        let sender = self.result_sender.take().expect("Sender already taken");
+        let stop_flag_clone = self.stop_flag.clone();
+        let method = self.method_config();
+        let objective = Objective {
+            main: parsed,
+            second,
+            residuals: self.least_squares(),
+            mode: self.gradient_mode,
+            eps: self.grad_eps,
+            maximize: self.maximize,
+        };
+        let x_range = (self.heatmap_bounds[0], self.heatmap_bounds[1]);
+        let y_range = (self.heatmap_bounds[2], self.heatmap_bounds[3]);
+        let resolution = self.heatmap_resolution;
+
+        self.state = OptimizerState::Running;
+
+        std::thread::spawn(move || {
+            let solve =
+                |start: DVector<f64>| method.run(start, &objective, stop_flag_clone.clone());
+
+            let map = optimizer::start_point_sensitivity(
+                x_range,
+                y_range,
+                resolution,
+                &solve,
+                stop_flag_clone.clone(),
+            );
+
+            let _ = sender.send(WorkerOutput::Sensitivity(map));
+        });
+    }
+
+    // Подбор параметров шага градиентного спуска перебором по сетке
+    fn start_auto_tune(&mut self) {
+        let Some((parsed, start_point)) = self.prepare_run() else {
+            return;
+        };
+
+        let second = if self.multi_objective {
+            match self.parse_second_function() {
+                Some(func) => Some((func, self.weight)),
+                None => return,
+            }
+        } else {
+            None
+        };
+
+        let sender = self.result_sender.take().expect("Sender already taken");
+        let stop_flag_clone = self.stop_flag.clone();
+        let mut params = self.descent_params();
+        params.perturb = None;
+        params.bounds = self.active_bounds.clone();
+        let line_search = self.line_search;
+        let objective = Objective {
+            main: parsed,
+            second,
+            residuals: self.least_squares(),
+            mode: self.gradient_mode,
+            eps: self.grad_eps,
+            maximize: self.maximize,
+        };
+
+        self.state = OptimizerState::Running;
+
+        std::thread::spawn(move || {
+            let f = |x: &DVector<f64>| objective.eval(x);
+            let grad = |x: &DVector<f64>| objective.gradient(x);
+
+            let mut report = optimizer::tune_step_params(
+                start_point,
+                &f,
+                &grad,
+                &params,
+                &|p: &DescentParams| line_search.build(p),
+                stop_flag_clone,
+            );
+            if let Some((report, best)) = report.as_mut().filter(|_| objective.maximize) {
+                for trial in &mut report.trials {
+                    trial.f_x = -trial.f_x;
+                }
+                best.negate_objective();
+            }
+
+            let _ = sender.send(WorkerOutput::Tuning(report));
+        });
+    }
+
+    // Запуск всех методов с одинаковым бюджетом вычислений и выбор лучшего
+    fn start_portfolio(&mut self) {
+        let Some((parsed, start_point)) = self.prepare_run() else {
+            return;
+        };
+
+        let second = if self.multi_objective {
+            match self.parse_second_function() {
+                Some(func) => Some((func, self.weight)),
+                None => return,
+            }
+        } else {
+            None
+        };
+
+        let sender = self.result_sender.take().expect("Sender already taken");
+        let stop_flag_clone = self.stop_flag.clone();
+        let budget = self.portfolio_budget;
+        let methods: Vec<MethodConfig> = self
+            .algorithms()
+            .into_iter()
+            .map(|algorithm| self.method_config_for(algorithm))
+            .collect();
+        let objective = Objective {
+            main: parsed,
+            second,
+            residuals: self.least_squares(),
+            mode: self.gradient_mode,
+            eps: self.grad_eps,
+            maximize: self.maximize,
+        };
+
+        self.state = OptimizerState::Running;
+
+        std::thread::spawn(move || {
+            let mut runs = Vec::with_capacity(methods.len());
+            for method in &methods {
+                if stop_flag_clone.load(Ordering::SeqCst) {
+                    break;
+                }
+                let (result, evaluations) = method.run_with_budget(
+                    start_point.clone(),
+                    &objective,
+                    budget,
+                    stop_flag_clone.clone(),
+                );
+                runs.push((method.algorithm, result, evaluations));
+            }
+
+            let _ = sender.send(WorkerOutput::Portfolio(runs));
+        });
+    }
+
+    // Выбранный метод и метод сравнения из одной начальной точки, параллельно;
+    // общий флаг останавливает оба
+    fn start_pair_comparison(&mut self) {
+        let Some((parsed, start_point)) = self.prepare_run() else {
+            return;
+        };
+
+        let second = if self.multi_objective {
+            match self.parse_second_function() {
+                Some(func) => Some((func, self.weight)),
+                None => return,
+            }
+        } else {
+            None
+        };
+
+        let sender = self.result_sender.take().expect("Sender already taken");
+        let stop_flag_clone = self.stop_flag.clone();
+        let methods = [
+            self.method_config(),
+            self.method_config_for(self.compare_algorithm),
+        ];
+        let objective = Objective {
+            main: parsed,
+            second,
+            residuals: self.least_squares(),
+            mode: self.gradient_mode,
+            eps: self.grad_eps,
+            maximize: self.maximize,
+        };
+
+        self.state = OptimizerState::Running;
+
+        std::thread::spawn(move || {
+            let [first, other] = methods;
+            let other_objective = objective.clone();
+            let other_start = start_point.clone();
+            let other_stop = stop_flag_clone.clone();
+            let (a, b) = std::thread::scope(|scope| {
+                let handle = scope.spawn(|| other.run(other_start, &other_objective, other_stop));
+                let a = first.run(start_point, &objective, stop_flag_clone);
+                (a, handle.join())
+            });
+            // Паника во втором потоке оставляет только первый результат
+            let output = match b {
+                Ok(b) => WorkerOutput::Pair(Box::new([(first.algorithm, a), (other.algorithm, b)])),
+                Err(_) => WorkerOutput::Single(a),
+            };
+            let _ = sender.send(output);
+        });
+    }
+
+    fn start_pareto_sweep(&mut self) {
+        let Some((parsed, start_point)) = self.prepare_run() else {
+            return;
+        };
+        let Some(second) = self.parse_second_function() else {
+            return;
+        };
+
+        let sender = self.result_sender.take().expect("Sender already taken");
+        let stop_flag_clone = self.stop_flag.clone();
+
+        let num_weights = self.pareto_weights;
+        let params = self.descent_params();
+        let line_search = self.line_search;
+        let (mode, eps) = (self.gradient_mode, self.grad_eps);
+
+        self.state = OptimizerState::Running;
+
+        std::thread::spawn(move || {
+            let nan = |x: &DVector<f64>| DVector::from_element(x.len(), f64::NAN);
+            let f1 = |x: &DVector<f64>| parsed.eval(x).unwrap_or(f64::NAN);
+            let f2 = |x: &DVector<f64>| second.eval(x).unwrap_or(f64::NAN);
+            let grad1 = |x: &DVector<f64>| parsed.gradient(x, eps, mode).unwrap_or_else(|_| nan(x));
+            let grad2 = |x: &DVector<f64>| second.gradient(x, eps, mode).unwrap_or_else(|_| nan(x));
+
+            let front = optimizer::weighted_sum_sweep(
+                start_point,
+                &f1,
+                &f2,
+                &grad1,
+                &grad2,
+                num_weights,
+                &params,
+                &|p: &DescentParams| line_search.build(p),
+                stop_flag_clone,
+            );
+
+            let _ = sender.send(WorkerOutput::Pareto(front));
+        });
+    }
+
+    // Перенос текущего результата в список сравнения
+    fn freeze_result(&mut self) {
+        let lang = self.lang;
+        if let Some(result) = self.result.take() {
+            let name = self
+                .path_label
+                .take()
+                .unwrap_or_else(|| lang.tf("run_name", &[&(self.comparison.len() + 1)]));
+            self.comparison.push(ComparisonRun {
+                name,
+                note: String::new(),
+                method: self.last_method,
+                result,
+            });
+        }
+    }
+
+    // Запуск из начальной точки со случайным сдвигом; прежний результат
+    // остаётся на графике в списке сравнения. Номер прогона добавляется
+    // к зерну, чтобы при фиксированном зерне повторные сдвиги различались
+    fn start_perturbed(&mut self) {
+        self.freeze_result();
+        self.reset_result();
+        self.perturb_seed = Some(self.run_seed().wrapping_add(self.comparison.len() as u64));
+        self.start_optimization();
+    }
+
+    // Кнопка «Сдвинуть и запустить» и σ сдвига
+    fn show_perturb_controls(&mut self, ui: &mut egui::Ui) {
+        let lang = self.lang;
+        let mut clicked = false;
+        ui.horizontal(|ui| {
+            clicked = ui
+                .button(lang.t("perturb_run"))
+                .on_hover_text(lang.t("perturb_run_hint"))
+                .clicked();
+            ui.label("σ:");
+            ui.add(
+                egui::DragValue::new(&mut self.perturb_sigma)
+                    .speed(0.01)
+                    .range(1e-6..=1e3),
+            );
+        });
+        if clicked {
+            self.start_perturbed();
+        }
+    }
+
+    // Результат и всё, что к нему относится, сбрасываются; параметры остаются
+    fn reset_result(&mut self) {
+        self.stop_animation();
+        self.result = None;
+        self.constraint_violations.clear();
+        self.pareto_front = None;
+        self.multi_start.clear();
+        self.restart_finals.clear();
+        self.sensitivity = None;
+        self.tuning = None;
+        self.ranking.clear();
+        self.state = OptimizerState::Idle;
+    }
+
+    // Enter или Ctrl+R — запуск, Escape — остановка, Ctrl+Backspace — сброс.
+    // Пока фокус в текстовом поле, клавиши достаются ему
+    fn handle_shortcuts(&mut self, ctx: &egui::Context) {
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+        let (run, stop, reset) = ctx.input_mut(|i| {
+            let run = i.consume_key(egui::Modifiers::NONE, egui::Key::Enter)
+                | i.consume_key(egui::Modifiers::COMMAND, egui::Key::R);
+            let stop = i.consume_key(egui::Modifiers::NONE, egui::Key::Escape);
+            let reset = i.consume_key(egui::Modifiers::COMMAND, egui::Key::Backspace);
+            (run, stop, reset)
+        });
+        match self.state {
+            OptimizerState::Idle if run => self.start_optimization(),
+            OptimizerState::Running if stop => self.stop_optimization(),
+            OptimizerState::Finished if reset => self.reset_result(),
+            _ => {}
+        }
+    }
+
+    fn stop_optimization(&mut self) {
+        self.stop_flag.store(true, Ordering::SeqCst);
+        self.state = OptimizerState::Stopping;
+    }
+
+    fn check_for_result(&mut self) {
+        let lang = self.lang;
+        if let Some(rx) = &self.result_receiver {
+            while let Ok(output) = rx.try_recv() {
+                match output {
+                    WorkerOutput::Progress { iter, point, f_x } => {
+                        self.live_path.push((iter, point, f_x));
+                        continue;
+                    }
+                    WorkerOutput::Single(res) => {
+                        self.result = Some(match self.resume_from.take() {
+                            Some(mut previous) => {
+                                previous.append(res);
+                                previous
+                            }
+                            None => res,
+                        })
+                    }
+                    WorkerOutput::Pareto(front) => self.pareto_front = Some(front),
+                    WorkerOutput::MultiStart(results) => self.multi_start = results,
+                    WorkerOutput::Penalty(output) => {
+                        self.constraint_violations = output.violations;
+                        self.result = Some(output.result);
+                    }
+                    WorkerOutput::Sensitivity(map) => self.sensitivity = Some(map),
+                    WorkerOutput::Tuning(Some((report, best))) => {
+                        let trial = &report.trials[report.best];
+                        self.initial_step = trial.initial_step;
+                        self.step_decay = trial.step_decay;
+                        self.step_increase = trial.step_increase;
+                        self.last_method = Algorithm::GradientDescent;
+                        self.result = Some(best);
+                        self.tuning = Some(report);
+                    }
+                    WorkerOutput::Portfolio(mut runs) => {
+                        // NaN считается худшим значением
+                        let maximize = self.maximize;
+                        runs.sort_by(|a, b| {
+                            let by_value = a.1.f_x.total_cmp(&b.1.f_x);
+                            a.1.f_x.is_nan().cmp(&b.1.f_x.is_nan()).then(if maximize {
+                                by_value.reverse()
+                            } else {
+                                by_value
+                            })
+                        });
+                        self.ranking = runs
+                            .iter()
+                            .map(|(alg, res, evaluations)| RankedRun {
+                                method: *alg,
+                                f_x: res.f_x,
+                                iterations: res.iterations,
+                                evaluations: *evaluations,
+                            })
+                            .collect();
+                        self.ranking_budget = self.portfolio_budget;
+                        let mut runs = runs.into_iter();
+                        if let Some((alg, best, _)) = runs.next() {
+                            self.last_method = alg;
+                            self.result = Some(best);
+                        }
+                        for (alg, result, _) in runs {
+                            self.comparison.push(ComparisonRun {
+                                name: lang.tf("auto_run_name", &[&lang.t(alg.label())]),
+                                note: String::new(),
+                                method: alg,
+                                result,
+                            });
+                        }
+                    }
+                    WorkerOutput::Pair(pair) => {
+                        let [(alg, result), (other_alg, other)] = *pair;
+                        self.last_method = alg;
+                        self.result = Some(result);
+                        self.comparison.push(ComparisonRun {
+                            name: lang.t(other_alg.label()).to_string(),
+                            note: String::new(),
+                            method: other_alg,
+                            result: other,
+                        });
+                    }
+                    WorkerOutput::Restarts(Some(report)) => {
+                        self.result = Some(report.best);
+                        self.restart_finals = report.finals;
+                    }
+                    WorkerOutput::Restarts(None) => {
+                        self.error_message = Some(lang.t("err_restarts").to_string());
+                    }
+                    WorkerOutput::Tuning(None) => {
+                        self.error_message = Some(lang.t("err_tuning").to_string());
+                    }
+                }
+                self.state = OptimizerState::Finished;
+                self.live_path.clear();
+                self.resume_from = None;
+                let (tx, new_rx) = mpsc::channel();
+                self.result_sender = Some(tx);
+                self.result_receiver = Some(new_rx);
+                break;
+            }
+        }
+    }
+}
+
+// Таблица точек траектории: номер, координаты, f и ||∇f||, если нормы
+// градиента известны для каждой точки. Щелчок по заголовку упорядочивает
+// строки по столбцу, повторный — меняет порядок; кнопка в строке копирует
+// её значения через табуляцию
+fn show_iterates_table(
+    ui: &mut egui::Ui,
+    res: &OptimizerResult,
+    names: &[String],
+    sort: &mut (usize, bool),
+    lang: Lang,
+) {
+    let with_grad = res.grad_norms.len() == res.history.len();
+    let mut headers: Vec<String> = vec!["k".to_string()];
+    headers.extend(names.iter().cloned());
+    headers.push("f".to_string());
+    if with_grad {
+        headers.push("||g||".to_string());
+    }
+    let row = |k: usize| {
+        let (x, f_x) = &res.history[k];
+        let mut values = vec![res.iteration_at(k) as f64];
+        values.extend(x.iter().copied());
+        values.push(*f_x);
+        if with_grad {
+            values.push(res.grad_norms[k]);
+        }
+        values
+    };
+    if sort.0 >= headers.len() {
+        *sort = (0, false);
+    }
+    let mut order: Vec<usize> = (0..res.history.len()).collect();
+    if *sort != (0, false) {
+        order.sort_by(|&a, &b| row(a)[sort.0].total_cmp(&row(b)[sort.0]));
+        if sort.1 {
+            order.reverse();
+        }
+    }
+
+    let width = 90.0;
+    ui.horizontal(|ui| {
+        ui.add_space(ui.spacing().interact_size.y + ui.spacing().item_spacing.x);
+        for (j, header) in headers.iter().enumerate() {
+            let marker = match *sort {
+                (c, false) if c == j => " ⏶",
+                (c, true) if c == j => " ⏷",
+                _ => "",
+            };
+            let button = egui::Button::new(format!("{}{}", header, marker)).frame(false);
+            if ui.add_sized([width, 0.0], button).clicked() {
+                *sort = if sort.0 == j {
+                    (j, !sort.1)
+                } else {
+                    (j, false)
+                };
+            }
+        }
+    });
+    let row_height = ui.spacing().interact_size.y;
+    egui::ScrollArea::both()
+        .id_salt("iterates_table")
+        .max_height(250.0)
+        .auto_shrink([false, true])
+        .show_rows(ui, row_height, order.len(), |ui, rows| {
+            for &k in &order[rows] {
+                let values = row(k);
+                ui.horizontal(|ui| {
+                    if ui
+                        .small_button("📋")
+                        .on_hover_text(lang.t("copy_row"))
+                        .clicked()
+                    {
+                        let text = values
+                            .iter()
+                            .map(|v| v.to_string())
+                            .collect::<Vec<_>>()
+                            .join("\t");
+                        ui.ctx().copy_text(text);
+                    }
+                    for (j, v) in values.iter().enumerate() {
+                        let text = if j == 0 {
+                            k.to_string()
+                        } else {
+                            format!("{:.6e}", v)
+                        };
+                        ui.add_sized(
+                            [width, row_height],
+                            egui::Label::new(egui::RichText::new(text).monospace()),
+                        );
+                    }
+                });
+            }
+        });
+}
+
+// Распределение f(x*) по перезапускам; лучший считается достигнутым
+// с относительной точностью 1e-6
+fn show_restart_summary(ui: &mut egui::Ui, finals: &[(DVector<f64>, f64)], best: f64, lang: Lang) {
+    let mut values: Vec<f64> = finals
+        .iter()
+        .map(|(_, f)| *f)
+        .filter(|f| f.is_finite())
+        .collect();
+    values.sort_by(f64::total_cmp);
+    let hits = values
+        .iter()
+        .filter(|f| (*f - best).abs() <= 1e-6 * (1.0 + best.abs()))
+        .count();
+    let median = values.get(values.len() / 2).copied().unwrap_or(f64::NAN);
+    let worst = if best <= median {
+        values.last()
+    } else {
+        values.first()
+    };
+    ui.label(lang.tf(
+        "restart_summary",
+        &[
+            &finals.len(),
+            &hits,
+            &format!("{:.6}", median),
+            &format!("{:.6}", worst.copied().unwrap_or(f64::NAN)),
+        ],
+    ));
+}
+
+// Промежуточные точки запуска: последняя точка и растущий путь для n = 2
+fn show_live_progress(
+    ui: &mut egui::Ui,
+    coords: CoordinateSystem,
+    path: &[(usize, DVector<f64>, f64)],
+    num_vars: usize,
+    lang: Lang,
+) {
+    let Some((iter, x, f_x)) = path.last() else {
+        return;
+    };
+    ui.label(
+        lang.tf(
+            "iteration_point",
+            &[
+                iter,
+                &x.iter()
+                    .map(|v| format!("{:.6}", v))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                &format!("{:.6}", f_x),
+            ],
+        ),
+    );
+    if num_vars != 2 {
+        return;
+    }
+    let points: Vec<[f64; 2]> = path
+        .iter()
+        .map(|(_, point, _)| {
+            let p = coords.to_cartesian(point.as_slice());
+            [p[0], p[1]]
+        })
+        .collect();
+    Plot::new("live_path_plot")
+        .view_aspect(1.0)
+        .show(ui, |plot_ui| {
+            plot_ui.line(Line::new(PlotPoints::from(points)).name(lang.t("path")));
+        });
+}
+
+// Проекция траектории на плоскость первых двух декартовых координат
+fn path_points(coords: CoordinateSystem, res: &OptimizerResult) -> Vec<[f64; 2]> {
+    res.history
+        .iter()
+        .map(|(point, _)| {
+            let p = coords.to_cartesian(point.as_slice());
+            [p[0], p[1]]
+        })
+        .collect()
+}
+
+// Значение и численный градиент в точке графика под курсором;
+// координаты графика декартовы, функция задана в выбранной системе
+fn hover_text(
+    func: &ParsedFunction,
+    coords: CoordinateSystem,
+    mode: GradientMode,
+    eps: f64,
+    pointer: PlotPoint,
+    lang: Lang,
+) -> String {
+    let x = DVector::from_vec(coords.cartesian_to_native(&[pointer.x, pointer.y]));
+    let names = coords.var_names(x.len());
+    let point = names
+        .iter()
+        .zip(x.iter())
+        .map(|(name, v)| format!("{} = {:.4}", name, v))
+        .collect::<Vec<_>>()
+        .join(", ");
+    // Вне области определения символьная производная может оставаться конечной
+    let value = func.eval(&x).ok().filter(|v| v.is_finite());
+    let gradient = value.and_then(|_| func.gradient(&x, eps, mode).ok());
+    let value = match value {
+        Some(v) => format!("{:.6}", v),
+        None => lang.t("undefined").to_string(),
+    };
+    let gradient = match gradient {
+        Some(g) if g.iter().all(|v| v.is_finite()) => format!(
+            "[{}]",
+            g.iter()
+                .map(|v| format!("{:.4}", v))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        _ => lang.t("undefined").to_string(),
+    };
+    lang.tf("hover_value", &[&point, &value, &gradient])
+}
+
+// При максимизации анализируется -f, чтобы максимум не выглядел седлом
+fn show_hessian_diagnostics(
+    ui: &mut egui::Ui,
+    func: &ParsedFunction,
+    x: &DVector<f64>,
+    maximize: bool,
+    lang: Lang,
+) {
+    let Ok(hessian) = func.hessian(x, 1e-4) else {
+        return;
+    };
+    let sign = if maximize { -1.0 } else { 1.0 };
+    let Some(diagnostics) = optimizer::hessian_diagnostics(&(sign * hessian)) else {
+        ui.label(lang.t("hessian_undefined"));
+        return;
+    };
+    let (min, max) = (
+        diagnostics.eigenvalues[0],
+        diagnostics.eigenvalues[diagnostics.eigenvalues.len() - 1],
+    );
+    ui.label(lang.tf(
+        "hessian_condition",
+        &[
+            &format!("{:.3e}", diagnostics.condition),
+            &format!("{:.4e}", min),
+            &format!("{:.4e}", max),
+        ],
+    ));
+    if diagnostics.is_saddle() {
+        ui.colored_label(Palette::new(ui.visuals()).warn, lang.t("hessian_saddle"));
+    }
+}
+
+// current — текущий результат и его метод, выводится первой строкой без удаления
+fn show_comparison_table(
+    ui: &mut egui::Ui,
+    runs: &mut Vec<ComparisonRun>,
+    current: Option<(Algorithm, &OptimizerResult)>,
+    lang: Lang,
+) {
+    let elapsed = |res: &OptimizerResult| format!("{:.2}", res.elapsed.as_secs_f64() * 1000.0);
+    let mut removed = None;
+    egui::CollapsingHeader::new(lang.tf("comparison", &[&runs.len()]))
+        .default_open(true)
+        .show(ui, |ui| {
+            egui::Grid::new("comparison_table")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label(lang.t("name"));
+                    ui.label(lang.t("note"));
+                    ui.label(lang.t("method"));
+                    ui.label(lang.t("iterations"));
+                    ui.label("f(x*)");
+                    ui.label(lang.t("time_ms"));
+                    ui.label("");
+                    ui.end_row();
+                    if let Some((method, res)) = current {
+                        ui.label(lang.t("current_run"));
+                        ui.label("");
+                        ui.label(lang.t(method.label()));
+                        ui.label(res.iterations.to_string());
+                        ui.label(format!("{:.6}", res.f_x));
+                        ui.label(elapsed(res));
+                        ui.end_row();
+                    }
+                    for (k, run) in runs.iter_mut().enumerate() {
+                        ui.add(egui::TextEdit::singleline(&mut run.name).desired_width(120.0));
+                        ui.add(egui::TextEdit::singleline(&mut run.note).desired_width(160.0));
+                        ui.label(lang.t(run.method.label()));
+                        ui.label(run.result.iterations.to_string());
+                        ui.label(format!("{:.6}", run.result.f_x));
+                        ui.label(elapsed(&run.result));
+                        if ui
+                            .small_button("✖")
+                            .on_hover_text(lang.t("remove"))
+                            .clicked()
+                        {
+                            removed = Some(k);
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+    if let Some(k) = removed {
+        runs.remove(k);
+    }
+}
+
+// Точки (k, lg v) для графика в логарифмическом масштабе, k — номера итераций
+// прореженной траектории res; нули, отрицательные и нечисловые значения пропускаются
+fn log10_points(res: &OptimizerResult, values: &[f64]) -> PlotPoints {
+    values
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| **v > 0.0 && v.is_finite())
+        .map(|(k, v)| [res.iteration_at(k) as f64, v.log10()])
+        .collect()
+}
+
+fn termination_message(
+    reason: TerminationReason,
+    palette: Palette,
+    lang: Lang,
+) -> (egui::Color32, &'static str) {
+    match reason {
+        TerminationReason::Converged => (palette.good, lang.t("term_converged")),
+        TerminationReason::FlatRegion => (palette.warn, lang.t("term_flat")),
+        TerminationReason::MaxIterations => (palette.warn, lang.t("term_max_iterations")),
+        TerminationReason::UserStopped => (palette.warn, lang.t("term_user_stopped")),
+        TerminationReason::LineSearchFailed => (palette.bad, lang.t("term_line_search")),
+        TerminationReason::FunctionTolerance => (palette.good, lang.t("term_ftol")),
+        TerminationReason::StepTolerance => (palette.good, lang.t("term_xtol")),
+        TerminationReason::GradientNaN => (palette.bad, lang.t("term_nan")),
+        TerminationReason::NonFiniteValue => (palette.bad, lang.t("term_non_finite")),
+        TerminationReason::InvalidParams => (palette.bad, lang.t("term_invalid_params")),
+    }
+}
+
+fn show_tuning_report(ui: &mut egui::Ui, report: &TuneReport, lang: Lang) {
+    let best = &report.trials[report.best];
+    egui::CollapsingHeader::new(lang.tf(
+        "tuning",
+        &[
+            &best.initial_step,
+            &best.step_decay,
+            &best.step_increase,
+            &report.trials.len(),
+        ],
+    ))
+    .show(ui, |ui| {
+        let join = |values: &[f64]| {
+            let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+            format!("{{{}}}", values.join(", "))
+        };
+        ui.label(lang.tf(
+            "tuning_grid",
+            &[
+                &join(&optimizer::TUNE_INITIAL_STEPS),
+                &join(&optimizer::TUNE_STEP_DECAYS),
+                &join(&optimizer::TUNE_STEP_INCREASES),
+            ],
+        ));
+        ui.label(lang.t("tuning_rule"));
+        egui::ScrollArea::vertical()
+            .id_salt("tuning_scroll")
+            .max_height(200.0)
+            .show(ui, |ui| {
+                egui::Grid::new("tuning_table")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label(lang.t("step"));
+                        ui.label(lang.t("decay"));
+                        ui.label(lang.t("increase"));
+                        ui.label(lang.t("iterations"));
+                        ui.label("f(x*)");
+                        ui.label(lang.t("converged"));
+                        ui.end_row();
+                        for (k, trial) in report.trials.iter().enumerate() {
+                            let text = |s: String| {
+                                if k == report.best {
+                                    egui::RichText::new(s).strong()
+                                } else {
+                                    egui::RichText::new(s)
+                                }
+                            };
+                            ui.label(text(trial.initial_step.to_string()));
+                            ui.label(text(trial.step_decay.to_string()));
+                            ui.label(text(trial.step_increase.to_string()));
+                            ui.label(text(trial.iterations.to_string()));
+                            ui.label(text(format!("{:.6}", trial.f_x)));
+                            ui.label(text(
+                                if trial.converged {
+                                    lang.t("yes")
+                                } else {
+                                    lang.t("no")
+                                }
+                                .to_string(),
+                            ));
+                            ui.end_row();
+                        }
+                    });
+            });
+    });
+}
+
+// Значение f в начальной точке, если оно вычислимо и конечно
+fn start_value(func: &ParsedFunction, x: &DVector<f64>) -> Option<f64> {
+    func.eval(x).ok().filter(|v| v.is_finite())
+}
+
+// Цвет от синего (минимум) к красному (максимум)
+fn heat_color(t: f64) -> egui::Color32 {
+    let t = t.clamp(0.0, 1.0) as f32;
+    egui::Color32::from_rgb(
+        (255.0 * t) as u8,
+        (80.0 * (1.0 - t)) as u8,
+        (255.0 * (1.0 - t)) as u8,
+    )
+}
+
+// Число цветовых полос вдоль пути и на шкале
+const PATH_BANDS: usize = 32;
+
+// Диапазон f по траектории; None, если значения почти одинаковы и раскраска
+// ничего не покажет
+fn path_value_range(history: &[(DVector<f64>, f64)]) -> Option<(f64, f64)> {
+    let (lo, hi) = history
+        .iter()
+        .map(|(_, f)| *f)
+        .filter(|f| f.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), f| {
+            (lo.min(f), hi.max(f))
+        });
+    (hi - lo > 1e-9 * (1.0 + hi.abs().max(lo.abs()))).then_some((lo, hi))
+}
+
+// Положение f на шкале 0..1; логарифм от f - f_min, потому что у спуска
+// значения обычно убывают на порядки
+fn path_shade(f: f64, (lo, hi): (f64, f64)) -> f64 {
+    (1.0 + 999.0 * (f - lo) / (hi - lo)).log10() / 3.0
+}
+
+// Отрезки пути, окрашенные по среднему f на концах; соседние отрезки одной
+// полосы объединяются в одну ломаную, отрезки с неопределённым f пропускаются
+fn colored_path(
+    path: &[[f64; 2]],
+    values: &[f64],
+    range: (f64, f64),
+) -> Vec<(egui::Color32, Vec<[f64; 2]>)> {
+    let mut lines: Vec<(usize, Vec<[f64; 2]>)> = Vec::new();
+    let mut open = false;
+    for i in 0..path.len().min(values.len()).saturating_sub(1) {
+        let f = (values[i] + values[i + 1]) / 2.0;
+        if !f.is_finite() {
+            open = false;
+            continue;
+        }
+        let band = ((path_shade(f, range) * PATH_BANDS as f64) as usize).min(PATH_BANDS - 1);
+        match lines.last_mut() {
+            Some((k, points)) if open && *k == band => points.push(path[i + 1]),
+            _ => lines.push((band, vec![path[i], path[i + 1]])),
+        }
+        open = true;
+    }
+    lines
+        .into_iter()
+        .map(|(band, points)| (heat_color((band as f64 + 0.5) / PATH_BANDS as f64), points))
+        .collect()
+}
+
+fn show_color_bar(ui: &mut egui::Ui, (lo, hi): (f64, f64), lang: Lang) {
+    ui.horizontal(|ui| {
+        ui.label(format!("f = {:.4}", lo));
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(160.0, 12.0), egui::Sense::hover());
+        let width = rect.width() / PATH_BANDS as f32;
+        for band in 0..PATH_BANDS {
+            let left = rect.left() + width * band as f32;
+            ui.painter().rect_filled(
+                egui::Rect::from_min_max(
+                    egui::pos2(left, rect.top()),
+                    egui::pos2(left + width, rect.bottom()),
+                ),
+                0.0,
+                heat_color((band as f64 + 0.5) / PATH_BANDS as f64),
+            );
+        }
+        ui.label(format!("{:.4}", hi));
+    })
+    .response
+    .on_hover_text(lang.t("path_colors_hint"));
+}
+
+impl GradientDescentApp {
+    fn apply_preset(&mut self, preset: &Preset) {
+        let format_point = |x: &[f64]| {
+            x.iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        self.coords = CoordinateSystem::Cartesian;
+        self.num_vars = preset.num_vars;
+        self.least_squares_mode = false;
+        self.func_str = preset.expr.to_string();
+        self.initial_point_str = format_point(preset.start);
+        self.known_optimum_str = format_point(preset.minimum);
+        self.gradient_check = None;
+    }
+
+    // Значение f в известном оптимуме, если он задан и функция разобрана
+    fn known_minimum(&self) -> Option<(Vec<f64>, f64)> {
+        if self.known_optimum_str.trim().is_empty() {
+            return None;
+        }
+        let func = self.parsed_func.as_ref()?;
+        let res = self.result.as_ref()?;
+        let point = self.reference_point(res).ok()?;
+        let value = func.eval(&DVector::from_row_slice(&point)).ok()?;
+        Some((point, value))
+    }
+
+    // Опорная точка для оценки ошибок: известный оптимум или итоговая точка
+    fn reference_point(&self, res: &OptimizerResult) -> Result<Vec<f64>, String> {
+        let lang = self.lang;
+        if self.known_optimum_str.trim().is_empty() {
+            return Ok(res.x.iter().copied().collect());
+        }
+        let values = self
+            .known_optimum_str
+            .split(',')
+            .map(|part| {
+                part.trim()
+                    .parse::<f64>()
+                    .map_err(|_| lang.tf("err_parse_value", &[&part.trim()]))
+            })
+            .collect::<Result<Vec<f64>, String>>()?;
+        if values.len() != self.num_vars {
+            return Err(lang.tf("err_value_count", &[&self.num_vars, &values.len()]));
+        }
+        Ok(values)
+    }
+
+    fn show_error_ratios(&self, ui: &mut egui::Ui, res: &OptimizerResult) {
+        let lang = self.lang;
+        egui::CollapsingHeader::new(lang.t("error_ratios")).show(ui, |ui| {
+            let reference = match self.reference_point(res) {
+                Ok(r) => r,
+                Err(e) => {
+                    ui.colored_label(egui::Color32::RED, lang.tf("known_optimum_error", &[&e]));
+                    return;
+                }
+            };
+            let ratios = optimizer::error_ratios(&res.history, &reference);
+            let fmt = |v: Option<f64>| v.map_or("—".to_string(), |v| format!("{:.4e}", v));
+            egui::ScrollArea::vertical()
+                .id_salt("error_ratios_scroll")
+                .max_height(200.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("error_ratios")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.label("k");
+                            ui.label("e(k)");
+                            ui.label("e(k)/e(k-1)");
+                            ui.label("e(k)/e(k-1)²");
+                            ui.end_row();
+                            for r in &ratios {
+                                ui.label(res.iteration_at(r.iteration).to_string());
+                                ui.label(format!("{:.4e}", r.error));
+                                ui.label(fmt(r.linear));
+                                ui.label(fmt(r.quadratic));
+                                ui.end_row();
+                            }
+                        });
+                });
+        });
+    }
+
+    // ||x_k - x*|| в логарифмическом масштабе; строится только для заданного
+    // оптимума, итоговая точка в качестве x* дала бы ноль в конце кривой
+    fn show_distance_to_optimum(&self, ui: &mut egui::Ui, res: &OptimizerResult) {
+        let lang = self.lang;
+        if self.known_optimum_str.trim().is_empty() {
+            return;
+        }
+        let Ok(reference) = self.reference_point(res) else {
+            return;
+        };
+        let distances: Vec<f64> = optimizer::error_ratios(&res.history, &reference)
+            .iter()
+            .map(|r| r.error)
+            .collect();
+        egui::CollapsingHeader::new(lang.t("distance_to_optimum")).show(ui, |ui| {
+            Plot::new("distance_to_optimum_plot")
+                .height(150.0)
+                .x_axis_label(lang.t("iteration"))
+                .y_axis_formatter(|mark, _| format!("{:.0e}", 10f64.powf(mark.value)))
+                .show(ui, |plot_ui| {
+                    plot_ui.line(Line::new(log10_points(res, &distances)).name("||x - x*||"));
+                });
+        });
+    }
+
+    // Параметры прошлого сеанса; если хранилища нет или запись не читается,
+    // остаются значения по умолчанию
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        let mut app = Self {
+            repaint_ctx: Some(cc.egui_ctx.clone()),
+            ..Self::default()
+        };
+        if let Some(config) = cc
+            .storage
+            .and_then(|storage| eframe::get_value::<RunConfig>(storage, eframe::APP_KEY))
+        {
+            app.apply_run_config(config);
+        }
+        // Тема не входит в параметры запуска и хранится отдельно
+        if let Some(theme) = cc
+            .storage
+            .and_then(|storage| eframe::get_value::<Theme>(storage, THEME_KEY))
+        {
+            app.theme = theme;
+        }
+        cc.egui_ctx.set_theme(app.theme.preference());
+        app
+    }
+
+    fn run_config(&self) -> RunConfig {
+        RunConfig {
+            algorithm: self.algorithm,
+            coords: self.coords,
+            func_str: self.func_str.clone(),
+            least_squares_mode: self.least_squares_mode,
+            residuals_str: self.residuals_str.clone(),
+            maximize: self.maximize,
+            gradient_mode: self.gradient_mode,
+            grad_eps: self.grad_eps,
+            line_search: self.line_search,
+            num_vars: self.num_vars,
+            initial_point_str: self.initial_point_str.clone(),
+            initial_step: self.initial_step,
+            auto_step: self.auto_step,
+            step_decay: self.step_decay,
+            max_backtracks: self.max_backtracks,
+            step_increase: self.step_increase,
+            max_step: self.max_step,
+            armijo_c1: self.armijo_c1,
+            tolerance: self.tolerance,
+            ftol: self.ftol,
+            xtol: self.xtol,
+            restart_period: self.restart_period,
+            restart_doubling: self.restart_doubling,
+            max_grad_norm: self.max_grad_norm,
+            max_iterations: self.max_iterations,
+            max_history_points: self.max_history_points,
+            verbose_log: self.verbose_log,
+            detect_flat: self.detect_flat,
+            scale_tolerance: self.scale_tolerance,
+            rel_grad: self.rel_grad,
+            normalize_objective: self.normalize_objective,
+            trust_radius: self.trust_radius,
+            max_trust_radius: self.max_trust_radius,
+            momentum: self.momentum,
+            nesterov: self.nesterov,
+            adam: self.adam.clone(),
+            rmsprop: self.rmsprop.clone(),
+            nelder_mead: self.nelder_mead.clone(),
+            annealing: self.annealing.clone(),
+            restarts: self.restarts.clone(),
+            bb_variant: self.bb_variant,
+            lbfgs_memory: self.lbfgs_memory,
+            powell_restart: self.powell_restart,
+            powell_threshold: self.powell_threshold,
+            perturb_enabled: self.perturb_enabled,
+            fixed_seed: self.fixed_seed,
+            seed: self.seed,
+            perturb_sigma: self.perturb_sigma,
+            perturb: self.perturb.clone(),
+            lower_bounds_str: self.lower_bounds_str.clone(),
+            upper_bounds_str: self.upper_bounds_str.clone(),
+            known_optimum_str: self.known_optimum_str.clone(),
+            multi_objective: self.multi_objective,
+            func2_str: self.func2_str.clone(),
+            weight: self.weight,
+            constraints_str: self.constraints_str.clone(),
+            penalty: self.penalty.clone(),
+        }
+    }
+
+    fn apply_run_config(&mut self, config: RunConfig) {
+        self.algorithm = config.algorithm;
+        self.coords = config.coords;
+        self.func_str = config.func_str;
+        self.least_squares_mode = config.least_squares_mode;
+        self.residuals_str = config.residuals_str;
+        self.maximize = config.maximize;
+        self.gradient_mode = config.gradient_mode;
+        self.grad_eps = config.grad_eps;
+        self.line_search = config.line_search;
+        self.num_vars = config.num_vars;
+        self.initial_point_str = config.initial_point_str;
+        self.initial_step = config.initial_step;
+        self.auto_step = config.auto_step;
+        self.step_decay = config.step_decay;
+        self.max_backtracks = config.max_backtracks;
+        self.step_increase = config.step_increase;
+        self.max_step = config.max_step;
+        self.armijo_c1 = config.armijo_c1;
+        self.tolerance = config.tolerance;
+        self.ftol = config.ftol;
+        self.xtol = config.xtol;
+        self.restart_period = config.restart_period;
+        self.restart_doubling = config.restart_doubling;
+        self.max_grad_norm = config.max_grad_norm;
+        self.max_iterations = config.max_iterations;
+        self.max_history_points = config.max_history_points;
+        self.verbose_log = config.verbose_log;
+        self.detect_flat = config.detect_flat;
+        self.scale_tolerance = config.scale_tolerance;
+        self.rel_grad = config.rel_grad;
+        self.normalize_objective = config.normalize_objective;
+        self.trust_radius = config.trust_radius;
+        self.max_trust_radius = config.max_trust_radius;
+        self.momentum = config.momentum;
+        self.nesterov = config.nesterov;
+        self.adam = config.adam;
+        self.rmsprop = config.rmsprop;
+        self.nelder_mead = config.nelder_mead;
+        self.annealing = config.annealing;
+        self.restarts = config.restarts;
+        self.bb_variant = config.bb_variant;
+        self.lbfgs_memory = config.lbfgs_memory;
+        self.powell_restart = config.powell_restart;
+        self.powell_threshold = config.powell_threshold;
+        self.perturb_enabled = config.perturb_enabled;
+        self.fixed_seed = config.fixed_seed;
+        self.seed = config.seed;
+        self.perturb_sigma = config.perturb_sigma;
+        self.perturb = config.perturb;
+        self.lower_bounds_str = config.lower_bounds_str;
+        self.upper_bounds_str = config.upper_bounds_str;
+        self.known_optimum_str = config.known_optimum_str;
+        self.multi_objective = config.multi_objective;
+        self.func2_str = config.func2_str;
+        self.weight = config.weight;
+        self.constraints_str = config.constraints_str;
+        self.penalty = config.penalty;
+    }
+
+    // Возврат полей ввода к значениям по умолчанию; результат, канал
+    // и флаг остановки не меняются
+    fn reset_params(&mut self) {
+        self.apply_run_config(RunConfig::default());
+        self.gradient_check = None;
+        self.error_message = None;
+    }
+
+    // Выражение из файла заменяет введённое; при ошибке разбора текст всё равно
+    // подставляется в поле, чтобы его можно было исправить
+    fn load_function_file(&mut self) {
+        let lang = self.lang;
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("TXT", &["txt"])
+            .pick_file()
+        else {
+            return;
+        };
+        let loaded = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| parser::parse_function_file(&text));
+        let (expr, num_vars) = match loaded {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                self.error_message = Some(lang.tf("err_load", &[&path.display(), &e]));
+                return;
+            }
+        };
+        if let Some(n) = num_vars {
+            if self.coords.dimension().is_some_and(|d| d != n) {
+                self.coords = CoordinateSystem::Cartesian;
+            }
+            self.num_vars = n;
+        }
+        self.func_str = expr;
+        self.gradient_check = None;
+        let names = self.coords.var_names(self.num_vars);
+        let (objective, _, _) = parser::split_inline_bounds(&self.func_str, &names);
+        self.error_message = self
+            .parse_function(&objective)
+            .err()
+            .map(|e| lang.tf("err_function", &[&e]));
+    }
+
+    fn save_run(&mut self) {
+        let lang = self.lang;
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("JSON", &["json"])
+            .set_file_name("run.json")
+            .save_file()
+        else {
+            return;
+        };
+        let saved = SavedRun {
+            config: self.run_config(),
+            result: self.result.clone(),
+            comparison: self.comparison.clone(),
+        };
+        let written = serde_json::to_string_pretty(&saved)
+            .map_err(|e| e.to_string())
+            .and_then(|json| std::fs::write(&path, json).map_err(|e| e.to_string()));
+        if let Err(e) = written {
+            self.error_message = Some(lang.tf("err_save", &[&path.display(), &e]));
+        }
+    }
+
+    fn load_run(&mut self) {
+        let lang = self.lang;
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("JSON", &["json"])
+            .pick_file()
+        else {
+            return;
+        };
+        let loaded = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|json| serde_json::from_str::<SavedRun>(&json).map_err(|e| e.to_string()));
+        let saved = match loaded {
+            Ok(saved) => saved,
+            Err(e) => {
+                self.error_message = Some(lang.tf("err_load", &[&path.display(), &e]));
+                return;
+            }
+        };
+
+        self.apply_run_config(saved.config);
+        self.error_message = None;
+        self.constraint_violations.clear();
+        self.pareto_front = None;
+        self.multi_start.clear();
+        self.restart_finals.clear();
+        self.sensitivity = None;
+        self.tuning = None;
+        self.ranking.clear();
+        self.contours = None;
+        self.stop_animation();
+        // Функция нужна для линий уровня восстановленного пути
+        let names = self.coords.var_names(self.num_vars);
+        let (objective, _, _) = parser::split_inline_bounds(&self.func_str, &names);
+        self.parsed_func = self.parse_function(&objective).ok();
+        self.last_method = self.algorithm;
+        self.state = if saved.result.is_some() {
+            OptimizerState::Finished
+        } else {
+            OptimizerState::Idle
+        };
+        self.result = saved.result;
+        self.comparison = saved.comparison;
+    }
+
+    fn stop_animation(&mut self) {
+        self.animation_frame = None;
+        self.animation_playing = false;
+        self.animation_clock = 0.0;
+    }
+
+    // Продвижение анимации на число итераций, накопившееся за кадр
+    fn advance_animation(&mut self, ctx: &egui::Context) {
+        if !self.animation_playing {
+            return;
+        }
+        let Some(last) = self
+            .result
+            .as_ref()
+            .and_then(|r| r.history.len().checked_sub(1))
+        else {
+            self.stop_animation();
+            return;
+        };
+        self.animation_clock += ctx.input(|i| i.stable_dt) as f64 * self.animation_speed;
+        let steps = self.animation_clock.floor();
+        self.animation_clock -= steps;
+        let frame = self.animation_frame.get_or_insert(0);
+        *frame = (*frame + steps as usize).min(last);
+        if *frame == last {
+            self.animation_playing = false;
+        } else {
+            ctx.request_repaint();
+        }
+    }
+
+    fn show_animation_controls(&mut self, ui: &mut egui::Ui) {
+        let lang = self.lang;
+        let Some(last) = self
+            .result
+            .as_ref()
+            .and_then(|r| r.history.len().checked_sub(1))
+        else {
+            return;
+        };
+        ui.horizontal(|ui| {
+            let play_label = if self.animation_playing { "⏸" } else { "▶" };
+            if ui
+                .button(play_label)
+                .on_hover_text(lang.t("anim_play"))
+                .clicked()
+            {
+                if self.animation_playing {
+                    self.animation_playing = false;
+                } else {
+                    // Повторный запуск после конца начинается сначала
+                    if self.animation_frame.is_none_or(|frame| frame >= last) {
+                        self.animation_frame = Some(0);
+                    }
+                    self.animation_clock = 0.0;
+                    self.animation_playing = true;
+                }
+            }
+            if ui.button("⏭").on_hover_text(lang.t("anim_next")).clicked() {
+                self.animation_playing = false;
+                self.animation_frame = Some(
+                    self.animation_frame
+                        .map_or(0, |frame| (frame + 1).min(last)),
+                );
+            }
+            if ui.button("⏹").on_hover_text(lang.t("anim_full")).clicked() {
+                self.stop_animation();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label(lang.t("anim_speed"));
+            ui.add(egui::Slider::new(&mut self.animation_speed, 0.5..=50.0).logarithmic(true));
+        });
+    }
+
+    fn export_trajectory(&mut self) {
+        let lang = self.lang;
+        let Some(res) = &self.result else {
+            return;
+        };
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("CSV", &["csv"])
+            .set_file_name("trajectory.csv")
+            .save_file()
+        else {
+            return;
+        };
+        let names = self.coords.var_names(self.num_vars);
+        if let Err(e) = std::fs::write(&path, export::trajectory_csv(res, &names)) {
+            self.error_message = Some(lang.tf("err_save", &[&path.display(), &e]));
+        }
+    }
+
+    // Снимок делается в следующем кадре, когда график нарисован с подписями осей
+    fn request_plot_png(&mut self, ctx: &egui::Context) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("PNG", &["png"])
+            .set_file_name("path.png")
+            .save_file()
+        else {
+            return;
+        };
+        self.png_path = Some(path);
+        self.png_rect = None;
+        ctx.request_repaint();
+    }
+
+    fn save_plot_png(&mut self, ctx: &egui::Context) {
+        let lang = self.lang;
+        let Some(rect) = self.png_rect else {
+            return;
+        };
+        let screenshot = ctx.input(|i| {
+            i.raw.events.iter().find_map(|e| match e {
+                egui::Event::Screenshot { image, .. } => Some(image.clone()),
+                _ => None,
+            })
+        });
+        let Some(screenshot) = screenshot else {
+            return;
+        };
+        self.png_rect = None;
+        let Some(path) = self.png_path.take() else {
+            return;
+        };
+        let image = screenshot.region(&rect, Some(ctx.pixels_per_point()));
+        let [w, h] = image.size;
+        if let Err(e) = image::save_buffer(
+            &path,
+            image.as_raw(),
+            w as u32,
+            h as u32,
+            image::ExtendedColorType::Rgba8,
+        ) {
+            self.error_message = Some(lang.tf("err_save", &[&path.display(), &e]));
+        }
+    }
+
+    // Прямоугольник x_min, x_max, y_min, y_max, охватывающий путь спуска с запасом
+    fn path_bounds(&self, res: &OptimizerResult) -> Option<[f64; 4]> {
+        let path = path_points(self.coords, res);
+        let (mut x_min, mut x_max, mut y_min, mut y_max) = (
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+        );
+        for p in path.iter().filter(|p| p[0].is_finite() && p[1].is_finite()) {
+            x_min = x_min.min(p[0]);
+            x_max = x_max.max(p[0]);
+            y_min = y_min.min(p[1]);
+            y_max = y_max.max(p[1]);
+        }
+        if !x_min.is_finite() || !y_min.is_finite() {
+            return None;
+        }
+        let margin_x = (0.2 * (x_max - x_min)).max(0.5);
+        let margin_y = (0.2 * (y_max - y_min)).max(0.5);
+        Some([
+            x_min - margin_x,
+            x_max + margin_x,
+            y_min - margin_y,
+            y_max + margin_y,
+        ])
+    }
+
+    // Значения функции в узлах сетки по декартовым координатам; узлы,
+    // где вычисление завершилось ошибкой, остаются неопределёнными
+    fn sample_grid(&self, func: &ParsedFunction, resolution: usize, bounds: [f64; 4]) -> Grid {
+        let coords = self.coords;
+        Grid::sample_batch(
+            resolution,
+            (bounds[0], bounds[1]),
+            (bounds[2], bounds[3]),
+            |nodes| {
+                let points: Vec<DVector<f64>> = nodes
+                    .iter()
+                    .map(|&(x, y)| DVector::from_vec(coords.cartesian_to_native(&[x, y])))
+                    .collect();
+                func.eval_batch(&points)
+                    .into_iter()
+                    .map(Result::ok)
+                    .collect()
+            },
+        )
+    }
+
+    // Сетка пересчитывается только при изменении пути или разрешения
+    fn update_contours(&mut self) {
+        let (Some(res), Some(func)) = (&self.result, &self.parsed_func) else {
+            self.contours = None;
+            return;
+        };
+        if !self.show_contours || self.num_vars != 2 || res.history.is_empty() {
+            self.contours = None;
+            return;
+        }
+        let Some(bounds) = self.path_bounds(res) else {
+            self.contours = None;
+            return;
+        };
+
+        if let Some(cache) = &self.contours {
+            if cache.resolution == self.contour_resolution && cache.bounds == bounds {
+                return;
+            }
+        }
+
+        let grid = self.sample_grid(func, self.contour_resolution, bounds);
+        let lines = grid
+            .levels(12)
+            .into_iter()
+            .map(|level| (level, grid.contour(level)))
+            .collect();
+        self.contours = Some(ContourCache {
+            resolution: self.contour_resolution,
+            bounds,
+            lines,
+        });
+    }
+
+    fn update_surface(&mut self) {
+        let (Some(res), Some(func)) = (&self.result, &self.parsed_func) else {
+            self.surface = None;
+            return;
+        };
+        if !self.surface_view || self.num_vars != 2 || res.history.is_empty() {
+            self.surface = None;
+            return;
+        }
+        let Some(bounds) = self.path_bounds(res) else {
+            self.surface = None;
+            return;
+        };
+
+        if let Some(cache) = &self.surface {
+            if cache.resolution == self.surface_resolution && cache.bounds == bounds {
+                return;
+            }
+        }
+
+        let grid = self.sample_grid(func, self.surface_resolution, bounds);
+        self.surface = Some(SurfaceCache {
+            resolution: self.surface_resolution,
+            bounds,
+            surface: Surface::new(grid),
+        });
+    }
+
+    // Каркас поверхности, окрашенный по высоте, и первые visible точек пути
+    // на высоте f(x); неопределённые узлы сетки пропускаются
+    fn show_surface(
+        &self,
+        ui: &mut egui::Ui,
+        surface: &Surface,
+        res: &OptimizerResult,
+        visible: usize,
+    ) {
+        let lang = self.lang;
+        let view = View {
+            yaw: self.surface_yaw,
+            pitch: self.surface_pitch,
+        };
+        let wireframe = surface.wireframe(&view, 12);
+        let trajectory: Vec<[f64; 2]> = res.history[..visible]
+            .iter()
+            .map(|(x, f_x)| {
+                let p = self.coords.to_cartesian(x.as_slice());
+                view.project(surface.normalize(p[0], p[1], *f_x))
+            })
+            .collect();
+        let current = self.animation_frame.and(trajectory.last().copied());
+
+        let palette = Palette::new(ui.visuals());
+        Plot::new("surface_plot")
+            .view_aspect(self.plot_aspect)
+            .data_aspect(1.0)
+            .show_axes(false)
+            .show_grid(false)
+            .show_x(false)
+            .show_y(false)
+            .show(ui, |plot_ui| {
+                for line in wireframe {
+                    plot_ui.line(
+                        Line::new(PlotPoints::from(line.points))
+                            .color(heat_color(line.height).gamma_multiply(0.6))
+                            .width(1.0),
+                    );
+                }
+                plot_ui.line(
+                    Line::new(PlotPoints::from(trajectory))
+                        .color(palette.path)
+                        .width(2.0)
+                        .name(lang.t("path")),
+                );
+                if let Some(current) = current {
+                    plot_ui.points(
+                        Points::new(PlotPoints::from(vec![current]))
+                            .radius(5.0)
+                            .color(palette.current)
+                            .name(lang.t("current_point")),
+                    );
+                }
+            });
+    }
+
+    fn show_multi_start(&self, ui: &mut egui::Ui) {
+        let lang = self.lang;
+        let format_point = |x: &DVector<f64>| {
+            x.iter()
+                .map(|v| format!("{:.4}", v))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        ui.label(lang.tf("multi_start", &[&self.multi_start.len()]));
+        egui::ScrollArea::vertical()
+            .max_height(200.0)
+            .show(ui, |ui| {
+                egui::Grid::new("multi_start_table")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label(lang.t("number"));
+                        ui.label("x0");
+                        ui.label("x*");
+                        ui.label("f(x*)");
+                        ui.label(lang.t("iterations"));
+                        ui.end_row();
+                        for (k, res) in self.multi_start.iter().enumerate() {
+                            ui.label((k + 1).to_string());
+                            ui.label(
+                                res.history
+                                    .first()
+                                    .map(|(x0, _)| format_point(x0))
+                                    .unwrap_or_default(),
+                            );
+                            ui.label(format_point(&res.x));
+                            ui.label(format!("{:.6}", res.f_x));
+                            ui.label(res.iterations.to_string());
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        if self.num_vars != 2 {
+            return;
+        }
+        let mut plot = Plot::new("multi_start_plot")
+            .view_aspect(self.plot_aspect)
+            .legend(Legend::default());
+        if self.equal_axes {
+            plot = plot.data_aspect(1.0);
+        }
+        plot.show(ui, |plot_ui| {
+            for (k, res) in self.multi_start.iter().enumerate() {
+                plot_ui.line(
+                    Line::new(PlotPoints::from(path_points(self.coords, res)))
+                        .name(lang.tf("start_n", &[&(k + 1)])),
+                );
+            }
+        });
+    }
+
+    fn show_convergence_plot(&self, ui: &mut egui::Ui, res: &OptimizerResult) {
+        let lang = self.lang;
+        if res.history.is_empty() {
+            ui.label(lang.t("no_convergence_data"));
+            return;
+        }
+        let values = |result: &OptimizerResult| -> PlotPoints {
+            let f: Vec<f64> = result.history.iter().map(|(_, f)| *f).collect();
+            if self.log_scale_f {
+                log10_points(result, &f)
+            } else {
+                f.iter()
+                    .enumerate()
+                    .filter(|(_, v)| v.is_finite())
+                    .map(|(k, v)| [result.iteration_at(k) as f64, *v])
+                    .collect()
+            }
+        };
+        if self.log_scale_f && res.history.iter().all(|(_, f)| *f <= 0.0) {
+            ui.label(lang.t("log_unavailable"));
+            return;
+        }
+
+        let mut plot = Plot::new("convergence_plot")
+            .view_aspect(self.plot_aspect)
+            .x_axis_label(lang.t("iteration"))
+            .y_axis_label(if self.log_scale_f {
+                lang.t("f_log")
+            } else {
+                "f"
+            })
+            .auto_bounds(egui::Vec2b::TRUE);
+        if self.log_scale_f {
+            plot = plot.y_axis_formatter(|mark, _| format!("{:.0e}", 10f64.powf(mark.value)));
+        }
+        if !self.comparison.is_empty() {
+            plot = plot.legend(Legend::default());
+        }
+        plot.show(ui, |plot_ui| {
+            for run in &self.comparison {
+                plot_ui.line(Line::new(values(&run.result)).name(&run.name));
+            }
+            plot_ui.line(Line::new(values(res)).name("f(x)"));
+        });
+    }
+
+    fn show_sensitivity_map(&self, ui: &mut egui::Ui, map: &SensitivityMap) {
+        let lang = self.lang;
+        let values: Vec<f64> = match self.heatmap_metric {
+            HeatmapMetric::FinalValue => map.final_f.clone(),
+            HeatmapMetric::Iterations => map.iterations.iter().map(|&k| k as f64).collect(),
+        };
+        let finite = values.iter().copied().filter(|v| v.is_finite());
+        let lo = finite.clone().fold(f64::INFINITY, f64::min);
+        let hi = finite.fold(f64::NEG_INFINITY, f64::max);
+        let span = if hi > lo { hi - lo } else { 1.0 };
+
+        let metric_name = match self.heatmap_metric {
+            HeatmapMetric::FinalValue => "f(x*)",
+            HeatmapMetric::Iterations => lang.t("metric_iterations"),
+        };
+        ui.label(lang.tf(
+            "sensitivity_summary",
+            &[
+                &map.resolution,
+                &map.resolution,
+                &metric_name,
+                &format!("{:.4}", lo),
+                &format!("{:.4}", hi),
+            ],
+        ));
+
+        let n = map.resolution;
+        let half_w = (map.x_range.1 - map.x_range.0) / (2.0 * (n.max(2) - 1) as f64);
+        let half_h = (map.y_range.1 - map.y_range.0) / (2.0 * (n.max(2) - 1) as f64);
+        let names = self.coords.var_names(self.num_vars);
+
+        let mut plot = Plot::new("sensitivity_plot")
+            .view_aspect(self.plot_aspect)
+            .x_axis_label(names[0].clone())
+            .y_axis_label(names[1].clone());
+        if self.equal_axes {
+            plot = plot.data_aspect(1.0);
+        }
+        plot.show(ui, |plot_ui| {
+            for j in 0..n {
+                for i in 0..n {
+                    let v = values[j * n + i];
+                    if !v.is_finite() {
+                        continue;
+                    }
+                    let (x, y) = map.node(i, j);
+                    let cell = vec![
+                        [x - half_w, y - half_h],
+                        [x + half_w, y - half_h],
+                        [x + half_w, y + half_h],
+                        [x - half_w, y + half_h],
+                    ];
+                    plot_ui.polygon(
+                        Polygon::new(PlotPoints::from(cell))
+                            .fill_color(heat_color((v - lo) / span))
+                            .stroke(egui::Stroke::NONE),
+                    );
+                }
+            }
+        });
+    }
+}
+
+impl eframe::App for GradientDescentApp {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, eframe::APP_KEY, &self.run_config());
+        eframe::set_value(storage, THEME_KEY, &self.theme);
+    }
+
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let lang = self.lang;
         self.check_for_result();
+        self.advance_animation(ctx);
+        self.save_plot_png(ctx);
+        self.handle_shortcuts(ctx);
 
         egui::SidePanel::left("control_panel")
             .resizable(true)
             .show(ctx, |ui| {
-                ui.heading("Параметры");
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::ComboBox::from_label(lang.t("language"))
+                        .selected_text(self.lang.label())
+                        .show_ui(ui, |ui| {
+                            for option in Lang::ALL {
+                                ui.selectable_value(&mut self.lang, option, option.label());
+                            }
+                        });
+                    let theme = self.theme;
+                    egui::ComboBox::from_label(lang.t("theme"))
+                        .selected_text(lang.t(self.theme.label()))
+                        .show_ui(ui, |ui| {
+                            for option in Theme::ALL {
+                                ui.selectable_value(
+                                    &mut self.theme,
+                                    option,
+                                    lang.t(option.label()),
+                                );
+                            }
+                        });
+                    if self.theme != theme {
+                        ui.ctx().set_theme(self.theme.preference());
+                    }
 
-                ui.horizontal(|ui| {
-                    ui.label("Размерность (n):");
-                    ui.add(egui::DragValue::new(&mut self.num_vars).range(1..=10));
-                });
+                    ui.heading(lang.t("params"));
 
-                ui.horizontal(|ui| {
-                    ui.label("Функция f(x):");
-                    ui.text_edit_singleline(&mut self.func_str);
-                });
+                    egui::ComboBox::from_label(lang.t("method"))
+                        .selected_text(lang.t(self.algorithm.label()))
+                        .show_ui(ui, |ui| {
+                            for algorithm in self.algorithms() {
+                                ui.selectable_value(
+                                    &mut self.algorithm,
+                                    algorithm,
+                                    lang.t(algorithm.label()),
+                                );
+                            }
+                        });
 
-                ui.horizontal(|ui| {
-                    ui.label("Начальная точка:");
-                    ui.text_edit_singleline(&mut self.initial_point_str);
-                });
+                    egui::ComboBox::from_label(lang.t("coordinates"))
+                        .selected_text(lang.t(self.coords.label()))
+                        .show_ui(ui, |ui| {
+                            for coords in CoordinateSystem::ALL {
+                                ui.selectable_value(
+                                    &mut self.coords,
+                                    coords,
+                                    lang.t(coords.label()),
+                                );
+                            }
+                        });
+                    if let Some(n) = self.coords.dimension() {
+                        self.num_vars = n;
+                    }
 
-                ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.label(lang.t("dimension"));
+                        ui.add_enabled(
+                            self.coords == CoordinateSystem::Cartesian,
+                            egui::DragValue::new(&mut self.num_vars).range(1..=10),
+                        );
+                    });
 
-                ui.horizontal(|ui| {
-                    ui.label("Начальный шаг:");
-                    ui.add(
-                        egui::DragValue::new(&mut self.initial_step)
-                            .speed(0.1)
-                            .range(0.0..=10.0),
-                    );
-                });
+                    egui::ComboBox::from_label(lang.t("preset"))
+                        .selected_text(lang.t("preset_choose"))
+                        .show_ui(ui, |ui| {
+                            for preset in &PRESETS {
+                                if ui.selectable_label(false, lang.t(preset.name)).clicked() {
+                                    self.apply_preset(preset);
+                                }
+                            }
+                        });
 
-                ui.horizontal(|ui| {
-                    ui.label("Коэф. дробления:");
-                    ui.add(
-                        egui::DragValue::new(&mut self.step_decay)
-                            .speed(0.05)
-                            .range(0.1..=0.9),
-                    );
-                });
+                    ui.horizontal(|ui| {
+                        ui.label(lang.t("function"));
+                        let edit = ui.add_enabled(
+                            !self.least_squares_mode,
+                            egui::TextEdit::singleline(&mut self.func_str),
+                        );
+                        if edit.changed() {
+                            self.gradient_check = None;
+                        }
+                        if self.coords == CoordinateSystem::Cartesian && self.num_vars <= 3 {
+                            edit.on_hover_text(lang.t("alias_hint"));
+                        }
+                    });
+                    let toggled = ui
+                        .checkbox(&mut self.least_squares_mode, lang.t("least_squares"))
+                        .on_hover_text(lang.t("least_squares_hint"))
+                        .changed();
+                    if self.least_squares_mode {
+                        let edit = ui.text_edit_multiline(&mut self.residuals_str);
+                        if toggled || edit.changed() {
+                            self.func_str = LeastSquares::sum_of_squares(&self.residuals_str);
+                            self.gradient_check = None;
+                        }
+                    } else if toggled {
+                        for algorithm in [&mut self.algorithm, &mut self.compare_algorithm] {
+                            if *algorithm == Algorithm::GaussNewton {
+                                *algorithm = Algorithm::GradientDescent;
+                            }
+                        }
+                    }
+                    if ui
+                        .button(lang.t("load_function"))
+                        .on_hover_text(lang.t("load_function_hint"))
+                        .clicked()
+                    {
+                        self.load_function_file();
+                    }
+                    ui.checkbox(&mut self.maximize, lang.t("maximize"))
+                        .on_hover_text(lang.t("maximize_hint"));
 
-                ui.horizontal(|ui| {
-                    ui.label("Коэф. увеличения:");
-                    ui.add(
-                        egui::DragValue::new(&mut self.step_increase)
-                            .speed(0.1)
-                            .range(1.0..=2.0),
-                    );
-                });
+                    egui::ComboBox::from_label(lang.t("gradient"))
+                        .selected_text(lang.t(self.gradient_mode.label()))
+                        .show_ui(ui, |ui| {
+                            for mode in GradientMode::ALL {
+                                ui.selectable_value(
+                                    &mut self.gradient_mode,
+                                    mode,
+                                    lang.t(mode.label()),
+                                );
+                            }
+                        });
+                    ui.horizontal(|ui| {
+                        ui.label(lang.t("grad_eps"))
+                            .on_hover_text(lang.t("grad_eps_hint"));
+                        ui.add(
+                            egui::DragValue::new(&mut self.grad_eps)
+                                .speed(1e-7)
+                                .range(1e-10..=1e-2)
+                                .custom_formatter(|v, _| format!("{:.1e}", v)),
+                        );
+                    });
 
-                ui.separator();
+                    ui.horizontal(|ui| {
+                        if ui
+                            .button(lang.t("check_gradient"))
+                            .on_hover_text(lang.t("check_gradient_hint"))
+                            .clicked()
+                        {
+                            self.gradient_check = Some(self.check_gradient());
+                        }
+                        match &self.gradient_check {
+                            Some(Ok(diff)) => {
+                                ui.label(lang.tf("gradient_diff", &[&format!("{:.3e}", diff)]));
+                            }
+                            Some(Err(e)) => {
+                                ui.colored_label(egui::Color32::RED, e);
+                            }
+                            None => {}
+                        }
+                    });
 
-                ui.horizontal(|ui| {
-                    ui.label("Точность:");
-                    ui.add(
-                        egui::DragValue::new(&mut self.tolerance)
-                            .speed(1e-7)
-                            .range(1e-12..=1.0),
-                    );
-                });
+                    if self.gradient_mode == GradientMode::Analytic {
+                        if let Some(func) = &self.parsed_func {
+                            match func.gradient_strings() {
+                                Some(derivatives) => {
+                                    let names = self.coords.var_names(self.num_vars);
+                                    for (name, d) in names.iter().zip(derivatives) {
+                                        ui.label(format!("∂f/∂{} = {}", name, d));
+                                    }
+                                }
+                                None => {
+                                    ui.colored_label(
+                                        Palette::new(ui.visuals()).warn,
+                                        lang.t("symbolic_failed"),
+                                    );
+                                }
+                            }
+                        }
+                    }
 
-                ui.horizontal(|ui| {
-                    ui.label("Макс. итераций:");
+                    // Тело заголовка выполняется только в раскрытом виде, поэтому
+                    // градиент не пересчитывается в каждом кадре без надобности
+                    egui::CollapsingHeader::new(lang.t("partial_derivatives")).show(ui, |ui| {
+                        match self.partial_derivatives() {
+                            Ok(values) => {
+                                let names = self.coords.var_names(self.num_vars);
+                                egui::Grid::new("partial_derivatives_table")
+                                    .striped(true)
+                                    .show(ui, |ui| {
+                                        for (name, value) in names.iter().zip(values) {
+                                            ui.label(format!("∂f/∂{}", name));
+                                            if value.is_finite() {
+                                                ui.label(format!("{:.6e}", value));
+                                            } else {
+                                                ui.colored_label(
+                                                    egui::Color32::RED,
+                                                    lang.t("derivs_eval_error"),
+                                                );
+                                            }
+                                            ui.end_row();
+                                        }
+                                    });
+                            }
+                            Err(key) => {
+                                ui.colored_label(egui::Color32::RED, lang.t(key));
+                            }
+                        }
+                    });
+
+                    if let Some(func) = &self.parsed_func {
+                        if !func.domain_warnings().is_empty() {
+                            ui.colored_label(
+                                Palette::new(ui.visuals()).warn,
+                                lang.t("domain_warning"),
+                            );
+                            for warning in func.domain_warnings() {
+                                ui.label(format!("• {}", warning));
+                            }
+                        }
+                        if func.has_kinks() {
+                            ui.label(lang.t("kink_note"));
+                        }
+                    }
+
+                    ui.horizontal(|ui| {
+                        ui.label(lang.t("start_point")).on_hover_text(lang.tf(
+                            "coordinates_hint",
+                            &[&self.coords.var_names(self.num_vars).join(", ")],
+                        ));
+                        ui.add(
+                            egui::TextEdit::multiline(&mut self.initial_point_str)
+                                .desired_rows(1)
+                                .hint_text(lang.t("start_point_hint")),
+                        );
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.label(lang.t("known_optimum"))
+                            .on_hover_text(lang.t("known_optimum_hint"));
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.known_optimum_str)
+                                .hint_text(lang.t("not_set")),
+                        );
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.label(lang.t("lower_bounds"))
+                            .on_hover_text(lang.t("lower_bounds_hint"));
+                        ui.text_edit_singleline(&mut self.lower_bounds_str);
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(lang.t("upper_bounds"));
+                        ui.text_edit_singleline(&mut self.upper_bounds_str);
+                    });
+                    let has_bounds = !(self.lower_bounds_str.trim().is_empty()
+                        && self.upper_bounds_str.trim().is_empty());
+                    if has_bounds && !self.algorithm.supports_bounds() {
+                        ui.label(lang.t("bounds_unsupported"));
+                    }
+
+                    ui.checkbox(&mut self.multi_objective, lang.t("two_objectives"));
+                    if self.multi_objective {
+                        ui.horizontal(|ui| {
+                            ui.label(lang.t("function2"));
+                            ui.text_edit_singleline(&mut self.func2_str);
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label(lang.t("weight"));
+                            ui.add(egui::Slider::new(&mut self.weight, 0.0..=1.0));
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label(lang.t("weight_count"));
+                            ui.add(egui::DragValue::new(&mut self.pareto_weights).range(2..=101));
+                        });
+                    }
+
+                    ui.label(lang.t("constraints"))
+                        .on_hover_text(lang.t("constraints_hint"));
                     ui.add(
-                        egui::DragValue::new(&mut self.max_iterations)
-                            .speed(1)
-                            .range(1..=10000),
+                        egui::TextEdit::multiline(&mut self.constraints_str)
+                            .desired_rows(2)
+                            .hint_text("x1 + x2 - 1"),
                     );
-                });
+                    if !self.constraints_str.trim().is_empty() {
+                        ui.horizontal(|ui| {
+                            ui.label("μ0:");
+                            ui.add(
+                                egui::DragValue::new(&mut self.penalty.initial_mu)
+                                    .speed(0.1)
+                                    .range(1e-3..=1e6),
+                            );
+                            ui.label(lang.t("penalty_growth"));
+                            ui.add(
+                                egui::DragValue::new(&mut self.penalty.growth)
+                                    .speed(0.1)
+                                    .range(1.1..=100.0),
+                            );
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label(lang.t("penalty_outer"));
+                            ui.add(
+                                egui::DragValue::new(&mut self.penalty.outer_iterations)
+                                    .range(1..=30),
+                            );
+                        });
+                    }
+
+                    ui.separator();
+
+                    if self.algorithm == Algorithm::BarzilaiBorwein {
+                        ui.horizontal(|ui| {
+                            ui.radio_value(&mut self.bb_variant, BbVariant::Bb1, "BB1")
+                                .on_hover_text("(s·s) / (s·y)");
+                            ui.radio_value(&mut self.bb_variant, BbVariant::Bb2, "BB2")
+                                .on_hover_text("(s·y) / (y·y)");
+                        });
+                        ui.label(lang.t("bb_note"));
+                        ui.separator();
+                    }
+
+                    if self.algorithm == Algorithm::ConjugateGradient {
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut self.powell_restart, lang.t("powell_restart"));
+                            ui.add_enabled(
+                                self.powell_restart,
+                                egui::DragValue::new(&mut self.powell_threshold)
+                                    .speed(0.01)
+                                    .range(0.01..=1.0),
+                            );
+                        })
+                        .response
+                        .on_hover_text(lang.t("powell_restart_hint"));
+                        ui.separator();
+                    }
+
+                    if self.algorithm == Algorithm::Lbfgs {
+                        ui.horizontal(|ui| {
+                            ui.label(lang.t("lbfgs_memory"));
+                            ui.add(egui::DragValue::new(&mut self.lbfgs_memory).range(1..=100));
+                        })
+                        .response
+                        .on_hover_text(lang.t("lbfgs_memory_hint"));
+                        ui.separator();
+                    }
+
+                    if self.algorithm == Algorithm::Momentum {
+                        ui.horizontal(|ui| {
+                            ui.label(lang.t("momentum"));
+                            ui.add(
+                                egui::DragValue::new(&mut self.momentum)
+                                    .speed(0.01)
+                                    .range(0.0..=0.99),
+                            );
+                        });
+                        ui.checkbox(&mut self.nesterov, lang.t("nesterov"))
+                            .on_hover_text(lang.t("nesterov_hint"));
+                        ui.label(lang.t("momentum_note"));
+                        ui.separator();
+                    }
 
-                ui.separator();
+                    if self.algorithm == Algorithm::Adam {
+                        egui::CollapsingHeader::new(lang.t("adam_settings"))
+                            .default_open(true)
+                            .show(ui, |ui| {
+                                ui.horizontal(|ui| {
+                                    ui.label(lang.t("learning_rate"));
+                                    ui.add(
+                                        egui::DragValue::new(&mut self.adam.learning_rate)
+                                            .speed(0.001)
+                                            .range(1e-6..=10.0),
+                                    );
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label("β1:");
+                                    ui.add(
+                                        egui::DragValue::new(&mut self.adam.beta1)
+                                            .speed(0.001)
+                                            .range(0.0..=0.999),
+                                    );
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label("β2:");
+                                    ui.add(
+                                        egui::DragValue::new(&mut self.adam.beta2)
+                                            .speed(0.0001)
+                                            .range(0.0..=0.99999),
+                                    );
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label("ε:");
+                                    ui.add(
+                                        egui::DragValue::new(&mut self.adam.epsilon)
+                                            .speed(1e-9)
+                                            .range(1e-12..=1e-2),
+                                    );
+                                });
+                            });
+                        ui.separator();
+                    }
+
+                    if self.algorithm == Algorithm::RmsProp {
+                        egui::CollapsingHeader::new(lang.t("rmsprop_settings"))
+                            .default_open(true)
+                            .show(ui, |ui| {
+                                ui.horizontal(|ui| {
+                                    ui.label(lang.t("learning_rate"));
+                                    ui.add(
+                                        egui::DragValue::new(&mut self.rmsprop.learning_rate)
+                                            .speed(0.001)
+                                            .range(1e-6..=10.0),
+                                    );
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label("ρ:");
+                                    ui.add(
+                                        egui::DragValue::new(&mut self.rmsprop.rho)
+                                            .speed(0.001)
+                                            .range(0.0..=0.999),
+                                    );
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label("ε:");
+                                    ui.add(
+                                        egui::DragValue::new(&mut self.rmsprop.epsilon)
+                                            .speed(1e-9)
+                                            .range(1e-12..=1e-2),
+                                    );
+                                });
+                            });
+                        ui.separator();
+                    }
+
+                    if self.algorithm == Algorithm::NelderMead {
+                        egui::CollapsingHeader::new(lang.t("simplex_settings"))
+                            .default_open(true)
+                            .show(ui, |ui| {
+                                for (label, value, max) in [
+                                    (lang.t("nm_alpha"), &mut self.nelder_mead.alpha, 10.0),
+                                    (lang.t("nm_gamma"), &mut self.nelder_mead.gamma, 10.0),
+                                    (lang.t("nm_rho"), &mut self.nelder_mead.rho, 1.0),
+                                    (lang.t("nm_sigma"), &mut self.nelder_mead.sigma, 1.0),
+                                    (lang.t("nm_step"), &mut self.nelder_mead.simplex_step, 100.0),
+                                ] {
+                                    ui.horizontal(|ui| {
+                                        ui.label(label);
+                                        ui.add(
+                                            egui::DragValue::new(value)
+                                                .speed(0.01)
+                                                .range(1e-3..=max),
+                                        );
+                                    });
+                                }
+                            });
+                        ui.separator();
+                    }
+
+                    if self.algorithm == Algorithm::Annealing {
+                        egui::CollapsingHeader::new(lang.t("annealing_settings"))
+                            .default_open(true)
+                            .show(ui, |ui| {
+                                ui.horizontal(|ui| {
+                                    ui.label(lang.t("sa_temperature"));
+                                    ui.add(
+                                        egui::DragValue::new(
+                                            &mut self.annealing.initial_temperature,
+                                        )
+                                        .speed(0.01)
+                                        .range(1e-6..=1e6),
+                                    );
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label(lang.t("sa_cooling"));
+                                    ui.add(
+                                        egui::DragValue::new(&mut self.annealing.cooling)
+                                            .speed(0.0005)
+                                            .range(0.5..=0.99999),
+                                    );
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label(lang.t("sa_step"));
+                                    ui.add(
+                                        egui::DragValue::new(&mut self.annealing.step)
+                                            .speed(0.01)
+                                            .range(1e-6..=100.0),
+                                    );
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label(lang.t("sa_min_temperature"));
+                                    ui.add(
+                                        egui::DragValue::new(&mut self.annealing.min_temperature)
+                                            .speed(1e-7)
+                                            .range(0.0..=1.0)
+                                            .custom_formatter(|v, _| format!("{:.1e}", v)),
+                                    );
+                                });
+                            });
+                        ui.separator();
+                    }
+
+                    if matches!(
+                        self.algorithm,
+                        Algorithm::TrustRegionCg | Algorithm::TrustRegionDogleg
+                    ) {
+                        ui.horizontal(|ui| {
+                            ui.label(lang.t("initial_radius"));
+                            ui.add(
+                                egui::DragValue::new(&mut self.trust_radius)
+                                    .speed(0.1)
+                                    .range(1e-3..=100.0),
+                            );
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label(lang.t("max_radius"));
+                            ui.add(
+                                egui::DragValue::new(&mut self.max_trust_radius)
+                                    .speed(1.0)
+                                    .range(1e-3..=1e4),
+                            );
+                        });
+                        ui.separator();
+                    }
 
-                match self.state {
-                    OptimizerState::Idle => {
-                        if ui.button("▶ Запуск").clicked() {
-                            self.start_optimization();
+                    if self.algorithm == Algorithm::GradientDescent {
+                        egui::ComboBox::from_label(lang.t("line_search"))
+                            .selected_text(lang.t(self.line_search.label()))
+                            .show_ui(ui, |ui| {
+                                for kind in LineSearchKind::ALL {
+                                    ui.selectable_value(
+                                        &mut self.line_search,
+                                        kind,
+                                        lang.t(kind.label()),
+                                    );
+                                }
+                            });
+                        ui.checkbox(&mut self.perturb_enabled, lang.t("pgd"))
+                            .on_hover_text(lang.t("pgd_hint"));
+                        if self.perturb_enabled {
+                            ui.horizontal(|ui| {
+                                ui.label(lang.t("pgd_threshold"));
+                                ui.add(
+                                    egui::DragValue::new(&mut self.perturb.threshold)
+                                        .speed(1e-4)
+                                        .range(1e-10..=1.0),
+                                );
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label(lang.t("pgd_patience"));
+                                ui.add(
+                                    egui::DragValue::new(&mut self.perturb.patience)
+                                        .range(1..=1000),
+                                );
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label(lang.t("pgd_radius"));
+                                ui.add(
+                                    egui::DragValue::new(&mut self.perturb.radius)
+                                        .speed(0.01)
+                                        .range(1e-6..=10.0),
+                                );
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label(lang.t("pgd_max"));
+                                ui.add(
+                                    egui::DragValue::new(&mut self.perturb.max_perturbations)
+                                        .range(1..=100),
+                                );
+                            });
                         }
+                        ui.separator();
                     }
-                    OptimizerState::Running | OptimizerState::Stopping => {
-                        let button_text = if self.state == OptimizerState::Running {
-                            "⏸ Стоп"
-                        } else {
-                            "⏹ Остановка..."
-                        };
-                        if ui.button(button_text).clicked() {
-                            self.stop_optimization();
+
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut self.fixed_seed, lang.t("fixed_seed"))
+                            .on_hover_text(lang.t("fixed_seed_hint"));
+                        ui.add_enabled(self.fixed_seed, egui::DragValue::new(&mut self.seed));
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.label(lang.t("initial_step"));
+                        ui.add_enabled(
+                            !self.auto_step,
+                            egui::DragValue::new(&mut self.initial_step)
+                                .speed(0.1)
+                                .range(1e-6..=10.0),
+                        );
+                        ui.checkbox(&mut self.auto_step, lang.t("auto_step"))
+                            .on_hover_text(lang.t("auto_step_hint"));
+                        if let (true, Some(step)) = (self.auto_step, self.estimated_step) {
+                            ui.label(format!("≈ {:.3e}", step));
                         }
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.label(lang.t("step_decay"));
+                        ui.add(
+                            egui::DragValue::new(&mut self.step_decay)
+                                .speed(0.05)
+                                .range(0.1..=0.9),
+                        );
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.label(lang.t("max_backtracks"));
+                        ui.add(egui::DragValue::new(&mut self.max_backtracks).range(1..=1000))
+                            .on_hover_text(lang.t("max_backtracks_hint"));
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.label(lang.t("step_increase"));
+                        ui.add(
+                            egui::DragValue::new(&mut self.step_increase)
+                                .speed(0.1)
+                                .range(1.0..=2.0),
+                        );
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.label(lang.t("max_step"));
+                        ui.add(
+                            egui::DragValue::new(&mut self.max_step)
+                                .speed(0.1)
+                                .range(1e-3..=1e3),
+                        );
+                    })
+                    .response
+                    .on_hover_text(lang.t("max_step_hint"));
+
+                    if self.algorithm == Algorithm::GradientDescent
+                        && self.line_search == LineSearchKind::Armijo
+                    {
+                        ui.horizontal(|ui| {
+                            ui.label(lang.t("armijo_c1"));
+                            ui.add(
+                                egui::DragValue::new(&mut self.armijo_c1)
+                                    .speed(1e-5)
+                                    .range(1e-8..=0.5),
+                            )
+                            .on_hover_text(lang.t("armijo_hint"));
+                        });
                     }
-                    OptimizerState::Finished => {
-                        if ui.button("Сброс").clicked() {
-                            self.result = None;
-                            self.state = OptimizerState::Idle;
+
+                    ui.separator();
+
+                    ui.horizontal(|ui| {
+                        ui.label(lang.t("tolerance"));
+                        ui.add(
+                            egui::DragValue::new(&mut self.tolerance)
+                                .speed(1e-7)
+                                .range(1e-12..=1.0),
+                        );
+                    });
+
+                    if self.algorithm == Algorithm::GradientDescent {
+                        ui.horizontal(|ui| {
+                            ui.radio_value(&mut self.rel_grad, false, lang.t("grad_test_absolute"));
+                            ui.radio_value(&mut self.rel_grad, true, lang.t("grad_test_relative"))
+                                .on_hover_text(lang.t("grad_test_relative_hint"));
+                        });
+                        ui.checkbox(&mut self.scale_tolerance, lang.t("scale_tolerance"))
+                            .on_hover_text(lang.t("scale_tolerance_hint"));
+                        ui.checkbox(&mut self.normalize_objective, lang.t("normalize_objective"))
+                            .on_hover_text(lang.t("normalize_objective_hint"));
+                        for (label, value, hint) in [
+                            ("ftol:", &mut self.ftol, lang.t("ftol_hint")),
+                            ("xtol:", &mut self.xtol, lang.t("xtol_hint")),
+                        ] {
+                            ui.horizontal(|ui| {
+                                ui.label(label).on_hover_text(hint);
+                                ui.add(egui::DragValue::new(value).speed(1e-7).range(0.0..=1.0));
+                            });
+                        }
+                        ui.horizontal(|ui| {
+                            ui.label(lang.t("restart_period"));
+                            ui.add(
+                                egui::DragValue::new(&mut self.restart_period)
+                                    .speed(1)
+                                    .range(0..=10000),
+                            );
+                        })
+                        .response
+                        .on_hover_text(lang.t("restart_period_hint"));
+                        if self.restart_period > 0 {
+                            ui.checkbox(&mut self.restart_doubling, lang.t("restart_doubling"));
                         }
+                        ui.horizontal(|ui| {
+                            ui.label(lang.t("max_grad_norm"));
+                            ui.add(
+                                egui::DragValue::new(&mut self.max_grad_norm)
+                                    .speed(0.1)
+                                    .range(0.0..=1e6),
+                            );
+                        })
+                        .response
+                        .on_hover_text(lang.t("max_grad_norm_hint"));
                     }
-                }
 
-                if let Some(err) = &self.error_message {
-                    ui.colored_label(egui::Color32::RED, err);
-                }
+                    ui.horizontal(|ui| {
+                        ui.label(lang.t("max_iterations"));
+                        ui.add(
+                            egui::DragValue::new(&mut self.max_iterations)
+                                .speed(1)
+                                .range(1..=10000),
+                        );
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(lang.t("max_history_points"));
+                        ui.add(
+                            egui::DragValue::new(&mut self.max_history_points)
+                                .speed(10)
+                                .range(0..=10000),
+                        );
+                    })
+                    .response
+                    .on_hover_text(lang.t("max_history_points_hint"));
+
+                    ui.checkbox(&mut self.detect_flat, lang.t("detect_flat"))
+                        .on_hover_text(lang.t("detect_flat_hint"));
+                    ui.checkbox(&mut self.verbose_log, lang.t("verbose_log"));
+
+                    ui.separator();
+
+                    if self.num_vars == 2 {
+                        egui::CollapsingHeader::new(lang.t("sensitivity")).show(ui, |ui| {
+                            let names = self.coords.var_names(self.num_vars);
+                            for (k, label) in [
+                                format!("{} min:", names[0]),
+                                format!("{} max:", names[0]),
+                                format!("{} min:", names[1]),
+                                format!("{} max:", names[1]),
+                            ]
+                            .iter()
+                            .enumerate()
+                            {
+                                ui.horizontal(|ui| {
+                                    ui.label(label);
+                                    ui.add(
+                                        egui::DragValue::new(&mut self.heatmap_bounds[k])
+                                            .speed(0.1),
+                                    );
+                                });
+                            }
+                            ui.horizontal(|ui| {
+                                ui.label(lang.t("nodes_per_axis"));
+                                ui.add(
+                                    egui::DragValue::new(&mut self.heatmap_resolution)
+                                        .range(2..=60),
+                                );
+                            });
+                            ui.horizontal(|ui| {
+                                ui.radio_value(
+                                    &mut self.heatmap_metric,
+                                    HeatmapMetric::FinalValue,
+                                    "f(x*)",
+                                );
+                                ui.radio_value(
+                                    &mut self.heatmap_metric,
+                                    HeatmapMetric::Iterations,
+                                    lang.t("metric_iterations"),
+                                );
+                            });
+                            let enabled = self.state == OptimizerState::Idle;
+                            if ui
+                                .add_enabled(enabled, egui::Button::new(lang.t("build_map")))
+                                .clicked()
+                            {
+                                self.start_sensitivity_map();
+                            }
+                        });
+                        ui.separator();
+                    }
+
+                    egui::CollapsingHeader::new(lang.t("random_restarts")).show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label(lang.t("restart_count"));
+                            ui.add(egui::DragValue::new(&mut self.restarts.count).range(1..=500));
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label(lang.t("restart_box"));
+                            ui.add(egui::DragValue::new(&mut self.restarts.lower).speed(0.1));
+                            ui.label("..");
+                            ui.add(egui::DragValue::new(&mut self.restarts.upper).speed(0.1));
+                        })
+                        .response
+                        .on_hover_text(lang.t("restart_box_hint"));
+                        let enabled = self.state == OptimizerState::Idle;
+                        if ui
+                            .add_enabled(enabled, egui::Button::new(lang.t("run_restarts")))
+                            .clicked()
+                        {
+                            self.start_random_restarts();
+                        }
+                    });
+                    ui.separator();
+
+                    ui.checkbox(&mut self.convergence_plot, lang.t("convergence_plot"));
+                    if self.convergence_plot {
+                        ui.checkbox(&mut self.log_scale_f, lang.t("log_scale"));
+                    }
+                    ui.checkbox(&mut self.show_contours, lang.t("contours"));
+                    if self.show_contours {
+                        ui.horizontal(|ui| {
+                            ui.label(lang.t("grid_resolution"));
+                            ui.add(egui::Slider::new(&mut self.contour_resolution, 20..=200));
+                        });
+                    }
+                    ui.checkbox(&mut self.equal_axes, lang.t("equal_axes"));
+                    ui.horizontal(|ui| {
+                        ui.label(lang.t("plot_aspect"));
+                        ui.add(
+                            egui::DragValue::new(&mut self.plot_aspect)
+                                .speed(0.05)
+                                .range(0.25..=4.0),
+                        );
+                    });
+                    ui.checkbox(&mut self.fixed_frame, lang.t("fixed_frame"))
+                        .on_hover_text(lang.t("fixed_frame_hint"));
+                    if self.fixed_frame && self.num_vars == 2 {
+                        let names = self.coords.var_names(self.num_vars);
+                        for (k, label) in [
+                            format!("{} min:", names[0]),
+                            format!("{} max:", names[0]),
+                            format!("{} min:", names[1]),
+                            format!("{} max:", names[1]),
+                        ]
+                        .iter()
+                        .enumerate()
+                        {
+                            ui.horizontal(|ui| {
+                                ui.label(label);
+                                ui.add(egui::DragValue::new(&mut self.frame_bounds[k]).speed(0.1));
+                            });
+                        }
+                    }
+
+                    ui.separator();
+
+                    ui.horizontal(|ui| {
+                        if ui.button(lang.t("save_run")).clicked() {
+                            self.save_run();
+                        }
+                        if ui
+                            .add_enabled(
+                                !matches!(
+                                    self.state,
+                                    OptimizerState::Running | OptimizerState::Stopping
+                                ),
+                                egui::Button::new(lang.t("load_run")),
+                            )
+                            .clicked()
+                        {
+                            self.load_run();
+                        }
+                        if ui
+                            .add_enabled(
+                                !matches!(
+                                    self.state,
+                                    OptimizerState::Running | OptimizerState::Stopping
+                                ),
+                                egui::Button::new(lang.t("restore_defaults")),
+                            )
+                            .clicked()
+                        {
+                            self.reset_params();
+                        }
+                    });
+
+                    match self.state {
+                        OptimizerState::Idle => {
+                            if ui
+                                .button(lang.t("run"))
+                                .on_hover_text(lang.t("shortcut_run"))
+                                .clicked()
+                            {
+                                self.start_optimization();
+                            }
+                            self.show_perturb_controls(ui);
+                            if self.multi_objective && ui.button(lang.t("pareto_front")).clicked() {
+                                self.start_pareto_sweep();
+                            }
+                            ui.horizontal(|ui| {
+                                if ui
+                                    .button(lang.t("solve_auto"))
+                                    .on_hover_text(lang.t("solve_auto_hint"))
+                                    .clicked()
+                                {
+                                    self.start_portfolio();
+                                }
+                                ui.label(lang.t("portfolio_budget"));
+                                ui.add(
+                                    egui::DragValue::new(&mut self.portfolio_budget)
+                                        .range(0..=10_000_000)
+                                        .speed(100.0),
+                                )
+                                .on_hover_text(lang.t("portfolio_budget_hint"));
+                            });
+                            let mut compare = false;
+                            ui.horizontal(|ui| {
+                                compare = ui
+                                    .button(lang.t("compare_with"))
+                                    .on_hover_text(lang.t("compare_with_hint"))
+                                    .clicked();
+                                egui::ComboBox::from_id_salt("compare_algorithm")
+                                    .selected_text(lang.t(self.compare_algorithm.label()))
+                                    .show_ui(ui, |ui| {
+                                        for algorithm in self.algorithms() {
+                                            ui.selectable_value(
+                                                &mut self.compare_algorithm,
+                                                algorithm,
+                                                lang.t(algorithm.label()),
+                                            );
+                                        }
+                                    });
+                            });
+                            if compare {
+                                self.start_pair_comparison();
+                            }
+                            if ui
+                                .button(lang.t("auto_tune"))
+                                .on_hover_text(lang.t("auto_tune_hint"))
+                                .clicked()
+                            {
+                                self.start_auto_tune();
+                            }
+                        }
+                        OptimizerState::Running | OptimizerState::Stopping => {
+                            let button_text = if self.state == OptimizerState::Running {
+                                lang.t("stop")
+                            } else {
+                                lang.t("stopping")
+                            };
+                            if ui
+                                .button(button_text)
+                                .on_hover_text(lang.t("shortcut_stop"))
+                                .clicked()
+                            {
+                                self.stop_optimization();
+                            }
+                        }
+                        OptimizerState::Finished => {
+                            let resumable = self.result.as_ref().is_some_and(|res| {
+                                res.termination == TerminationReason::MaxIterations
+                            }) && self.constraint_violations.is_empty();
+                            if resumable
+                                && ui
+                                    .button(lang.t("continue_run"))
+                                    .on_hover_text(lang.t("continue_run_hint"))
+                                    .clicked()
+                            {
+                                self.continue_optimization();
+                            }
+                            if self.result.is_some() && ui.button(lang.t("to_comparison")).clicked()
+                            {
+                                self.freeze_result();
+                            }
+                            self.show_perturb_controls(ui);
+                            if self.result.is_some() && ui.button(lang.t("export_csv")).clicked() {
+                                self.export_trajectory();
+                            }
+                            if self.result.is_some()
+                                && self.num_vars == 2
+                                && ui.button(lang.t("save_png")).clicked()
+                            {
+                                self.request_plot_png(ui.ctx());
+                            }
+                            if ui
+                                .button(lang.t("reset"))
+                                .on_hover_text(lang.t("shortcut_reset"))
+                                .clicked()
+                            {
+                                self.reset_result();
+                            }
+                            if self.num_vars == 2 {
+                                self.show_animation_controls(ui);
+                            }
+                        }
+                    }
+
+                    if let Some(warning) = &self.warning_message {
+                        ui.colored_label(Palette::new(ui.visuals()).warn, warning);
+                    }
+                    if let Some(err) = &self.error_message {
+                        ui.colored_label(egui::Color32::RED, err);
+                    }
+                });
             });
 
+        self.update_contours();
+        self.update_surface();
+
         egui::CentralPanel::default().show(ctx, |ui| {
-            ui.heading("Визуализация");
+            ui.heading(lang.t("visualization"));
+
+            if !self.comparison.is_empty() {
+                let current = self.result.as_ref().map(|res| (self.last_method, res));
+                show_comparison_table(ui, &mut self.comparison, current, lang);
+            }
+
+            if !self.ranking.is_empty() {
+                egui::CollapsingHeader::new(lang.t("ranking"))
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        egui::Grid::new("ranking_table")
+                            .striped(true)
+                            .show(ui, |ui| {
+                                ui.label(lang.t("number"));
+                                ui.label(lang.t("method"));
+                                ui.label("f(x*)");
+                                ui.label(lang.t("iterations"));
+                                ui.label(lang.t("evaluations"));
+                                ui.end_row();
+                                for (k, run) in self.ranking.iter().enumerate() {
+                                    ui.label((k + 1).to_string());
+                                    ui.label(lang.t(run.method.label()));
+                                    ui.label(format!("{:.6}", run.f_x));
+                                    ui.label(run.iterations.to_string());
+                                    if self.ranking_budget > 0 {
+                                        ui.label(format!(
+                                            "{} / {}",
+                                            run.evaluations, self.ranking_budget
+                                        ));
+                                    } else {
+                                        ui.label(run.evaluations.to_string());
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                        if self.ranking_budget > 0 {
+                            ui.label(lang.tf("ranking_budget", &[&self.ranking_budget]));
+                        }
+                        ui.label(lang.t("ranking_note"));
+                    });
+            }
+
+            if let Some(report) = &self.tuning {
+                show_tuning_report(ui, report, lang);
+            }
+
+            if self.result.is_none() && !self.live_path.is_empty() {
+                show_live_progress(ui, self.coords, &self.live_path, self.num_vars, lang);
+            }
 
+            let mut clear_log = false;
             if let Some(res) = &self.result {
-                ui.label(format!(
-                    "Результат: x* = [{}], f(x*) = {:.6}, итераций: {}",
-                    res.x
+                ui.label(
+                    lang.tf(
+                        "result",
+                        &[
+                            &res.x
+                                .iter()
+                                .map(|v| format!("{:.6}", v))
+                                .collect::<Vec<_>>()
+                                .join(", "),
+                            &format!("{:.6}", res.f_x),
+                            &res.iterations,
+                        ],
+                    ),
+                );
+                if let Some((point, value)) = self.known_minimum() {
+                    ui.label(
+                        lang.tf(
+                            "known_minimum",
+                            &[
+                                &point
+                                    .iter()
+                                    .map(|v| format!("{:.6}", v))
+                                    .collect::<Vec<_>>()
+                                    .join(", "),
+                                &format!("{:.6}", value),
+                                &format!("{:.3e}", res.f_x - value),
+                            ],
+                        ),
+                    );
+                }
+                ui.label(lang.tf(
+                    "eval_stats",
+                    &[
+                        &res.f_evals,
+                        &res.grad_evals,
+                        &format!("{:.2}", res.elapsed.as_secs_f64() * 1000.0),
+                    ],
+                ));
+                let (color, message) =
+                    termination_message(res.termination, Palette::new(ui.visuals()), lang);
+                ui.colored_label(color, message);
+                if let Some(source) = self.hessian_source {
+                    ui.label(lang.t(source));
+                }
+                if !self.restart_finals.is_empty() {
+                    show_restart_summary(ui, &self.restart_finals, res.f_x, lang);
+                }
+                if !self.constraint_violations.is_empty() {
+                    let violations = self
+                        .constraint_violations
                         .iter()
-                        .map(|v| format!("{:.6}", v))
+                        .enumerate()
+                        .map(|(k, v)| format!("g{}(x*) = {:.3e}", k + 1, v))
                         .collect::<Vec<_>>()
-                        .join(", "),
-                    res.f_x,
-                    res.iterations
-                ));
-                if res.terminated_early {
-                    ui.colored_label(egui::Color32::YELLOW, "Досрочно остановлено пользователем");
+                        .join(", ");
+                    ui.label(lang.tf("constraint_violations", &[&violations]));
+                }
+                if !res.perturbations.is_empty() {
+                    ui.label(lang.tf("perturbation_count", &[&res.perturbations.len()]));
+                }
+
+                if !res.log.is_empty() {
+                    egui::CollapsingHeader::new(lang.tf("log", &[&res.log.len()]))
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            clear_log = ui.button(lang.t("clear_log")).clicked();
+                            // Рисуются только видимые строки длинного лога
+                            let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+                            egui::ScrollArea::vertical()
+                                .max_height(200.0)
+                                .auto_shrink([false, true])
+                                .show_rows(ui, row_height, res.log.len(), |ui, rows| {
+                                    for line in &res.log[rows] {
+                                        ui.monospace(line);
+                                    }
+                                });
+                        });
                 }
 
-                if self.num_vars == 2 && !res.history.is_empty() {
-                    let points: PlotPoints = res.history.iter().map(|(x, y, _)| [*x, *y]).collect();
-                    let line = Line::new(points).name("Путь спуска");
-                    Plot::new("path_plot").view_aspect(1.0).show(ui, |plot_ui| {
-                        plot_ui.line(line);
+                if !res.history.is_empty() {
+                    egui::CollapsingHeader::new(lang.tf("iterates", &[&res.history.len()])).show(
+                        ui,
+                        |ui| {
+                            let names = self.coords.var_names(self.num_vars);
+                            show_iterates_table(ui, res, &names, &mut self.iterates_sort, lang);
+                        },
+                    );
+                }
+
+                // Для взвешенной суммы и задачи со штрафом матрица Гессе одной f
+                // ничего не говорит о найденной точке
+                if !self.multi_objective && self.constraint_violations.is_empty() {
+                    if let Some(func) = &self.parsed_func {
+                        show_hessian_diagnostics(ui, func, &res.x, self.maximize, lang);
+                    }
+                }
+
+                self.show_error_ratios(ui, res);
+                self.show_distance_to_optimum(ui, res);
+
+                if !res.trust_radii.is_empty() {
+                    egui::CollapsingHeader::new(lang.t("trust_radius")).show(ui, |ui| {
+                        let radii: PlotPoints = res
+                            .trust_radii
+                            .iter()
+                            .enumerate()
+                            .map(|(k, r)| [res.iteration_at(k) as f64, *r])
+                            .collect();
+                        Plot::new("trust_radius_plot")
+                            .height(150.0)
+                            .x_axis_label(lang.t("iteration"))
+                            .show(ui, |plot_ui| {
+                                plot_ui.line(Line::new(radii).name("Δ"));
+                            });
+                    });
+                }
+
+                if !res.grad_norms.is_empty() {
+                    egui::CollapsingHeader::new(lang.t("grad_norm"))
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            Plot::new("grad_norm_plot")
+                                .height(150.0)
+                                .x_axis_label(lang.t("iteration"))
+                                .y_axis_formatter(|mark, _| {
+                                    format!("{:.0e}", 10f64.powf(mark.value))
+                                })
+                                .legend(Legend::default())
+                                .show(ui, |plot_ui| {
+                                    plot_ui.line(
+                                        Line::new(log10_points(res, &res.grad_norms)).name("||g||"),
+                                    );
+                                    plot_ui.line(
+                                        Line::new(log10_points(res, &res.step_sizes))
+                                            .name("||Δx||"),
+                                    );
+                                });
+                        });
+                }
+
+                if self.coords != CoordinateSystem::Cartesian {
+                    let cartesian = self.coords.to_cartesian(res.x.as_slice());
+                    ui.label(
+                        lang.tf(
+                            "cartesian_point",
+                            &[&cartesian
+                                .iter()
+                                .map(|v| format!("{:.6}", v))
+                                .collect::<Vec<_>>()
+                                .join(", ")],
+                        ),
+                    );
+                }
+
+                if self.convergence_plot {
+                    self.show_convergence_plot(ui, res);
+                } else if self.num_vars == 2 && !res.history.is_empty() {
+                    ui.horizontal(|ui| {
+                        ui.selectable_value(&mut self.surface_view, false, lang.t("flat_view"));
+                        ui.selectable_value(&mut self.surface_view, true, lang.t("surface_view"));
+                        if self.surface_view {
+                            ui.separator();
+                            ui.label(lang.t("surface_grid"));
+                            ui.add(egui::Slider::new(&mut self.surface_resolution, 10..=100));
+                            ui.label(lang.t("yaw"));
+                            ui.add(
+                                egui::DragValue::new(&mut self.surface_yaw)
+                                    .speed(1.0)
+                                    .suffix("°"),
+                            );
+                            ui.label(lang.t("pitch"));
+                            ui.add(
+                                egui::DragValue::new(&mut self.surface_pitch)
+                                    .speed(1.0)
+                                    .range(0.0..=90.0)
+                                    .suffix("°"),
+                            );
+                        }
                     });
+                    let mut path = path_points(self.coords, res);
+                    // Границы графика по всему пути, чтобы при анимации вид не менялся
+                    let full_extent: Vec<[f64; 2]> = if self.animation_frame.is_some() {
+                        path.clone()
+                    } else {
+                        Vec::new()
+                    };
+                    let current = self.animation_frame.map(|frame| {
+                        let frame = frame.min(res.history.len() - 1);
+                        path.truncate(frame + 1);
+                        let (x, f_x) = &res.history[frame];
+                        ui.label(
+                            lang.tf(
+                                "iteration_point",
+                                &[
+                                    &frame,
+                                    &x.iter()
+                                        .map(|v| format!("{:.6}", v))
+                                        .collect::<Vec<_>>()
+                                        .join(", "),
+                                    &format!("{:.6}", f_x),
+                                ],
+                            ),
+                        );
+                        path[frame]
+                    });
+                    if let (true, Some(cache)) = (self.surface_view, &self.surface) {
+                        self.show_surface(ui, &cache.surface, res, path.len());
+                    } else {
+                        let points: PlotPoints = path.into();
+                        let kicks: Vec<[f64; 2]> = res
+                            .perturbations
+                            .iter()
+                            .filter_map(|&k| points.points().get(res.position_of(k + 1)))
+                            .map(|p| [p.x, p.y])
+                            .collect();
+                        let value_range = path_value_range(&res.history);
+                        let segments = value_range.map(|range| {
+                            let values: Vec<f64> = res.history.iter().map(|(_, f)| *f).collect();
+                            let path: Vec<[f64; 2]> =
+                                points.points().iter().map(|p| [p.x, p.y]).collect();
+                            colored_path(&path, &values, range)
+                        });
+                        let path_name = self.path_label.as_deref().unwrap_or(lang.t("path"));
+                        let line = Line::new(points).name(path_name);
+                        let mut plot = Plot::new("path_plot").view_aspect(self.plot_aspect);
+                        // Подписи осей добавляются только в кадр, который попадёт в PNG
+                        let capturing = self.png_path.is_some() && self.png_rect.is_none();
+                        if capturing {
+                            let names = self.coords.var_names(self.num_vars);
+                            plot = plot
+                                .x_axis_label(names[0].clone())
+                                .y_axis_label(names[1].clone());
+                        }
+                        // Рамка с пустым диапазоном по одной из осей не фиксируется
+                        let [x_min, x_max, y_min, y_max] = self.frame_bounds;
+                        let frame = (self.fixed_frame && x_min < x_max && y_min < y_max)
+                            .then(|| PlotBounds::from_min_max([x_min, y_min], [x_max, y_max]));
+                        if frame.is_some() {
+                            plot = plot
+                                .allow_drag(false)
+                                .allow_zoom(false)
+                                .allow_scroll(false)
+                                .allow_boxed_zoom(false);
+                        } else {
+                            for p in &full_extent {
+                                plot = plot.include_x(p[0]).include_y(p[1]);
+                            }
+                            if self.equal_axes {
+                                plot = plot.data_aspect(1.0);
+                            }
+                        }
+                        if !self.comparison.is_empty() {
+                            plot = plot.legend(Legend::default());
+                        }
+                        let palette = Palette::new(ui.visuals());
+                        let response = plot.show(ui, |plot_ui| {
+                            if let Some(frame) = frame {
+                                plot_ui.set_plot_bounds(frame);
+                            }
+                            if let Some(cache) = &self.contours {
+                                let count = cache.lines.len().max(2) - 1;
+                                for (k, (_, segments)) in cache.lines.iter().enumerate() {
+                                    let color =
+                                        heat_color(k as f64 / count as f64).gamma_multiply(0.5);
+                                    for segment in segments {
+                                        plot_ui.line(
+                                            Line::new(PlotPoints::from(segment.to_vec()))
+                                                .color(color)
+                                                .width(1.0),
+                                        );
+                                    }
+                                }
+                            }
+                            for run in &self.comparison {
+                                plot_ui.line(
+                                    Line::new(PlotPoints::from(path_points(
+                                        self.coords,
+                                        &run.result,
+                                    )))
+                                    .name(&run.name),
+                                );
+                            }
+                            match segments {
+                                Some(segments) => {
+                                    for (color, points) in segments {
+                                        plot_ui.line(
+                                            Line::new(PlotPoints::from(points))
+                                                .color(color)
+                                                .width(2.0)
+                                                .name(path_name),
+                                        );
+                                    }
+                                }
+                                None => plot_ui.line(line),
+                            }
+                            if let Some(current) = current {
+                                plot_ui.points(
+                                    Points::new(PlotPoints::from(vec![current]))
+                                        .radius(5.0)
+                                        .color(palette.current)
+                                        .name(lang.t("current_point")),
+                                );
+                            }
+                            if !kicks.is_empty() {
+                                plot_ui.points(
+                                    Points::new(PlotPoints::from(kicks))
+                                        .radius(4.0)
+                                        .color(egui::Color32::RED)
+                                        .name(lang.t("perturbations")),
+                                );
+                            }
+                            if !self.restart_finals.is_empty() {
+                                let finals: Vec<[f64; 2]> = self
+                                    .restart_finals
+                                    .iter()
+                                    .map(|(x, _)| {
+                                        let p = self.coords.to_cartesian(x.as_slice());
+                                        [p[0], p[1]]
+                                    })
+                                    .collect();
+                                plot_ui.points(
+                                    Points::new(PlotPoints::from(finals))
+                                        .radius(3.0)
+                                        .color(palette.finals)
+                                        .name(lang.t("restart_finals")),
+                                );
+                                let best = self.coords.to_cartesian(res.x.as_slice());
+                                plot_ui.points(
+                                    Points::new(PlotPoints::from(vec![[best[0], best[1]]]))
+                                        .radius(6.0)
+                                        .color(palette.good)
+                                        .name(lang.t("restart_best")),
+                                );
+                            }
+                            plot_ui.pointer_coordinate()
+                        });
+                        if capturing {
+                            self.png_rect = Some(response.response.rect);
+                            ui.ctx()
+                                .send_viewport_cmd(egui::ViewportCommand::Screenshot);
+                        }
+                        if let (Some(pointer), Some(func)) = (response.inner, &self.parsed_func) {
+                            response.response.on_hover_text_at_pointer(hover_text(
+                                func,
+                                self.coords,
+                                self.gradient_mode,
+                                self.grad_eps,
+                                pointer,
+                                lang,
+                            ));
+                        }
+                        if let Some(range) = value_range {
+                            show_color_bar(ui, range, lang);
+                        }
+                    }
                 } else if self.num_vars != 2 {
-                    ui.label("График доступен только для 2D задач.");
+                    ui.label(lang.t("path_2d_only"));
+                }
+            } else if !self.multi_start.is_empty() {
+                self.show_multi_start(ui);
+            } else if let Some(map) = &self.sensitivity {
+                self.show_sensitivity_map(ui, map);
+            } else if !self.comparison.is_empty() && self.num_vars == 2 {
+                let mut plot = Plot::new("comparison_plot")
+                    .view_aspect(self.plot_aspect)
+                    .legend(Legend::default());
+                if self.equal_axes {
+                    plot = plot.data_aspect(1.0);
                 }
+                plot.show(ui, |plot_ui| {
+                    for run in &self.comparison {
+                        plot_ui.line(
+                            Line::new(PlotPoints::from(path_points(self.coords, &run.result)))
+                                .name(&run.name),
+                        );
+                    }
+                });
+            } else if let Some(front) = &self.pareto_front {
+                ui.label(lang.tf("pareto_count", &[&front.len()]));
+
+                egui::ScrollArea::vertical()
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("pareto_table")
+                            .striped(true)
+                            .show(ui, |ui| {
+                                ui.label("w");
+                                ui.label("x*");
+                                ui.label("f1");
+                                ui.label("f2");
+                                ui.label(lang.t("iterations"));
+                                ui.end_row();
+                                for p in front {
+                                    ui.label(format!("{:.3}", p.weight));
+                                    ui.label(
+                                        p.x.iter()
+                                            .map(|v| format!("{:.4}", v))
+                                            .collect::<Vec<_>>()
+                                            .join(", "),
+                                    );
+                                    ui.label(format!("{:.6}", p.f1));
+                                    ui.label(format!("{:.6}", p.f2));
+                                    ui.label(p.iterations.to_string());
+                                    ui.end_row();
+                                }
+                            });
+                    });
+
+                let points: Vec<[f64; 2]> = front.iter().map(|p| [p.f1, p.f2]).collect();
+                Plot::new("pareto_plot")
+                    .view_aspect(self.plot_aspect)
+                    .x_axis_label("f1")
+                    .y_axis_label("f2")
+                    .show(ui, |plot_ui| {
+                        plot_ui.line(
+                            Line::new(PlotPoints::from(points.clone()))
+                                .name(lang.t("pareto_front")),
+                        );
+                        plot_ui.points(Points::new(PlotPoints::from(points)).radius(4.0));
+                    });
             } else {
-                ui.label("Запустите оптимизацию для отображения результатов.");
+                ui.label(lang.t("no_results"));
             }
+            if clear_log {
+                if let Some(res) = &mut self.result {
+                    res.log.clear();
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quadratic_run() -> OptimizerResult {
+        let f = |x: &DVector<f64>| x.dot(x);
+        let grad = |x: &DVector<f64>| 2.0 * x;
+        optimizer::gradient_descent(
+            DVector::from_vec(vec![1.0, -2.0]),
+            &f,
+            &grad,
+            &DescentParams::default(),
+            &AdaptiveHalving::new(1.0, 0.5, 1.2, 1.0),
+            Arc::new(AtomicBool::new(false)),
+        )
+    }
+
+    fn rosenbrock_objective() -> Objective {
+        Objective {
+            main: ParsedFunction::new("(1-x1)^2+100*(x2-x1^2)^2", 2).unwrap(),
+            second: None,
+            residuals: None,
+            mode: GradientMode::Central,
+            eps: 1e-6,
+            maximize: false,
+        }
+    }
+
+    #[test]
+    fn portfolio_budget_stops_every_method() {
+        let app = GradientDescentApp::default();
+        let objective = rosenbrock_objective();
+        let start = DVector::from_vec(vec![-1.2, 1.0]);
+        for algorithm in app.algorithms() {
+            let method = app.method_config_for(algorithm);
+            let (result, used) = method.run_with_budget(
+                start.clone(),
+                &objective,
+                50,
+                Arc::new(AtomicBool::new(false)),
+            );
+            // Превышение возможно только в пределах одной итерации
+            assert!(used < 50 + 20, "{}: {} вычислений", algorithm.label(), used);
+            assert!(result.f_evals + result.grad_evals <= used);
+        }
+    }
+
+    #[test]
+    fn portfolio_without_budget_runs_to_convergence() {
+        let app = GradientDescentApp::default();
+        let method = app.method_config_for(Algorithm::Bfgs);
+        let (result, used) = method.run_with_budget(
+            DVector::from_vec(vec![-1.2, 1.0]),
+            &rosenbrock_objective(),
+            0,
+            Arc::new(AtomicBool::new(false)),
+        );
+        assert!(result.termination.is_converged());
+        assert_eq!(used, result.f_evals + result.grad_evals);
+    }
+
+    #[test]
+    fn saved_run_keeps_comparison() {
+        let saved = SavedRun {
+            config: RunConfig::default(),
+            result: Some(quadratic_run()),
+            comparison: vec![ComparisonRun {
+                name: "BFGS".to_string(),
+                note: "заметка".to_string(),
+                method: Algorithm::Bfgs,
+                result: quadratic_run(),
+            }],
+        };
+        let json = serde_json::to_string(&saved).unwrap();
+        let loaded: SavedRun = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.comparison.len(), 1);
+        let run = &loaded.comparison[0];
+        assert_eq!((run.name.as_str(), run.note.as_str()), ("BFGS", "заметка"));
+        assert!(run.method == Algorithm::Bfgs);
+        assert_eq!(run.result.x, saved.comparison[0].result.x);
+    }
+
+    #[test]
+    fn saved_run_without_comparison_loads() {
+        let json = serde_json::json!({
+            "config": RunConfig::default(),
+            "result": null,
         });
+        let loaded: SavedRun = serde_json::from_value(json).unwrap();
+        assert!(loaded.comparison.is_empty());
+    }
+
+    #[test]
+    fn run_config_round_trips_through_json() {
+        let app = GradientDescentApp {
+            algorithm: Algorithm::Bfgs,
+            func_str: "x1^2 + 3*x2^2 + x3".to_string(),
+            num_vars: 3,
+            initial_point_str: "1, 2, 3".to_string(),
+            initial_step: 0.25,
+            tolerance: 1e-9,
+            max_iterations: 1234,
+            ..GradientDescentApp::default()
+        };
+        let json = serde_json::to_string(&app.run_config()).unwrap();
+
+        let mut restored = GradientDescentApp::default();
+        restored.apply_run_config(serde_json::from_str(&json).unwrap());
+        assert!(restored.algorithm == Algorithm::Bfgs);
+        assert_eq!(restored.func_str, app.func_str);
+        assert_eq!(restored.num_vars, 3);
+        assert_eq!(restored.initial_point_str, "1, 2, 3");
+        assert_eq!(restored.initial_step, 0.25);
+        assert_eq!(restored.tolerance, 1e-9);
+        assert_eq!(restored.max_iterations, 1234);
+        assert_eq!(serde_json::to_string(&restored.run_config()).unwrap(), json);
+    }
+
+    #[test]
+    fn maximize_reports_true_objective_values() {
+        let app = GradientDescentApp::default();
+        let objective = Objective {
+            main: ParsedFunction::new("-(x1^2 + x2^2)", 2).unwrap(),
+            maximize: true,
+            ..rosenbrock_objective()
+        };
+        let result = app.method_config_for(Algorithm::GradientDescent).run(
+            DVector::from_vec(vec![1.0, -2.0]),
+            &objective,
+            Arc::new(AtomicBool::new(false)),
+        );
+        assert!(result.termination.is_converged());
+        assert!(result.x.norm() < 1e-6);
+        // Максимум равен 0, значения на пути отрицательны и растут
+        assert!(result.f_x <= 0.0 && result.f_x > -1e-10);
+        assert_eq!(result.history[0].1, -5.0);
+        assert!(result.history.windows(2).all(|w| w[1].1 >= w[0].1));
+    }
+
+    #[test]
+    fn parse_point_explains_what_is_wrong() {
+        let app = GradientDescentApp {
+            num_vars: 2,
+            ..GradientDescentApp::default()
+        };
+        let lang = app.lang;
+        assert_eq!(
+            app.parse_point("1, 2, 3"),
+            Err(lang.tf("err_value_count", &[&2, &3]))
+        );
+        assert_eq!(
+            app.parse_point("1, foo"),
+            Err(lang.tf("err_parse_value", &[&"foo"]))
+        );
+        assert_eq!(
+            app.parse_point("1, -2.5"),
+            Ok(DVector::from_vec(vec![1.0, -2.5]))
+        );
+    }
+
+    #[test]
+    fn parse_point_accepts_brackets_semicolons_and_spaces() {
+        let app = GradientDescentApp {
+            num_vars: 2,
+            ..GradientDescentApp::default()
+        };
+        let expected = Ok(DVector::from_vec(vec![2.0, -1.5]));
+        for text in [
+            "[2, -1.5]",
+            "(2, -1.5)",
+            "2; -1.5",
+            "  ( 2 ,   -1.5 )  ",
+            "[2;-1.5]",
+        ] {
+            assert_eq!(app.parse_point(text), expected, "{}", text);
+        }
+        let lang = app.lang;
+        assert_eq!(
+            app.parse_point("2,,2"),
+            Err(lang.tf("err_empty_value", &[&2]))
+        );
+    }
+
+    #[test]
+    fn prepare_run_warns_on_step_increase_at_one() {
+        let mut app = GradientDescentApp {
+            step_increase: 1.0,
+            ..GradientDescentApp::default()
+        };
+        assert!(app.prepare_run().is_some());
+        assert!(app.warning_message.is_some());
+
+        app.step_increase = 1.0001;
+        assert!(app.prepare_run().is_some());
+        assert!(app.warning_message.is_none());
+
+        app.step_decay = 1.0;
+        assert!(app.prepare_run().is_none());
+        assert!(app.error_message.is_some());
+    }
+
+    #[test]
+    fn start_value_rejects_non_finite_objective() {
+        let func = ParsedFunction::new("1/x1", 1).unwrap();
+        assert_eq!(start_value(&func, &DVector::from_element(1, 0.0)), None);
+        assert_eq!(
+            start_value(&func, &DVector::from_element(1, 1.0)),
+            Some(1.0)
+        );
+    }
+
+    #[test]
+    fn weight_one_matches_first_objective_alone() {
+        let app = GradientDescentApp::default();
+        let single = Objective {
+            main: ParsedFunction::new("(x1 - 1)^2 + x2^2", 2).unwrap(),
+            ..rosenbrock_objective()
+        };
+        let combined = Objective {
+            second: Some((ParsedFunction::new("x1^2 + (x2 + 3)^2", 2).unwrap(), 1.0)),
+            ..single.clone()
+        };
+        let method = app.method_config_for(Algorithm::GradientDescent);
+        let start = DVector::from_vec(vec![-2.0, 2.0]);
+        let no_stop = || Arc::new(AtomicBool::new(false));
+        let alone = method.run(start.clone(), &single, no_stop());
+        let weighted = method.run(start, &combined, no_stop());
+        assert_eq!(weighted.history, alone.history);
+        assert_eq!(weighted.x, alone.x);
     }
 }
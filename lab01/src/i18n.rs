@@ -0,0 +1,666 @@
+// Строки интерфейса на русском и английском
+use std::fmt::{self, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Lang {
+    #[default]
+    Ru,
+    En,
+}
+
+impl Lang {
+    pub const ALL: [Lang; 2] = [Lang::Ru, Lang::En];
+
+    // Название языка на нём самом, чтобы его можно было найти в списке
+    pub fn label(self) -> &'static str {
+        match self {
+            Lang::Ru => "Русский",
+            Lang::En => "English",
+        }
+    }
+
+    fn table(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Lang::Ru => RU,
+            Lang::En => EN,
+        }
+    }
+
+    // Строка по ключу; без перевода — русский вариант, без него — сам ключ
+    pub fn t(self, key: &'static str) -> &'static str {
+        let find = |table: &[(&'static str, &'static str)]| {
+            table.iter().find(|(k, _)| *k == key).map(|(_, text)| *text)
+        };
+        find(self.table()).or_else(|| find(RU)).unwrap_or(key)
+    }
+
+    // Шаблон с подстановкой аргументов вместо {} по порядку
+    pub fn tf(self, key: &'static str, args: &[&dyn fmt::Display]) -> String {
+        let mut text = String::new();
+        let mut args = args.iter();
+        for (k, part) in self.t(key).split("{}").enumerate() {
+            if k > 0 {
+                if let Some(arg) = args.next() {
+                    let _ = write!(text, "{}", arg);
+                }
+            }
+            text.push_str(part);
+        }
+        text
+    }
+}
+
+const RU: &[(&str, &str)] = &[
+    // Методы и их настройки
+    ("alg_gradient_descent", "Градиентный спуск"),
+    ("alg_barzilai_borwein", "Шаг Барзилая–Борвейна"),
+    ("alg_momentum", "Спуск с инерцией (momentum)"),
+    ("alg_adam", "Adam"),
+    ("alg_rmsprop", "RMSProp"),
+    ("alg_conjugate_gradient", "Сопряжённые градиенты (Полак–Рибьер)"),
+    ("alg_newton", "Метод Ньютона"),
+    ("alg_bfgs", "Квазиньютоновский (BFGS)"),
+    ("alg_lbfgs", "BFGS с ограниченной памятью (L-BFGS)"),
+    ("alg_trust_region", "Доверительная область (КГ Штайхауга)"),
+    ("alg_trust_region_dogleg", "Доверительная область (dogleg)"),
+    ("alg_nelder_mead", "Симплекс Нелдера–Мида (без производных)"),
+    ("alg_annealing", "Имитация отжига (глобальный поиск)"),
+    ("alg_gauss_newton", "Гаусс — Ньютон (наименьшие квадраты)"),
+    ("hessian_analytic", "Матрица Гессе: аналитическая"),
+    ("hessian_numeric", "Матрица Гессе: конечные разности"),
+    ("ls_halving", "Дробление шага"),
+    ("ls_armijo", "Условие Армихо"),
+    ("ls_golden", "Золотое сечение"),
+    ("coords_cartesian", "Декартовы (x1..xn)"),
+    ("coords_polar", "Полярные (r, theta)"),
+    ("coords_spherical", "Сферические (r, theta, phi)"),
+    ("grad_forward", "Правая разность"),
+    ("grad_central", "Центральная разность"),
+    ("grad_analytic", "Аналитический"),
+    // Ошибки
+    ("err_parse_value", "не удалось разобрать '{}'"),
+    ("err_empty_value", "пустое значение на позиции {}"),
+    ("err_value_count", "ожидается {} значений, получено {}"),
+    ("err_bound_order", "для переменной {} нижняя граница больше верхней"),
+    ("err_constraints", "Ошибка в ограничениях: {}"),
+    ("err_function", "Ошибка в функции: {}"),
+    ("err_constraint", "Ошибка в ограничении {}: {}"),
+    ("err_second_function", "Ошибка во второй функции: {}"),
+    ("err_start_point", "Ошибка в начальной точке: {}"),
+    ("err_start_not_finite", "Целевая функция не определена или бесконечна в начальной точке"),
+    ("err_no_start_point", "не задана; используйте формат 'x1, x2'"),
+    ("err_line", "строка {}: {}"),
+    ("err_params", "Ошибка в параметрах: {}"),
+    ("warn_step_increase", "Коэффициент увеличения шага {} не больше 1: после дробления шаг не растёт, и на пологих участках спуск может застрять"),
+    ("err_tuning", "Авто-настройка: ни один запуск не дал конечного значения"),
+    ("err_save", "Не удалось сохранить {}: {}"),
+    ("err_load", "Не удалось загрузить {}: {}"),
+    // Причины останова
+    ("term_converged", "Сходимость: достигнута заданная точность"),
+    ("term_flat", "Градиент на уровне численного шума: плоская область"),
+    ("term_max_iterations", "Достигнуто максимальное число итераций, сходимость не достигнута"),
+    ("term_user_stopped", "Досрочно остановлено пользователем"),
+    ("term_line_search", "Не удалось найти шаг, уменьшающий функцию: спуск остановлен"),
+    ("term_ftol", "Сходимость: относительное изменение f меньше ftol"),
+    ("term_xtol", "Сходимость: длина шага меньше xtol"),
+    ("term_nan", "Градиент содержит NaN или бесконечность: функция не определена в точке"),
+    ("undefined", "не определено"),
+    ("hover_value", "{}\nf = {}\n∇f = {}"),
+    ("term_non_finite", "Значение f не является конечным числом: показана последняя допустимая точка"),
+    ("term_invalid_params", "Недопустимые параметры метода: причина в логе"),
+    // Панель параметров
+    ("language", "Язык"),
+    ("theme", "Тема"),
+    ("theme_system", "Системная"),
+    ("theme_light", "Светлая"),
+    ("theme_dark", "Тёмная"),
+    ("params", "Параметры"),
+    ("method", "Метод"),
+    ("coordinates", "Координаты"),
+    ("dimension", "Размерность (n):"),
+    ("function", "Функция f(x):"),
+    ("alias_hint", "Вместо x1, x2, x3 можно писать x, y, z"),
+    ("maximize", "Максимизировать"),
+    ("preset", "Тестовая функция"),
+    ("preset_choose", "выбрать…"),
+    ("preset_sphere", "Сфера"),
+    ("preset_rosenbrock", "Розенброк"),
+    ("preset_rastrigin", "Растригин"),
+    ("preset_himmelblau", "Химмельблау"),
+    ("preset_booth", "Бут"),
+    ("preset_beale", "Била"),
+    ("known_minimum", "Известный минимум: x* = [{}], f(x*) = {}, отклонение f: {}"),
+    ("least_squares", "Наименьшие квадраты"),
+    ("least_squares_hint", "Невязки по одной на строку; функция — сумма их квадратов, доступен метод Гаусса — Ньютона"),
+    ("maximize_hint", "Ищется максимум f; фронт Парето всегда строится для минимума"),
+    ("gradient", "Градиент"),
+    ("symbolic_failed", "Выражение не удалось продифференцировать, используется центральная разность"),
+    ("grad_eps", "Шаг разностей h:"),
+    ("grad_eps_hint", "Шаг конечных разностей численного градиента. Слишком малый h усиливает ошибки округления (≈ ε_маш·|f| / h), слишком большой — ошибку самой разностной схемы (≈ h² для центральной, h для правой)"),
+    ("check_gradient", "Проверить градиент"),
+    ("check_gradient_hint", "Сравнить символьный градиент с центральной разностью в начальной точке"),
+    ("gradient_diff", "max |аналитический - численный| = {}"),
+    ("domain_warning", "Область определения ограничена (вне её значение будет NaN):"),
+    ("kink_note", "abs, max и min не дифференцируемы в точках излома: там прямая разность даёт одностороннюю производную, а центральная — среднее левой и правой."),
+    ("start_point", "Начальная точка:"),
+    ("coordinates_hint", "Координаты: {}"),
+    ("start_point_hint", "по одной точке на строку, # — комментарий"),
+    ("known_optimum", "Известный оптимум:"),
+    ("known_optimum_hint", "Через запятую; пусто — в качестве оптимума берётся итоговая точка"),
+    ("lower_bounds", "Нижние границы:"),
+    ("lower_bounds_hint", "Через запятую, допускаются -inf и inf; пусто — без ограничений. Границы можно также задать в строке функции: x1^2 + x2^2; 0 <= x1 <= 5"),
+    ("upper_bounds", "Верхние границы:"),
+    ("bounds_unsupported", "Ограничения учитываются только градиентным спуском, шагом Барзилая–Борвейна, спуском с инерцией, Adam, RMSProp и имитацией отжига."),
+    ("two_objectives", "Два критерия"),
+    ("constraints", "Ограничения g(x) = 0 (по одному в строке):"),
+    ("constraints_hint", "Учитываются методом штрафов f + μ·Σg²: при запуске внутренние задачи решает градиентный спуск с выбранным одномерным поиском, μ растёт между внешними итерациями"),
+    ("penalty_growth", "рост μ:"),
+    ("penalty_outer", "Внешних итераций:"),
+    ("constraint_violations", "Невязки ограничений: {}"),
+    ("function2", "Функция f2(x):"),
+    ("weight", "Вес w (w·f1 + (1-w)·f2):"),
+    ("weight_count", "Число весов на [0, 1]:"),
+    ("momentum", "Инерция μ:"),
+    ("nesterov", "Ускорение Нестерова"),
+    ("nesterov_hint", "Градиент вычисляется в точке x + μ·v, а не в текущей точке"),
+    ("momentum_note", "Начальный шаг используется как постоянная скорость обучения."),
+    ("bb_note", "Начальный шаг используется на первой итерации и при s·y <= 0."),
+    ("lbfgs_memory", "Пар (s, y) в памяти:"),
+    ("lbfgs_memory_hint", "Сколько последних пар (s, y) хранится для направления; память O(m·n) вместо n×n у BFGS."),
+    ("powell_restart", "Рестарт Пауэлла, порог:"),
+    ("powell_restart_hint", "Направление сбрасывается на антиградиент, когда |g_{k+1}·g_k| / (g_k·g_k) не меньше порога: соседние градиенты перестали быть почти ортогональными и сопряжённость потеряна. Тест рассчитан на точный одномерный поиск: при дроблении шага до первого убывания он срабатывает часто и метод приближается к градиентному спуску."),
+    ("adam_settings", "Настройки Adam"),
+    ("rmsprop_settings", "Настройки RMSProp"),
+    ("learning_rate", "Скорость обучения:"),
+    ("simplex_settings", "Настройки симплекса"),
+    ("nm_alpha", "Отражение α:"),
+    ("nm_gamma", "Растяжение γ:"),
+    ("nm_rho", "Сжатие ρ:"),
+    ("nm_sigma", "Редукция σ:"),
+    ("nm_step", "Шаг симплекса:"),
+    ("annealing_settings", "Настройки отжига"),
+    ("sa_temperature", "Начальная температура T0:"),
+    ("sa_cooling", "Охлаждение (T ← c·T):"),
+    ("sa_step", "σ шага при T0:"),
+    ("sa_min_temperature", "Минимальная температура:"),
+    ("initial_radius", "Начальный радиус:"),
+    ("max_radius", "Макс. радиус:"),
+    ("line_search", "Выбор шага"),
+    ("pgd", "Выход из седловых точек (PGD)"),
+    ("pgd_hint", "Если градиент долго остаётся малым, к точке добавляется случайное возмущение"),
+    ("pgd_threshold", "Порог ||g||:"),
+    ("pgd_patience", "Итераций до возмущения:"),
+    ("pgd_radius", "Радиус возмущения:"),
+    ("pgd_max", "Макс. возмущений:"),
+    ("fixed_seed", "Фиксированное зерно ГСЧ:"),
+    ("perturb_run", "Сдвинуть и запустить"),
+    ("perturb_run_hint", "Запуск из начальной точки, каждая координата которой сдвинута на N(0, σ²); текущий результат переносится в сравнение"),
+    ("perturbed_run", "Сдвиг σ = {}, старт ({})"),
+    ("fixed_seed_hint", "Возмущения градиентного спуска и имитация отжига повторяются от запуска к запуску; без флажка зерно выбирается случайно"),
+    ("initial_step", "Начальный шаг:"),
+    ("auto_step", "авто"),
+    ("auto_step_hint", "Оценить шаг по градиенту и одной пробной точке вдоль антиградиента в начальной точке"),
+    ("step_decay", "Коэф. дробления:"),
+    ("max_backtracks", "Дроблений шага за итерацию:"),
+    ("max_backtracks_hint", "Поиск шага прекращается раньше, если шаг перестал сдвигать точку"),
+    ("step_increase", "Коэф. увеличения:"),
+    ("max_step", "Наибольший шаг:"),
+    ("max_step_hint", "Предел, до которого растёт шаг после удачных итераций. Для пологих функций вроде 0.01*(x1^2+x2^2) его стоит увеличить."),
+    ("armijo_c1", "Константа Армихо c1:"),
+    ("armijo_hint", "Шаг t принимается при f(x + t·d) <= f(x) + c1·t·(g·d)"),
+    ("tolerance", "Точность:"),
+    ("grad_test_absolute", "||g|| < ε"),
+    ("grad_test_relative", "||g|| < ε·||g0||"),
+    ("grad_test_relative_hint", "Точность задаётся относительно нормы градиента в начальной точке"),
+    ("scale_tolerance", "Масштабировать точность по размерности"),
+    ("normalize_objective", "Нормировать f по значению в начальной точке"),
+    ("normalize_objective_hint", "Спуск ведётся по f(x) / |f(x0)|, поэтому шаг и точность не зависят от масштаба f; в результате — значения исходной функции"),
+    ("restart_period", "Период тёплых рестартов:"),
+    ("restart_period_hint", "Каждые столько итераций шаг возвращается к начальному, 0 — без рестартов"),
+    ("restart_doubling", "Удваивать период после рестарта"),
+    ("max_grad_norm", "Наибольшая норма градиента:"),
+    ("max_grad_norm_hint", "Более длинный градиент укорачивается до этой нормы перед выбором шага, 0 — без ограничения"),
+    ("scale_tolerance_hint", "Сравнивать с точностью ||g|| / √n — среднеквадратичную компоненту градиента, а не его норму"),
+    ("ftol_hint", "Останов при |f_{k+1} - f_k| / (|f_k| + eps) < ftol; 0 — выключен"),
+    ("xtol_hint", "Останов при ||x_{k+1} - x_k|| < xtol; 0 — выключен"),
+    ("max_iterations", "Макс. итераций:"),
+    ("max_history_points", "Точек траектории:"),
+    ("max_history_points_hint", "Предел числа сохраняемых точек пути: остаются каждая k-я и последняя. 0 — хранить все. При прореживании номер точки на графиках перестаёт совпадать с номером итерации."),
+    ("detect_flat", "Распознавать плоские области"),
+    ("detect_flat_hint", "Останавливать спуск, когда норма градиента сравнима с шумом конечных разностей"),
+    ("verbose_log", "Подробный лог"),
+    ("sensitivity", "Карта чувствительности к начальной точке"),
+    ("random_restarts", "Случайные перезапуски"),
+    ("restart_count", "Запусков:"),
+    ("restart_box", "Координаты старта:"),
+    ("restart_box_hint", "Каждая координата начальной точки выбирается равномерно из этого отрезка; генератор задаётся общим зерном."),
+    ("run_restarts", "Запустить перезапуски"),
+    ("restart_finals", "Итоговые точки"),
+    ("restart_best", "Лучшая точка"),
+    ("restart_summary", "Запусков: {}, лучшее значение достигнуто в {}; медиана f(x*) = {}, худшее = {}"),
+    ("err_restarts", "Перезапуски прерваны до первого результата"),
+    ("nodes_per_axis", "Узлов по оси:"),
+    ("metric_iterations", "итерации"),
+    ("build_map", "Построить карту"),
+    ("convergence_plot", "График f(x) по итерациям"),
+    ("log_scale", "Логарифмическая шкала f"),
+    ("contours", "Линии уровня (2D)"),
+    ("grid_resolution", "Разрешение сетки:"),
+    ("equal_axes", "Равные оси"),
+    ("fixed_frame", "Фиксированная рамка графика"),
+    ("fixed_frame_hint", "Одни и те же границы осей для всех запусков; масштабирование и сдвиг графика отключаются"),
+    ("plot_aspect", "Соотношение сторон графика:"),
+    ("save_run", "Сохранить запуск"),
+    ("load_run", "Загрузить запуск"),
+    ("load_function", "Загрузить функцию из файла"),
+    ("partial_derivatives", "Частные производные в начальной точке"),
+    ("derivs_parse_error", "ошибка разбора функции"),
+    ("derivs_point_error", "ошибка в начальной точке"),
+    ("derivs_eval_error", "ошибка вычисления"),
+    ("load_function_hint", "Текстовый файл с выражением; строка \"# vars: 3\" задаёт число переменных"),
+    ("run", "▶ Запуск"),
+    ("pareto_front", "Фронт Парето"),
+    ("solve_auto", "Решить (авто)"),
+    ("solve_auto_hint", "Запустить все методы с одинаковым бюджетом вычислений f и ∇f и выбрать лучший результат"),
+    ("portfolio_budget", "Бюджет:"),
+    ("portfolio_budget_hint", "Наибольшее число вычислений f и ∇f на метод; матрица Гессе не учитывается, 0 — без ограничения"),
+    ("evaluations", "Вычислений f и ∇f"),
+    ("ranking_budget", "Бюджет на метод: {} вычислений f и ∇f; метод, исчерпавший его, остановлен досрочно"),
+    ("compare_with", "Сравнить с"),
+    ("compare_with_hint", "Параллельно запустить выбранный метод и метод из списка из одной начальной точки; второй результат добавляется в сравнение"),
+    ("auto_tune", "Авто-настройка"),
+    ("auto_tune_hint", "Перебор начального шага, коэффициентов уменьшения и увеличения шага градиентного спуска с выбранным одномерным поиском"),
+    ("stop", "⏸ Стоп"),
+    ("stopping", "⏹ Остановка..."),
+    ("to_comparison", "В сравнение"),
+    ("continue_run", "Продолжить"),
+    ("continue_run_hint", "Ещё столько же итераций из найденной точки; путь и счётчики добавляются к текущему результату."),
+    ("export_csv", "Экспорт CSV"),
+    ("save_png", "Сохранить график в PNG"),
+    ("reset", "Сброс"),
+    ("shortcut_run", "Enter или Ctrl+R"),
+    ("shortcut_stop", "Escape"),
+    ("shortcut_reset", "Ctrl+Backspace"),
+    ("restore_defaults", "Параметры по умолчанию"),
+    ("anim_play", "Пошаговый показ пути"),
+    ("anim_next", "Следующая итерация"),
+    ("anim_full", "Показать путь целиком"),
+    ("anim_speed", "Итераций в секунду:"),
+    // Результаты
+    ("visualization", "Визуализация"),
+    ("number", "№"),
+    ("iterations", "итераций"),
+    ("eval_stats", "Вычислений f: {}, ∇f: {}; время: {} мс"),
+    ("hessian_condition", "Число обусловленности H(x*): {} (λ_min = {}, λ_max = {})"),
+    ("hessian_saddle", "λ_min < 0: найдена седловая точка, а не минимум"),
+    ("hessian_undefined", "Матрица Гессе в x* не определена"),
+    ("iteration", "итерация"),
+    ("run_name", "Прогон {}"),
+    ("auto_run_name", "Авто: {}"),
+    ("comparison", "Сравнение прогонов ({})"),
+    ("name", "Название"),
+    ("current_run", "Текущий"),
+    ("time_ms", "время, мс"),
+    ("note", "Заметка"),
+    ("remove", "Удалить"),
+    ("ranking", "Рейтинг методов"),
+    ("ranking_note", "Лучший результат показан ниже, остальные добавлены в сравнение"),
+    ("tuning", "Авто-настройка: шаг {}, уменьшение {}, увеличение {} (запусков: {})"),
+    ("tuning_grid", "Перебор: начальный шаг {}, уменьшение {}, увеличение {}"),
+    ("tuning_rule", "Лучший — по наименьшему f(x*), при равных значениях — по числу итераций"),
+    ("step", "шаг"),
+    ("decay", "уменьшение"),
+    ("increase", "увеличение"),
+    ("converged", "сошёлся"),
+    ("yes", "да"),
+    ("no", "нет"),
+    ("error_ratios", "Отношения ошибок e(k+1)/e(k)"),
+    ("known_optimum_error", "Известный оптимум: {}"),
+    ("distance_to_optimum", "Расстояние до оптимума ||x - x*||"),
+    ("not_set", "не задан"),
+    ("result", "Результат: x* = [{}], f(x*) = {}, итераций: {}"),
+    ("perturbation_count", "Случайных возмущений: {}"),
+    ("log", "Подробный лог ({} записей)"),
+    ("clear_log", "Очистить лог"),
+    ("iterates", "Таблица точек ({})"),
+    ("copy_row", "Скопировать строку"),
+    ("trust_radius", "Радиус доверительной области"),
+    ("grad_norm", "Норма градиента и длина шага"),
+    ("cartesian_point", "В декартовых координатах: [{}]"),
+    ("flat_view", "Путь на плоскости"),
+    ("surface_view", "Поверхность (3D)"),
+    ("surface_grid", "Сетка:"),
+    ("yaw", "Поворот:"),
+    ("pitch", "Наклон:"),
+    ("iteration_point", "Итерация {}: x = [{}], f = {}"),
+    ("path", "Путь спуска"),
+    ("path_colors_hint", "Цвет пути — значение f в логарифмической шкале от f_min (синий) до f_max (красный), как у линий уровня."),
+    ("current_point", "Текущая точка"),
+    ("perturbations", "Возмущения"),
+    ("path_2d_only", "Путь спуска доступен только для 2D задач; график f(x) по итерациям — для любой размерности."),
+    ("multi_start", "Запусков из разных точек: {}"),
+    ("start_n", "Старт {}"),
+    ("no_convergence_data", "Нет данных для графика сходимости."),
+    ("log_unavailable", "Все значения f не положительны: логарифмическая шкала недоступна."),
+    ("f_log", "f (лог. шкала)"),
+    ("sensitivity_summary", "Карта чувствительности: {}×{} запусков, {} от {} (синий) до {} (красный)"),
+    ("pareto_count", "Фронт Парето: {} точек"),
+    ("no_results", "Запустите оптимизацию для отображения результатов."),
+];
+
+const EN: &[(&str, &str)] = &[
+    ("alg_gradient_descent", "Gradient descent"),
+    ("alg_barzilai_borwein", "Barzilai–Borwein step"),
+    ("alg_momentum", "Descent with momentum"),
+    ("alg_adam", "Adam"),
+    ("alg_rmsprop", "RMSProp"),
+    ("alg_conjugate_gradient", "Conjugate gradient (Polak–Ribière)"),
+    ("alg_newton", "Newton's method"),
+    ("alg_bfgs", "Quasi-Newton (BFGS)"),
+    ("alg_lbfgs", "Limited-memory BFGS (L-BFGS)"),
+    ("alg_trust_region", "Trust region (Steihaug CG)"),
+    ("alg_trust_region_dogleg", "Trust region (dogleg)"),
+    ("alg_nelder_mead", "Nelder–Mead simplex (derivative-free)"),
+    ("alg_annealing", "Simulated annealing (global search)"),
+    ("alg_gauss_newton", "Gauss-Newton (least squares)"),
+    ("hessian_analytic", "Hessian: analytic"),
+    ("hessian_numeric", "Hessian: finite differences"),
+    ("ls_halving", "Step halving"),
+    ("ls_armijo", "Armijo condition"),
+    ("ls_golden", "Golden section"),
+    ("coords_cartesian", "Cartesian (x1..xn)"),
+    ("coords_polar", "Polar (r, theta)"),
+    ("coords_spherical", "Spherical (r, theta, phi)"),
+    ("grad_forward", "Forward difference"),
+    ("grad_central", "Central difference"),
+    ("grad_analytic", "Analytic"),
+    ("err_parse_value", "could not parse '{}'"),
+    ("err_empty_value", "empty value at position {}"),
+    ("err_value_count", "expected {} values, got {}"),
+    ("err_bound_order", "lower bound exceeds upper bound for variable {}"),
+    ("err_constraints", "Invalid bounds: {}"),
+    ("err_function", "Invalid function: {}"),
+    ("err_constraint", "Invalid constraint {}: {}"),
+    ("err_second_function", "Invalid second function: {}"),
+    ("err_start_point", "Invalid start point: {}"),
+    ("err_start_not_finite", "Objective is not finite at the start point"),
+    ("err_no_start_point", "not given; use the format 'x1, x2'"),
+    ("err_line", "line {}: {}"),
+    ("err_params", "Invalid parameters: {}"),
+    ("warn_step_increase", "Step increase factor {} is at most 1: the step never grows after shrinking, so descent may stall on shallow regions"),
+    ("err_tuning", "Auto-tuning: no run produced a finite value"),
+    ("err_save", "Could not save {}: {}"),
+    ("err_load", "Could not load {}: {}"),
+    ("term_converged", "Converged: requested tolerance reached"),
+    ("term_flat", "Gradient is at numerical noise level: flat region"),
+    ("term_max_iterations", "Iteration limit reached without convergence"),
+    ("term_user_stopped", "Stopped by the user"),
+    ("term_line_search", "No step decreasing the function was found: descent stopped"),
+    ("term_ftol", "Converged: relative change of f below ftol"),
+    ("term_xtol", "Converged: step length below xtol"),
+    ("term_nan", "Gradient contains NaN or infinity: function is undefined at the point"),
+    ("undefined", "undefined"),
+    ("hover_value", "{}\nf = {}\n∇f = {}"),
+    ("term_non_finite", "The value of f is not finite: the last valid point is shown"),
+    ("term_invalid_params", "Invalid method parameters: see the log for the reason"),
+    ("language", "Language"),
+    ("theme", "Theme"),
+    ("theme_system", "System"),
+    ("theme_light", "Light"),
+    ("theme_dark", "Dark"),
+    ("params", "Parameters"),
+    ("method", "Method"),
+    ("coordinates", "Coordinates"),
+    ("dimension", "Dimension (n):"),
+    ("function", "Function f(x):"),
+    ("alias_hint", "You can write x, y, z instead of x1, x2, x3"),
+    ("maximize", "Maximize"),
+    ("preset", "Test function"),
+    ("preset_choose", "choose…"),
+    ("preset_sphere", "Sphere"),
+    ("preset_rosenbrock", "Rosenbrock"),
+    ("preset_rastrigin", "Rastrigin"),
+    ("preset_himmelblau", "Himmelblau"),
+    ("preset_booth", "Booth"),
+    ("preset_beale", "Beale"),
+    ("known_minimum", "Known minimum: x* = [{}], f(x*) = {}, f gap: {}"),
+    ("least_squares", "Least squares"),
+    ("least_squares_hint", "One residual per line; the objective is the sum of their squares and the Gauss-Newton method becomes available"),
+    ("maximize_hint", "Searches for the maximum of f; the Pareto front is always built for the minimum"),
+    ("gradient", "Gradient"),
+    ("symbolic_failed", "The expression could not be differentiated; central differences are used"),
+    ("grad_eps", "Difference step h:"),
+    ("grad_eps_hint", "Finite-difference step of the numeric gradient. Too small an h amplifies roundoff (≈ ε_mach·|f| / h), too large an h increases truncation error (≈ h² for central, h for forward differences)"),
+    ("check_gradient", "Check gradient"),
+    ("check_gradient_hint", "Compare the symbolic gradient with central differences at the start point"),
+    ("gradient_diff", "max |analytic - numeric| = {}"),
+    ("domain_warning", "The domain is restricted (the value is NaN outside it):"),
+    ("kink_note", "abs, max and min are not differentiable at their kinks: there the forward difference gives a one-sided derivative and the central one the average of the left and right ones."),
+    ("start_point", "Start point:"),
+    ("coordinates_hint", "Coordinates: {}"),
+    ("start_point_hint", "one point per line, # starts a comment"),
+    ("known_optimum", "Known optimum:"),
+    ("known_optimum_hint", "Comma-separated; empty means the final point is used as the optimum"),
+    ("lower_bounds", "Lower bounds:"),
+    ("lower_bounds_hint", "Comma-separated, -inf and inf are allowed; empty means unbounded. Bounds can also be given in the function string: x1^2 + x2^2; 0 <= x1 <= 5"),
+    ("upper_bounds", "Upper bounds:"),
+    ("bounds_unsupported", "Bounds are only honoured by gradient descent, the Barzilai–Borwein step, descent with momentum, Adam, RMSProp and simulated annealing."),
+    ("two_objectives", "Two objectives"),
+    ("constraints", "Constraints g(x) = 0 (one per line):"),
+    ("constraints_hint", "Handled by the penalty method f + μ·Σg²: on Run, gradient descent with the selected line search solves the inner problems while μ grows between outer iterations"),
+    ("penalty_growth", "μ growth:"),
+    ("penalty_outer", "Outer iterations:"),
+    ("constraint_violations", "Constraint residuals: {}"),
+    ("function2", "Function f2(x):"),
+    ("weight", "Weight w (w·f1 + (1-w)·f2):"),
+    ("weight_count", "Number of weights on [0, 1]:"),
+    ("momentum", "Momentum μ:"),
+    ("nesterov", "Nesterov acceleration"),
+    ("nesterov_hint", "The gradient is evaluated at x + μ·v instead of the current point"),
+    ("momentum_note", "The initial step is used as a constant learning rate."),
+    ("bb_note", "The initial step is used on the first iteration and when s·y <= 0."),
+    ("lbfgs_memory", "Stored (s, y) pairs:"),
+    ("lbfgs_memory_hint", "How many recent (s, y) pairs are kept for the direction; O(m·n) memory instead of n×n for BFGS."),
+    ("powell_restart", "Powell restart, threshold:"),
+    ("powell_restart_hint", "The direction is reset to the negative gradient when |g_{k+1}·g_k| / (g_k·g_k) reaches the threshold: consecutive gradients are no longer nearly orthogonal and conjugacy is lost. The test assumes an accurate line search: with step halving down to the first decrease it fires often and the method drifts towards plain gradient descent."),
+    ("adam_settings", "Adam settings"),
+    ("rmsprop_settings", "RMSProp settings"),
+    ("learning_rate", "Learning rate:"),
+    ("simplex_settings", "Simplex settings"),
+    ("nm_alpha", "Reflection α:"),
+    ("nm_gamma", "Expansion γ:"),
+    ("nm_rho", "Contraction ρ:"),
+    ("nm_sigma", "Shrink σ:"),
+    ("nm_step", "Simplex step:"),
+    ("annealing_settings", "Annealing settings"),
+    ("sa_temperature", "Initial temperature T0:"),
+    ("sa_cooling", "Cooling (T ← c·T):"),
+    ("sa_step", "Step σ at T0:"),
+    ("sa_min_temperature", "Minimum temperature:"),
+    ("initial_radius", "Initial radius:"),
+    ("max_radius", "Max radius:"),
+    ("line_search", "Step selection"),
+    ("pgd", "Escape saddle points (PGD)"),
+    ("pgd_hint", "If the gradient stays small for long, a random perturbation is added to the point"),
+    ("pgd_threshold", "||g|| threshold:"),
+    ("pgd_patience", "Iterations before perturbation:"),
+    ("pgd_radius", "Perturbation radius:"),
+    ("pgd_max", "Max perturbations:"),
+    ("fixed_seed", "Fixed RNG seed:"),
+    ("perturb_run", "Perturb & run"),
+    ("perturb_run_hint", "Run from the start point with each coordinate shifted by N(0, σ²); the current result moves to the comparison"),
+    ("perturbed_run", "Perturbed σ = {}, start ({})"),
+    ("fixed_seed_hint", "Gradient-descent perturbations and simulated annealing repeat exactly between runs; when unchecked, a random seed is drawn"),
+    ("initial_step", "Initial step:"),
+    ("auto_step", "auto"),
+    ("auto_step_hint", "Estimate the step from the gradient and one trial point along the negative gradient at the start point"),
+    ("step_decay", "Decay factor:"),
+    ("max_backtracks", "Backtracks per iteration:"),
+    ("max_backtracks_hint", "The step search also stops once the step no longer moves the point"),
+    ("step_increase", "Increase factor:"),
+    ("max_step", "Maximum step:"),
+    ("max_step_hint", "Upper limit the step grows to after successful iterations. Raise it for shallow functions such as 0.01*(x1^2+x2^2)."),
+    ("armijo_c1", "Armijo constant c1:"),
+    ("armijo_hint", "Step t is accepted when f(x + t·d) <= f(x) + c1·t·(g·d)"),
+    ("tolerance", "Tolerance:"),
+    ("grad_test_absolute", "||g|| < ε"),
+    ("grad_test_relative", "||g|| < ε·||g0||"),
+    ("grad_test_relative_hint", "Tolerance is relative to the gradient norm at the start point"),
+    ("scale_tolerance", "Scale tolerance by dimension"),
+    ("normalize_objective", "Normalize f by its start value"),
+    ("normalize_objective_hint", "Descend on f(x) / |f(x0)| so the step and tolerance do not depend on the scale of f; results show the original values"),
+    ("restart_period", "Warm restart period:"),
+    ("restart_period_hint", "Reset the step to its initial value every this many iterations, 0 means no restarts"),
+    ("restart_doubling", "Double the period after each restart"),
+    ("max_grad_norm", "Max gradient norm:"),
+    ("max_grad_norm_hint", "Longer gradients are scaled down to this norm before the step search, 0 means no clipping"),
+    ("scale_tolerance_hint", "Compare ||g|| / √n, the RMS gradient component, with the tolerance instead of the raw norm"),
+    ("ftol_hint", "Stop when |f_{k+1} - f_k| / (|f_k| + eps) < ftol; 0 disables"),
+    ("xtol_hint", "Stop when ||x_{k+1} - x_k|| < xtol; 0 disables"),
+    ("max_iterations", "Max iterations:"),
+    ("max_history_points", "Path points:"),
+    ("max_history_points_hint", "Cap on stored path points: every k-th point and the last one are kept. 0 keeps all. Once thinned, point numbers on the plots no longer match iteration numbers."),
+    ("detect_flat", "Detect flat regions"),
+    ("detect_flat_hint", "Stop when the gradient norm is comparable to finite-difference noise"),
+    ("verbose_log", "Verbose log"),
+    ("sensitivity", "Sensitivity to the start point"),
+    ("random_restarts", "Random restarts"),
+    ("restart_count", "Runs:"),
+    ("restart_box", "Start coordinates:"),
+    ("restart_box_hint", "Each coordinate of a start point is drawn uniformly from this interval; the generator uses the shared seed."),
+    ("run_restarts", "Run restarts"),
+    ("restart_finals", "Final points"),
+    ("restart_best", "Best point"),
+    ("restart_summary", "Runs: {}, best value reached by {}; median f(x*) = {}, worst = {}"),
+    ("err_restarts", "Restarts were stopped before the first result"),
+    ("nodes_per_axis", "Nodes per axis:"),
+    ("metric_iterations", "iterations"),
+    ("build_map", "Build map"),
+    ("convergence_plot", "Plot f(x) per iteration"),
+    ("log_scale", "Logarithmic f scale"),
+    ("contours", "Contour lines (2D)"),
+    ("grid_resolution", "Grid resolution:"),
+    ("equal_axes", "Equal axes"),
+    ("fixed_frame", "Fixed plot frame"),
+    ("fixed_frame_hint", "Same axis bounds for every run; zooming and panning are disabled"),
+    ("plot_aspect", "Plot aspect ratio:"),
+    ("save_run", "Save run"),
+    ("load_run", "Load run"),
+    ("load_function", "Load function from file"),
+    ("partial_derivatives", "Partial derivatives at the initial point"),
+    ("derivs_parse_error", "parse error"),
+    ("derivs_point_error", "invalid initial point"),
+    ("derivs_eval_error", "eval error"),
+    ("load_function_hint", "Text file with the expression; a \"# vars: 3\" line sets the number of variables"),
+    ("run", "▶ Run"),
+    ("pareto_front", "Pareto front"),
+    ("solve_auto", "Solve (auto)"),
+    ("solve_auto_hint", "Run every method with the same budget of f and ∇f evaluations and pick the best result"),
+    ("portfolio_budget", "Budget:"),
+    ("portfolio_budget_hint", "Maximum number of f and ∇f evaluations per method; Hessian evaluations are not counted, 0 means unlimited"),
+    ("evaluations", "f and ∇f evaluations"),
+    ("ranking_budget", "Budget per method: {} evaluations of f and ∇f; a method that used it up was stopped early"),
+    ("compare_with", "Compare with"),
+    ("compare_with_hint", "Run the selected method and the one from this list in parallel from the same start point; the second result is added to the comparison"),
+    ("auto_tune", "Auto-tune"),
+    ("auto_tune_hint", "Grid search over the initial step and the decay and increase factors of gradient descent with the selected line search"),
+    ("stop", "⏸ Stop"),
+    ("stopping", "⏹ Stopping..."),
+    ("to_comparison", "Add to comparison"),
+    ("continue_run", "Continue"),
+    ("continue_run_hint", "Run the same number of iterations again from the point found; the path and counters are appended to the current result."),
+    ("export_csv", "Export CSV"),
+    ("save_png", "Save plot as PNG"),
+    ("reset", "Reset"),
+    ("shortcut_run", "Enter or Ctrl+R"),
+    ("shortcut_stop", "Escape"),
+    ("shortcut_reset", "Ctrl+Backspace"),
+    ("restore_defaults", "Restore defaults"),
+    ("anim_play", "Step through the path"),
+    ("anim_next", "Next iteration"),
+    ("anim_full", "Show the whole path"),
+    ("anim_speed", "Iterations per second:"),
+    ("visualization", "Visualization"),
+    ("number", "#"),
+    ("iterations", "iterations"),
+    ("eval_stats", "Evaluations of f: {}, ∇f: {}; time: {} ms"),
+    ("hessian_condition", "Condition number of H(x*): {} (λ_min = {}, λ_max = {})"),
+    ("hessian_saddle", "λ_min < 0: this is a saddle point, not a minimum"),
+    ("hessian_undefined", "The Hessian is undefined at x*"),
+    ("iteration", "iteration"),
+    ("run_name", "Run {}"),
+    ("auto_run_name", "Auto: {}"),
+    ("comparison", "Run comparison ({})"),
+    ("name", "Name"),
+    ("current_run", "Current"),
+    ("time_ms", "time, ms"),
+    ("note", "Note"),
+    ("remove", "Remove"),
+    ("ranking", "Method ranking"),
+    ("ranking_note", "The best result is shown below; the others were added to the comparison"),
+    ("tuning", "Auto-tuning: step {}, decay {}, increase {} ({} runs)"),
+    ("tuning_grid", "Grid: initial step {}, decay {}, increase {}"),
+    ("tuning_rule", "Best means the lowest f(x*); ties are broken by iteration count"),
+    ("step", "step"),
+    ("decay", "decay"),
+    ("increase", "increase"),
+    ("converged", "converged"),
+    ("yes", "yes"),
+    ("no", "no"),
+    ("error_ratios", "Error ratios e(k+1)/e(k)"),
+    ("known_optimum_error", "Known optimum: {}"),
+    ("distance_to_optimum", "Distance to optimum ||x - x*||"),
+    ("not_set", "not set"),
+    ("result", "Result: x* = [{}], f(x*) = {}, iterations: {}"),
+    ("perturbation_count", "Random perturbations: {}"),
+    ("log", "Verbose log ({} entries)"),
+    ("clear_log", "Clear log"),
+    ("iterates", "Iterates table ({})"),
+    ("copy_row", "Copy row"),
+    ("trust_radius", "Trust region radius"),
+    ("grad_norm", "Gradient norm and step length"),
+    ("cartesian_point", "In Cartesian coordinates: [{}]"),
+    ("flat_view", "Flat path"),
+    ("surface_view", "Surface (3D)"),
+    ("surface_grid", "Grid:"),
+    ("yaw", "Rotation:"),
+    ("pitch", "Tilt:"),
+    ("iteration_point", "Iteration {}: x = [{}], f = {}"),
+    ("path", "Descent path"),
+    ("path_colors_hint", "Path colour shows f on a logarithmic scale from f_min (blue) to f_max (red), matching the level lines."),
+    ("current_point", "Current point"),
+    ("perturbations", "Perturbations"),
+    ("path_2d_only", "The descent path is only available for 2D problems; the f(x) plot works for any dimension."),
+    ("multi_start", "Runs from different start points: {}"),
+    ("start_n", "Start {}"),
+    ("no_convergence_data", "No data for the convergence plot."),
+    ("log_unavailable", "All f values are non-positive: logarithmic scale is unavailable."),
+    ("f_log", "f (log scale)"),
+    ("sensitivity_summary", "Sensitivity map: {}×{} runs, {} from {} (blue) to {} (red)"),
+    ("pareto_count", "Pareto front: {} points"),
+    ("no_results", "Run the optimization to see results."),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ru_and_en_tables_have_the_same_keys() {
+        let keys = |table: &[(&'static str, &'static str)]| {
+            let mut keys: Vec<&str> = table.iter().map(|(k, _)| *k).collect();
+            keys.sort_unstable();
+            keys
+        };
+        let (ru, en) = (keys(RU), keys(EN));
+        assert_eq!(ru, en);
+        let mut unique = ru.clone();
+        unique.dedup();
+        assert_eq!(unique.len(), ru.len(), "повторяющиеся ключи");
+        // Число подстановок {} должно совпадать, иначе tf потеряет аргументы
+        for key in ru {
+            let holes = |lang: Lang| lang.t(key).matches("{}").count();
+            assert_eq!(holes(Lang::Ru), holes(Lang::En), "ключ {}", key);
+        }
+    }
+}
@@ -0,0 +1,105 @@
+// Задача наименьших квадратов f(x) = Σ r_i(x)², невязки задаются по одной на строку
+use crate::optimizer::{self, DescentParams, OptimizerResult};
+use crate::parser::{GradientMode, ParsedFunction, ParserError};
+use nalgebra::{DMatrix, DVector};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct LeastSquares {
+    residuals: Vec<ParsedFunction>,
+}
+
+impl LeastSquares {
+    pub fn new(residuals: Vec<ParsedFunction>) -> Self {
+        Self { residuals }
+    }
+
+    // Непустые строки текста с невязками
+    pub fn lines(text: &str) -> impl Iterator<Item = &str> {
+        text.lines().map(str::trim).filter(|line| !line.is_empty())
+    }
+
+    // Целевая функция для остальных методов: "(r1)^2 + (r2)^2 + ..."
+    pub fn sum_of_squares(text: &str) -> String {
+        Self::lines(text)
+            .map(|line| format!("({})^2", line))
+            .collect::<Vec<_>>()
+            .join(" + ")
+    }
+
+    pub fn residual_vector(&self, x: &DVector<f64>) -> Result<DVector<f64>, ParserError> {
+        let values = self
+            .residuals
+            .iter()
+            .map(|r| r.eval(x))
+            .collect::<Result<Vec<f64>, _>>()?;
+        Ok(DVector::from_vec(values))
+    }
+
+    // Строка i — градиент невязки r_i
+    pub fn jacobian(
+        &self,
+        x: &DVector<f64>,
+        eps: f64,
+        mode: GradientMode,
+    ) -> Result<DMatrix<f64>, ParserError> {
+        let mut j = DMatrix::zeros(self.residuals.len(), x.len());
+        for (i, r) in self.residuals.iter().enumerate() {
+            j.set_row(i, &r.gradient(x, eps, mode)?.transpose());
+        }
+        Ok(j)
+    }
+
+    // on_eval вызывается при каждом вычислении невязок и якобиана
+    pub fn gauss_newton(
+        &self,
+        start: DVector<f64>,
+        eps: f64,
+        mode: GradientMode,
+        params: &DescentParams,
+        on_eval: &dyn Fn(),
+        stop_flag: Arc<AtomicBool>,
+    ) -> OptimizerResult {
+        // Ошибка вычисления превращается в NaN, на котором метод останавливается
+        let residuals = |x: &DVector<f64>| {
+            on_eval();
+            self.residual_vector(x)
+                .unwrap_or_else(|_| DVector::from_element(self.residuals.len(), f64::NAN))
+        };
+        let jacobian = |x: &DVector<f64>| {
+            on_eval();
+            self.jacobian(x, eps, mode)
+                .unwrap_or_else(|_| DMatrix::from_element(self.residuals.len(), x.len(), f64::NAN))
+        };
+        optimizer::gauss_newton(start, &residuals, &jacobian, params, stop_flag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gauss_newton_solves_linear_system_in_one_step() {
+        let residuals = ["x1 + x2 - 3", "x1 - x2 - 1"]
+            .iter()
+            .map(|r| ParsedFunction::new(r, 2).unwrap())
+            .collect();
+        let ls = LeastSquares::new(residuals);
+        let calls = std::cell::Cell::new(0);
+        let res = ls.gauss_newton(
+            DVector::from_vec(vec![10.0, -7.0]),
+            1e-6,
+            GradientMode::Analytic,
+            &DescentParams::default(),
+            &|| calls.set(calls.get() + 1),
+            Arc::new(AtomicBool::new(false)),
+        );
+        // Для линейных невязок JᵀJ·d = -Jᵀr даёт точное решение
+        assert!(res.termination.is_converged());
+        assert_eq!(res.iterations, 1);
+        assert!((&res.x - DVector::from_vec(vec![2.0, 1.0])).amax() < 1e-12);
+        assert!(calls.get() > 0);
+    }
+}
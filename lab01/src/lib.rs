@@ -0,0 +1,51 @@
+//! Методы оптимизации и разбор целевых функций без графического интерфейса.
+//!
+//! Целевая функция задаётся строкой от переменных x1..xn ([`ParsedFunction`])
+//! или любыми замыканиями над [`nalgebra::DVector`]; градиентный спуск
+//! ([`gradient_descent`]) принимает f, ∇f, параметры и способ выбора шага
+//! и возвращает [`OptimizerResult`] с точкой, значением и траекторией.
+//!
+//! ```
+//! use gradient_descent_app::{gradient_descent, AdaptiveHalving, DescentParams};
+//! use gradient_descent_app::{GradientMode, ParsedFunction, TerminationReason};
+//! use nalgebra::DVector;
+//! use std::sync::atomic::AtomicBool;
+//! use std::sync::Arc;
+//!
+//! let func = ParsedFunction::new("x1^2 + x2^2", 2).unwrap();
+//! let f = |x: &DVector<f64>| func.eval(x).unwrap();
+//! let grad = |x: &DVector<f64>| func.gradient(x, 1e-6, GradientMode::Analytic).unwrap();
+//!
+//! let params = DescentParams::default();
+//! let line_search = AdaptiveHalving::new(
+//!     params.initial_step,
+//!     params.step_decay,
+//!     params.step_increase,
+//!     params.max_step,
+//! );
+//! let result = gradient_descent(
+//!     DVector::from_vec(vec![3.0, -2.0]),
+//!     &f,
+//!     &grad,
+//!     &params,
+//!     &line_search,
+//!     Arc::new(AtomicBool::new(false)),
+//! );
+//!
+//! assert_eq!(result.termination, TerminationReason::Converged);
+//! assert!(result.x.norm() < 1e-6);
+//! ```
+
+mod ast;
+pub mod contour;
+pub mod export;
+pub mod least_squares;
+pub mod line_search;
+pub mod optimizer;
+pub mod parser;
+pub mod presets;
+pub mod surface;
+
+pub use line_search::{AdaptiveHalving, BacktrackingArmijo, GoldenSection, LineSearch};
+pub use optimizer::{gradient_descent, DescentParams, OptimizerResult, TerminationReason};
+pub use parser::{GradientMode, ParsedFunction, ParserError};
@@ -0,0 +1,367 @@
+use crate::optimizer::ObjectiveFn;
+use nalgebra::DVector;
+use std::cell::Cell;
+
+// Принятый шаг одномерного поиска
+pub struct LineSearchStep {
+    pub x: DVector<f64>,
+    pub f_x: f64,
+    pub step: f64,
+    // Число вычислений f при поиске
+    pub evaluations: usize,
+    pub note: Option<&'static str>,
+}
+
+// Выбор шага вдоль направления спуска direction; slope — производная
+// по направлению g·d. None, если точку с меньшим значением f найти не удалось.
+pub trait LineSearch {
+    fn step(
+        &self,
+        f: &ObjectiveFn,
+        x: &DVector<f64>,
+        direction: &DVector<f64>,
+        f_x: f64,
+        slope: f64,
+    ) -> Option<LineSearchStep>;
+
+    // Возврат к начальному шагу для схем, которые подстраивают его между итерациями
+    fn reset(&self) {}
+}
+
+// Шаг перестал сдвигать точку в пределах машинной точности: дальнейшее
+// дробление не найдёт другой точки
+fn step_vanished(x: &DVector<f64>, direction: &DVector<f64>, step: f64) -> bool {
+    step * direction.norm() <= f64::EPSILON * x.norm()
+}
+
+// Исходная схема: шаг дробится до первого уменьшения f (не более
+// max_backtracks раз), после успеха увеличивается для следующей итерации
+// (не более чем до max_step)
+pub struct AdaptiveHalving {
+    initial: f64,
+    current: Cell<f64>,
+    decay: f64,
+    increase: f64,
+    max_step: f64,
+    max_backtracks: usize,
+}
+
+impl AdaptiveHalving {
+    pub fn new(initial_step: f64, decay: f64, increase: f64, max_step: f64) -> Self {
+        Self {
+            initial: initial_step,
+            current: Cell::new(initial_step),
+            decay,
+            increase,
+            max_step,
+            max_backtracks: 20,
+        }
+    }
+
+    pub fn with_max_backtracks(mut self, max_backtracks: usize) -> Self {
+        self.max_backtracks = max_backtracks;
+        self
+    }
+}
+
+impl LineSearch for AdaptiveHalving {
+    fn step(
+        &self,
+        f: &ObjectiveFn,
+        x: &DVector<f64>,
+        direction: &DVector<f64>,
+        f_x: f64,
+        _slope: f64,
+    ) -> Option<LineSearchStep> {
+        let mut trial_step = self.current.get();
+
+        for k in 0..self.max_backtracks {
+            if step_vanished(x, direction, trial_step) {
+                break;
+            }
+            let x_trial = x + trial_step * direction;
+            let f_trial = f(&x_trial);
+
+            if f_trial < f_x {
+                let next = self.increase * trial_step;
+                self.current.set(next.min(self.max_step));
+                return Some(LineSearchStep {
+                    x: x_trial,
+                    f_x: f_trial,
+                    step: trial_step,
+                    evaluations: k + 1,
+                    note: (next > self.max_step).then_some("рост шага ограничен max_step"),
+                });
+            }
+            trial_step *= self.decay;
+        }
+
+        None
+    }
+
+    fn reset(&self) {
+        self.current.set(self.initial);
+    }
+}
+
+// Дробление с условием Армихо f(x + t·d) <= f(x) + c1·t·(g·d). Как и в
+// AdaptiveHalving, следующий поиск начинается с принятого шага, умноженного
+// на increase (не больше max_step); reset возвращает начальный шаг
+pub struct BacktrackingArmijo {
+    initial: f64,
+    current: Cell<f64>,
+    c1: f64,
+    decay: f64,
+    increase: f64,
+    max_step: f64,
+    max_backtracks: usize,
+}
+
+impl BacktrackingArmijo {
+    pub fn new(initial_step: f64, c1: f64, decay: f64) -> Self {
+        Self {
+            initial: initial_step,
+            current: Cell::new(initial_step),
+            c1,
+            decay,
+            increase: 1.0,
+            max_step: f64::INFINITY,
+            max_backtracks: 20,
+        }
+    }
+
+    pub fn with_growth(mut self, increase: f64, max_step: f64) -> Self {
+        self.increase = increase;
+        self.max_step = max_step;
+        self.current.set(self.initial.min(max_step));
+        self
+    }
+
+    pub fn with_max_backtracks(mut self, max_backtracks: usize) -> Self {
+        self.max_backtracks = max_backtracks;
+        self
+    }
+}
+
+impl LineSearch for BacktrackingArmijo {
+    fn step(
+        &self,
+        f: &ObjectiveFn,
+        x: &DVector<f64>,
+        direction: &DVector<f64>,
+        f_x: f64,
+        slope: f64,
+    ) -> Option<LineSearchStep> {
+        // При g·d >= 0 направление не является направлением спуска; при g·d,
+        // близком к нулю, цикл ограничен max_backtracks и завершается неудачей
+        if slope >= 0.0 || slope.is_nan() {
+            return None;
+        }
+        let mut t = self.current.get();
+
+        for k in 0..self.max_backtracks {
+            if step_vanished(x, direction, t) {
+                break;
+            }
+            let x_trial = x + t * direction;
+            let f_trial = f(&x_trial);
+
+            if f_trial <= f_x + self.c1 * t * slope && f_trial < f_x {
+                let next = self.increase * t;
+                self.current.set(next.min(self.max_step));
+                return Some(LineSearchStep {
+                    x: x_trial,
+                    f_x: f_trial,
+                    step: t,
+                    evaluations: k + 1,
+                    note: (next > self.max_step).then_some("рост шага ограничен max_step"),
+                });
+            }
+            t *= self.decay;
+        }
+
+        None
+    }
+
+    fn reset(&self) {
+        self.current.set(self.initial.min(self.max_step));
+    }
+}
+
+// Золотое сечение: сначала отрезок [0, b], на котором φ(t) = f(x + t·d)
+// унимодальна, подбирается удвоением b (не дальше max_step), затем сужается
+// до относительной точности tol
+pub struct GoldenSection {
+    pub initial_step: f64,
+    pub tol: f64,
+    pub max_step: f64,
+}
+
+impl LineSearch for GoldenSection {
+    fn step(
+        &self,
+        f: &ObjectiveFn,
+        x: &DVector<f64>,
+        direction: &DVector<f64>,
+        f_x: f64,
+        _slope: f64,
+    ) -> Option<LineSearchStep> {
+        let phi = |t: f64| f(&(x + t * direction));
+        let mut evaluations = 0;
+
+        // Расширение отрезка, пока функция продолжает убывать
+        let mut b = self.initial_step.min(self.max_step);
+        let mut f_b = phi(b);
+        evaluations += 1;
+        let mut f_prev = f_x;
+        for _ in 0..40 {
+            if f_b >= f_prev || f_b.is_nan() || b >= self.max_step {
+                break;
+            }
+            f_prev = f_b;
+            b = (2.0 * b).min(self.max_step);
+            f_b = phi(b);
+            evaluations += 1;
+        }
+
+        let ratio = (5.0_f64.sqrt() - 1.0) / 2.0;
+        let mut a = 0.0;
+        let mut c = b - ratio * (b - a);
+        let mut d = a + ratio * (b - a);
+        let mut f_c = phi(c);
+        let mut f_d = phi(d);
+        evaluations += 2;
+
+        while (b - a) > self.tol * b.max(1.0) && evaluations < 200 {
+            if f_c < f_d {
+                b = d;
+                d = c;
+                f_d = f_c;
+                c = b - ratio * (b - a);
+                f_c = phi(c);
+            } else {
+                a = c;
+                c = d;
+                f_c = f_d;
+                d = a + ratio * (b - a);
+                f_d = phi(d);
+            }
+            evaluations += 1;
+        }
+
+        let t = (a + b) / 2.0;
+        let x_new = x + t * direction;
+        let f_new = f(&x_new);
+        evaluations += 1;
+
+        (f_new < f_x).then_some(LineSearchStep {
+            x: x_new,
+            f_x: f_new,
+            step: t,
+            evaluations,
+            note: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quadratic(x: &DVector<f64>) -> f64 {
+        x.dot(x)
+    }
+
+    fn descent_at(x: &DVector<f64>) -> (DVector<f64>, f64) {
+        let g = 2.0 * x;
+        let d = -&g;
+        let slope = g.dot(&d);
+        (d, slope)
+    }
+
+    #[test]
+    fn armijo_step_satisfies_sufficient_decrease() {
+        let x = DVector::from_vec(vec![3.0, -1.0]);
+        let (d, slope) = descent_at(&x);
+        let c1 = 0.5;
+        let ls = BacktrackingArmijo::new(4.0, c1, 0.5);
+        let step = ls.step(&quadratic, &x, &d, quadratic(&x), slope).unwrap();
+        assert!(step.f_x <= quadratic(&x) + c1 * step.step * slope);
+        assert!(step.step < 4.0);
+    }
+
+    #[test]
+    fn armijo_fails_on_ascent_direction() {
+        let x = DVector::from_vec(vec![1.0]);
+        let (d, slope) = descent_at(&x);
+        let ls = BacktrackingArmijo::new(1.0, 1e-4, 0.5);
+        assert!(ls.step(&quadratic, &x, &-d, 1.0, -slope).is_none());
+    }
+
+    #[test]
+    fn armijo_grows_step_up_to_max_step_and_resets() {
+        let x = DVector::from_vec(vec![100.0]);
+        let d = DVector::from_vec(vec![-1.0]);
+        let ls = BacktrackingArmijo::new(1.0, 1e-4, 0.5).with_growth(2.0, 3.0);
+        let steps: Vec<f64> = (0..3)
+            .map(|_| {
+                ls.step(&quadratic, &x, &d, quadratic(&x), -200.0)
+                    .unwrap()
+                    .step
+            })
+            .collect();
+        assert_eq!(steps, vec![1.0, 2.0, 3.0]);
+        ls.reset();
+        assert_eq!(
+            ls.step(&quadratic, &x, &d, quadratic(&x), -200.0)
+                .unwrap()
+                .step,
+            1.0
+        );
+    }
+
+    #[test]
+    fn halving_respects_backtrack_cap() {
+        let x = DVector::from_vec(vec![1.0]);
+        let (d, slope) = descent_at(&x);
+        // Из x = 1 уменьшение f даёт только шаг меньше 1
+        let ls = AdaptiveHalving::new(1024.0, 0.5, 1.0, f64::INFINITY).with_max_backtracks(5);
+        assert!(ls.step(&quadratic, &x, &d, 1.0, slope).is_none());
+        let ls = AdaptiveHalving::new(1024.0, 0.5, 1.0, f64::INFINITY).with_max_backtracks(20);
+        assert!(ls.step(&quadratic, &x, &d, 1.0, slope).is_some());
+    }
+
+    #[test]
+    fn every_line_search_decreases_f() {
+        let x = DVector::from_vec(vec![3.0, -1.0]);
+        let (d, slope) = descent_at(&x);
+        let searches: Vec<Box<dyn LineSearch>> = vec![
+            Box::new(AdaptiveHalving::new(1.0, 0.5, 1.0, 1.0)),
+            Box::new(BacktrackingArmijo::new(1.0, 1e-4, 0.5)),
+            Box::new(GoldenSection {
+                initial_step: 1.0,
+                tol: 1e-8,
+                max_step: f64::INFINITY,
+            }),
+        ];
+        for ls in &searches {
+            let step = ls.step(&quadratic, &x, &d, quadratic(&x), slope).unwrap();
+            assert!(step.f_x < quadratic(&x));
+        }
+    }
+
+    #[test]
+    fn golden_section_finds_exact_minimum_along_direction() {
+        let x = DVector::from_vec(vec![3.0, -1.0]);
+        let (d, slope) = descent_at(&x);
+        let ls = GoldenSection {
+            initial_step: 0.1,
+            tol: 1e-8,
+            max_step: f64::INFINITY,
+        };
+        let step = ls.step(&quadratic, &x, &d, quadratic(&x), slope).unwrap();
+        // Вдоль антиградиента x·x минимум достигается при t = 1/2 в точке 0
+        assert!((step.step - 0.5).abs() < 1e-6, "step = {}", step.step);
+        assert!(step.f_x < 1e-10);
+    }
+}
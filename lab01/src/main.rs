@@ -1,10 +1,15 @@
 use eframe::egui;
 
+mod cli;
 mod gui;
-mod optimizer;
-mod parser;
+mod i18n;
 
 fn main() -> Result<(), eframe::Error> {
+    // С аргументами командной строки окно не создаётся
+    if std::env::args_os().len() > 1 {
+        std::process::exit(cli::run());
+    }
+
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default().with_inner_size([1000.0, 700.0]),
         ..Default::default()
@@ -12,6 +17,6 @@ fn main() -> Result<(), eframe::Error> {
     eframe::run_native(
         "Градиентный спуск",
         options,
-        Box::new(|_cc| Ok(Box::new(gui::GradientDescentApp::default()))),
+        Box::new(|cc| Ok(Box::new(gui::GradientDescentApp::new(cc)))),
     )
 }
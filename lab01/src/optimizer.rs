@@ -1,82 +1,954 @@
-use nalgebra::DVector;
+use crate::line_search::{AdaptiveHalving, GoldenSection, LineSearch, LineSearchStep};
+use nalgebra::{DMatrix, DVector};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rand_distr::StandardNormal;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use thiserror::Error;
 
-pub type ObjectiveFn = dyn Fn(&DVector<f64>) -> f64;
-pub type GradientFn = dyn Fn(&DVector<f64>) -> DVector<f64>;
+pub type ObjectiveFn<'a> = dyn Fn(&DVector<f64>) -> f64 + 'a;
+pub type GradientFn<'a> = dyn Fn(&DVector<f64>) -> DVector<f64> + 'a;
+pub type HessianFn<'a> = dyn Fn(&DVector<f64>) -> DMatrix<f64> + 'a;
+pub type ResidualFn<'a> = dyn Fn(&DVector<f64>) -> DVector<f64> + 'a;
+pub type JacobianFn<'a> = dyn Fn(&DVector<f64>) -> DMatrix<f64> + 'a;
+pub type ProgressFn = dyn Fn(usize, &DVector<f64>, f64) + Send + Sync;
+// Одномерный поиск с параметрами шага из DescentParams; нужен там, где
+// запускается несколько спусков с разными параметрами
+pub type LineSearchFactory<'a> = dyn Fn(&DescentParams) -> Box<dyn LineSearch> + 'a;
 
-#[derive(Debug)]
+#[derive(Error, Debug)]
+pub enum OptimizerError {
+    #[error("Начальный шаг должен быть положительным, задано {0}")]
+    NonPositiveStep(f64),
+    #[error("Наибольший шаг должен быть положительным, задано {0}")]
+    NonPositiveMaxStep(f64),
+    #[error("Коэффициент уменьшения шага должен лежать в (0, 1), задано {0}")]
+    InvalidStepDecay(f64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum TerminationReason {
+    Converged,
+    FlatRegion,
+    MaxIterations,
+    UserStopped,
+    LineSearchFailed,
+    // В градиенте появились NaN или бесконечности
+    GradientNaN,
+    // Сработал критерий относительного изменения f (ftol)
+    FunctionTolerance,
+    // Сработал критерий длины шага (xtol)
+    StepTolerance,
+    // Значение f стало NaN или бесконечностью (точка вне области определения)
+    NonFiniteValue,
+    // Параметры не прошли DescentParams::validate, причина записана в лог
+    InvalidParams,
+}
+
+impl TerminationReason {
+    // Остановка по одному из критериев сходимости
+    pub fn is_converged(self) -> bool {
+        matches!(
+            self,
+            TerminationReason::Converged
+                | TerminationReason::FunctionTolerance
+                | TerminationReason::StepTolerance
+        )
+    }
+}
+
+/// Итог запуска: точка, значение, траектория, причина останова и счётчики вычислений.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OptimizerResult {
     pub x: DVector<f64>,
     pub f_x: f64,
     pub iterations: usize,
-    pub history: Vec<(f64, f64, f64)>,
-    pub terminated_early: bool,
+    pub history: Vec<(DVector<f64>, f64)>,
+    pub termination: TerminationReason,
+    pub log: Vec<String>,
+    pub trust_radii: Vec<f64>,
+    // ||∇f|| в точках history и длины шагов ||x_{k+1} - x_k||
+    // (заполняются градиентным спуском)
+    pub grad_norms: Vec<f64>,
+    pub step_sizes: Vec<f64>,
+    // Итерации, на которых к точке было добавлено случайное возмущение
+    pub perturbations: Vec<usize>,
+    // Номера итераций точек history после прореживания; пусто, если
+    // траектория не прореживалась и k-я точка соответствует k-й итерации
+    #[serde(default)]
+    pub history_iterations: Vec<usize>,
+    // Время работы и число вычислений f и ∇f, включая пробные точки
+    // одномерного поиска; в файлах старых запусков отсутствуют
+    #[serde(default)]
+    pub elapsed: Duration,
+    #[serde(default)]
+    pub f_evals: usize,
+    #[serde(default)]
+    pub grad_evals: usize,
+}
+
+const EVAL_CACHE_SIZE: usize = 4;
+
+// Несколько последних вычисленных значений: повторный запрос в той же точке
+// (принятая точка одномерного поиска, градиент в точке после останова)
+// берётся из кэша. Точки считаются совпадающими, если координаты отличаются
+// не больше чем на машинную точность относительно их величины
+struct EvalCache<T> {
+    entries: RefCell<VecDeque<(DVector<f64>, T)>>,
+}
+
+impl<T: Clone> EvalCache<T> {
+    fn new() -> Self {
+        Self {
+            entries: RefCell::new(VecDeque::with_capacity(EVAL_CACHE_SIZE)),
+        }
+    }
+
+    fn get(&self, x: &DVector<f64>) -> Option<T> {
+        let same = |y: &DVector<f64>| {
+            y.len() == x.len()
+                && x.iter()
+                    .zip(y.iter())
+                    .all(|(a, b)| (a - b).abs() <= f64::EPSILON * a.abs().max(1.0))
+        };
+        self.entries
+            .borrow()
+            .iter()
+            .find(|(y, _)| same(y))
+            .map(|(_, value)| value.clone())
+    }
+
+    fn insert(&self, x: &DVector<f64>, value: T) {
+        let mut entries = self.entries.borrow_mut();
+        if entries.len() == EVAL_CACHE_SIZE {
+            entries.pop_front();
+        }
+        entries.push_back((x.clone(), value));
+    }
+}
+
+// Обёртки над f и ∇f, подсчитывающие вызовы; повторные вызовы в недавних
+// точках не выполняются и не считаются
+struct EvalCounter {
+    started: Instant,
+    f_evals: Cell<usize>,
+    grad_evals: Cell<usize>,
+    f_cache: EvalCache<f64>,
+    grad_cache: EvalCache<DVector<f64>>,
+}
+
+impl EvalCounter {
+    fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            f_evals: Cell::new(0),
+            grad_evals: Cell::new(0),
+            f_cache: EvalCache::new(),
+            grad_cache: EvalCache::new(),
+        }
+    }
+
+    fn objective<'a>(&'a self, f: &'a ObjectiveFn) -> impl Fn(&DVector<f64>) -> f64 + 'a {
+        move |x| {
+            if let Some(f_x) = self.f_cache.get(x) {
+                return f_x;
+            }
+            self.f_evals.set(self.f_evals.get() + 1);
+            let f_x = f(x);
+            self.f_cache.insert(x, f_x);
+            f_x
+        }
+    }
+
+    fn gradient<'a>(&'a self, grad: &'a GradientFn) -> impl Fn(&DVector<f64>) -> DVector<f64> + 'a {
+        move |x| {
+            if let Some(g) = self.grad_cache.get(x) {
+                return g;
+            }
+            self.grad_evals.set(self.grad_evals.get() + 1);
+            let g = grad(x);
+            self.grad_cache.insert(x, g.clone());
+            g
+        }
+    }
+}
+
+impl OptimizerResult {
+    // При выходе из области определения итоговой считается последняя точка
+    // истории с конечным значением; если такой нет, остаётся начальная
+    fn revert_non_finite(mut self) -> Self {
+        if self.termination == TerminationReason::NonFiniteValue {
+            if let Some((x, f_x)) = self.history.iter().rev().find(|(_, f)| f.is_finite()) {
+                self.x = x.clone();
+                self.f_x = *f_x;
+            }
+        }
+        self
+    }
+
+    // Ограничение длины лога max_lines строками: остаются первая (старт)
+    // и последние, между ними — строка с числом пропущенных записей
+    pub fn cap_log(&mut self, max_lines: usize) {
+        if max_lines < 3 || self.log.len() <= max_lines {
+            return;
+        }
+        let end = self.log.len() - (max_lines - 2);
+        self.log.drain(1..end);
+        self.log
+            .insert(1, format!("… пропущено записей: {}", end - 1));
+    }
+
+    // Номер итерации k-й точки history
+    pub fn iteration_at(&self, k: usize) -> usize {
+        self.history_iterations.get(k).copied().unwrap_or(k)
+    }
+
+    // Позиция в history первой точки не раньше итерации iteration
+    pub fn position_of(&self, iteration: usize) -> usize {
+        if self.history_iterations.is_empty() {
+            iteration
+        } else {
+            self.history_iterations.partition_point(|&i| i < iteration)
+        }
+    }
+
+    // Прореживание длинной траектории: остаются каждая k-я точка и последняя,
+    // всего не больше cap + 1. Радиусы доверительной области, нормы градиента
+    // и длины шагов прореживаются по тем же номерам, номера итераций
+    // оставшихся точек сохраняются в history_iterations
+    fn thin_history(mut self, cap: usize) -> Self {
+        let len = self.history.len();
+        if cap == 0 || len <= cap + 1 {
+            return self;
+        }
+        let k = len.div_ceil(cap);
+        let mut kept: Vec<usize> = (0..len).step_by(k).collect();
+        if kept.last() != Some(&(len - 1)) {
+            kept.push(len - 1);
+        }
+        self.history_iterations = kept.iter().map(|&i| self.iteration_at(i)).collect();
+        self.history = kept.iter().map(|&i| self.history[i].clone()).collect();
+        if self.trust_radii.len() == len {
+            self.trust_radii = kept.iter().map(|&i| self.trust_radii[i]).collect();
+        }
+        if self.grad_norms.len() == len {
+            self.grad_norms = kept.iter().map(|&i| self.grad_norms[i]).collect();
+        }
+        // Шаг с номером i ведёт из i-й точки в следующую, у последней точки шага нет
+        self.step_sizes = kept
+            .iter()
+            .filter_map(|&i| self.step_sizes.get(i).copied())
+            .collect();
+        self
+    }
+
+    // Продолжение запуска из его последней точки: история и счётчики
+    // накапливаются, начальная точка продолжения не дублируется
+    pub fn append(&mut self, next: OptimizerResult) {
+        let offset = self.iterations;
+        if !self.history_iterations.is_empty() || !next.history_iterations.is_empty() {
+            let mut iterations: Vec<usize> = (0..self.history.len())
+                .map(|k| self.iteration_at(k))
+                .collect();
+            iterations.extend((1..next.history.len()).map(|k| next.iteration_at(k) + offset));
+            self.history_iterations = iterations;
+        }
+        self.history.extend(next.history.into_iter().skip(1));
+        self.trust_radii
+            .extend(next.trust_radii.into_iter().skip(1));
+        self.grad_norms.extend(next.grad_norms);
+        self.step_sizes.extend(next.step_sizes);
+        self.perturbations
+            .extend(next.perturbations.iter().map(|&i| i + offset));
+        self.log.extend(next.log);
+        self.iterations += next.iterations;
+        self.f_evals += next.f_evals;
+        self.grad_evals += next.grad_evals;
+        self.elapsed += next.elapsed;
+        self.termination = next.termination;
+        self.x = next.x;
+        self.f_x = next.f_x;
+    }
+
+    // Возврат от нормированной f / scale к исходным значениям f и ||∇f||
+    fn unscale_objective(&mut self, scale: f64) {
+        self.f_x *= scale;
+        for (_, f_x) in &mut self.history {
+            *f_x *= scale;
+        }
+        for g_norm in &mut self.grad_norms {
+            *g_norm *= scale;
+        }
+    }
+
+    // Максимизация сводится к минимизации -f; значения возвращаются к исходной f
+    pub fn negate_objective(&mut self) {
+        self.f_x = -self.f_x;
+        for (_, f_x) in &mut self.history {
+            *f_x = -*f_x;
+        }
+    }
+}
+
+/// Параметры спуска; значения по умолчанию подходят для большинства гладких функций.
+#[derive(Debug, Clone)]
+pub struct DescentParams {
+    pub initial_step: f64,
+    pub step_decay: f64,
+    // Наибольшее число дроблений шага за одну итерацию
+    pub max_backtracks: usize,
+    pub step_increase: f64,
+    // Верхняя граница шага, до которой он растёт после удачных итераций
+    pub max_step: f64,
+    // Константа c1 условия достаточного убывания Армихо
+    pub armijo_c1: f64,
+    pub tolerance: f64,
+    // Сравнивать с tolerance среднеквадратичную компоненту градиента ||g|| / √n
+    // вместо нормы, чтобы точность не зависела от размерности
+    pub scale_tolerance: bool,
+    // Останов при ||g_k|| < tolerance·||g_0|| вместо ||g_k|| < tolerance; при
+    // нулевом градиенте в начальной точке остаётся абсолютная проверка
+    pub rel_grad: bool,
+    // Градиентный спуск по f(x) / |f(x0)|: выбор шага и точность не зависят
+    // от масштаба значений функции; в результате значения исходной f
+    pub normalize_objective: bool,
+    // Дополнительные критерии останова градиентного спуска, 0 — выключен:
+    // |f_{k+1} - f_k| / (|f_k| + eps) < ftol и ||x_{k+1} - x_k|| < xtol
+    pub ftol: f64,
+    pub xtol: f64,
+    // Тёплые рестарты градиентного спуска: каждые restart_period итераций шаг
+    // возвращается к initial_step (0 — выключены), период может удваиваться
+    pub restart_period: usize,
+    pub restart_doubling: bool,
+    // Градиент с нормой больше max_grad_norm укорачивается до неё перед
+    // выбором шага (только градиентный спуск), 0 — без ограничения
+    pub max_grad_norm: f64,
+    pub max_iterations: usize,
+    pub verbose: bool,
+    pub detect_flat: bool,
+    pub fd_eps: f64,
+    // Индексы угловых координат с периодом 2π (полярная/сферическая система)
+    pub periodic: Vec<usize>,
+    pub perturb: Option<PerturbParams>,
+    pub bounds: Option<Bounds>,
+    pub progress: Option<Progress>,
+    // Наибольшее число точек траектории в результате, 0 — без прореживания
+    pub max_history_points: usize,
+}
+
+// Промежуточный отчёт о ходе оптимизации: callback получает номер итерации,
+// текущую точку и значение f каждые every итераций
+#[derive(Clone)]
+pub struct Progress {
+    pub every: usize,
+    pub callback: Arc<ProgressFn>,
+}
+
+impl std::fmt::Debug for Progress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Progress")
+            .field("every", &self.every)
+            .finish_non_exhaustive()
+    }
+}
+
+// Прямоугольные ограничения lower <= x <= upper
+#[derive(Debug, Clone)]
+pub struct Bounds {
+    pub lower: DVector<f64>,
+    pub upper: DVector<f64>,
+}
+
+impl Bounds {
+    pub fn project(&self, x: &mut DVector<f64>) {
+        for i in 0..x.len() {
+            x[i] = x[i].max(self.lower[i]).min(self.upper[i]);
+        }
+    }
+
+    // Проектированный градиент x - P(x - g): компоненты, выводящие за
+    // активную границу, обнуляются, внутри области совпадает с g
+    pub fn projected_gradient(&self, x: &DVector<f64>, g: &DVector<f64>) -> DVector<f64> {
+        let mut y = x - g;
+        self.project(&mut y);
+        x - y
+    }
+}
+
+// Градиент, по норме которого проверяется сходимость: при ограничениях
+// точка на границе может быть решением и при ненулевом градиенте
+fn stationarity_gradient(
+    params: &DescentParams,
+    x: &DVector<f64>,
+    g: &DVector<f64>,
+) -> DVector<f64> {
+    match &params.bounds {
+        Some(bounds) => bounds.projected_gradient(x, g),
+        None => g.clone(),
+    }
+}
+
+impl DescentParams {
+    fn report(&self, iter: usize, x: &DVector<f64>, f_x: f64) {
+        if let Some(progress) = &self.progress {
+            if iter.is_multiple_of(progress.every.max(1)) {
+                (progress.callback)(iter, x, f_x);
+            }
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), OptimizerError> {
+        // При нулевом шаге пробная точка совпадает с текущей и спуск
+        // завершается на первой же итерации без объяснения причины
+        if self.initial_step <= 0.0 || self.initial_step.is_nan() {
+            return Err(OptimizerError::NonPositiveStep(self.initial_step));
+        }
+        if self.max_step <= 0.0 || self.max_step.is_nan() {
+            return Err(OptimizerError::NonPositiveMaxStep(self.max_step));
+        }
+        // При коэффициенте >= 1 дробление не уменьшает шаг, и каждая итерация
+        // тратит все попытки поиска на одну и ту же неудачную точку
+        if !(self.step_decay > 0.0 && self.step_decay < 1.0) {
+            return Err(OptimizerError::InvalidStepDecay(self.step_decay));
+        }
+        Ok(())
+    }
+}
+
+// Параметры выхода из седловых точек (perturbed gradient descent)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerturbParams {
+    // Норма градиента, ниже которой точка считается подозрительной на седло
+    pub threshold: f64,
+    // Сколько итераций подряд градиент должен быть мал перед возмущением
+    pub patience: usize,
+    pub radius: f64,
+    pub max_perturbations: usize,
+    pub seed: u64,
+}
+
+impl Default for PerturbParams {
+    fn default() -> Self {
+        Self {
+            threshold: 1e-3,
+            patience: 10,
+            radius: 0.1,
+            max_perturbations: 5,
+            seed: 42,
+        }
+    }
+}
+
+impl Default for DescentParams {
+    fn default() -> Self {
+        Self {
+            initial_step: 1.0,
+            step_decay: 0.5,
+            max_backtracks: 20,
+            step_increase: 1.2,
+            max_step: 1.0,
+            armijo_c1: 1e-4,
+            tolerance: 1e-6,
+            scale_tolerance: false,
+            rel_grad: false,
+            normalize_objective: false,
+            ftol: 0.0,
+            xtol: 0.0,
+            restart_period: 0,
+            restart_doubling: false,
+            max_grad_norm: 0.0,
+            max_iterations: 1000,
+            verbose: false,
+            detect_flat: false,
+            fd_eps: 1e-6,
+            periodic: Vec::new(),
+            perturb: None,
+            bounds: None,
+            progress: None,
+            max_history_points: 0,
+        }
+    }
+}
+
+// Приведение угловых координат к (-π, π]: шаг через разрез не должен
+// выглядеть для критериев останова как большое перемещение
+pub fn wrap_periodic(x: &mut DVector<f64>, periodic: &[usize]) {
+    use std::f64::consts::PI;
+    for &i in periodic {
+        if i < x.len() {
+            let wrapped = (x[i] + PI).rem_euclid(2.0 * PI) - PI;
+            x[i] = if wrapped == -PI { PI } else { wrapped };
+        }
+    }
+}
+
+// Равномерно распределённая точка в шаре радиуса radius
+fn random_in_ball(rng: &mut StdRng, n: usize, radius: f64) -> DVector<f64> {
+    let dir = DVector::from_fn(n, |_, _| rng.sample::<f64, _>(StandardNormal));
+    let scale = radius * rng.gen::<f64>().powf(1.0 / n as f64) / dir.norm().max(f64::MIN_POSITIVE);
+    dir * scale
+}
+
+// Точка со сдвигом каждой координаты на N(0, sigma²); при ограничениях
+// результат проектируется на допустимый брус
+pub fn perturb_point(
+    x: &DVector<f64>,
+    sigma: f64,
+    seed: u64,
+    bounds: Option<&Bounds>,
+) -> DVector<f64> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut y = x + DVector::from_fn(x.len(), |_, _| sigma * rng.sample::<f64, _>(StandardNormal));
+    if let Some(bounds) = bounds {
+        bounds.project(&mut y);
+    }
+    y
+}
+
+// Оценка уровня шума конечно-разностного градиента в точке x. Шум значения f
+// оценивается четвёртой разностью f(x-2h·d) - 4f(x-h·d) + 6f(x) - 4f(x+h·d) +
+// f(x+2h·d) при h много меньше eps: она сокращает многочлены до третьей степени,
+// так что остаётся ошибка округления, усиленная в √70 раз
+fn fd_noise_floor(f: &ObjectiveFn, x: &DVector<f64>, f_x: f64, eps: f64) -> f64 {
+    let n = x.len();
+    let dir = DVector::from_element(n, 1.0 / (n as f64).sqrt());
+
+    let mut noise = f64::EPSILON * f_x.abs();
+    for k in 1..=2 {
+        let h = k as f64 * 1e-2 * eps;
+        let at = |j: f64| f(&(x + (j * h) * &dir));
+        let fourth = at(-2.0) - 4.0 * at(-1.0) + 6.0 * f_x - 4.0 * at(1.0) + at(2.0);
+        noise = noise.max(fourth.abs() / 70f64.sqrt());
+    }
+
+    2.0 * (n as f64).sqrt() * noise / eps
+}
+
+// Одномерный поиск с проекцией пробных точек на ограничения; у принятой
+// точки угловые координаты приводятся к (-π, π]
+fn search_step(
+    line_search: &dyn LineSearch,
+    f: &ObjectiveFn,
+    x: &DVector<f64>,
+    f_x: f64,
+    direction: &DVector<f64>,
+    slope: f64,
+    params: &DescentParams,
+) -> Option<LineSearchStep> {
+    let project = |y: &DVector<f64>| {
+        let mut y = y.clone();
+        if let Some(bounds) = &params.bounds {
+            bounds.project(&mut y);
+        }
+        y
+    };
+    let f_projected = |y: &DVector<f64>| f(&project(y));
+
+    let mut accepted = line_search.step(&f_projected, x, direction, f_x, slope)?;
+    accepted.x = project(&accepted.x);
+    wrap_periodic(&mut accepted.x, &params.periodic);
+    Some(accepted)
+}
+
+// Начальный шаг по одной пробной точке вдоль -g0: f на этом луче приближается
+// параболой φ(t) = f0 - t·||g||² + c·t²/2, и шаг t = ||g||²/c точен для
+// квадратичной функции. Пробная точка отстоит от x0 на единицу длины (ближе,
+// если там f не определена); если вдоль -g0 функция не выпукла (c <= 0),
+// возвращается пробный шаг. None — градиент в x0 нулевой или не определён
+pub fn estimate_initial_step(x0: &DVector<f64>, f: &ObjectiveFn, grad: &GradientFn) -> Option<f64> {
+    let g = grad(x0);
+    let g2 = g.norm_squared();
+    let f0 = f(x0);
+    if !(g2 > 0.0 && g2.is_finite() && f0.is_finite()) {
+        return None;
+    }
+    let mut probe = 1.0 / g2.sqrt();
+    for _ in 0..30 {
+        let f_probe = f(&(x0 - probe * &g));
+        if f_probe.is_finite() {
+            let c = 2.0 * (f_probe - f0 + probe * g2) / (probe * probe);
+            return Some(if c > 0.0 { g2 / c } else { probe });
+        }
+        probe *= 0.5;
+    }
+    None
+}
+
+// Спуск по f / scale с scale = |f(x0)|; при f(x0) = 0 или неопределённом
+// значении нормировка не применяется. Промежуточные отчёты и результат
+// переводятся обратно в значения исходной функции
+fn normalized_gradient_descent(
+    initial_point: DVector<f64>,
+    f: &ObjectiveFn,
+    grad: &GradientFn,
+    params: &DescentParams,
+    line_search: &dyn LineSearch,
+    stop_flag: Arc<AtomicBool>,
+) -> OptimizerResult {
+    let mut x0 = initial_point.clone();
+    if let Some(bounds) = &params.bounds {
+        bounds.project(&mut x0);
+    }
+    let f0 = f(&x0).abs();
+    let scale = if f0.is_finite() && f0 > 0.0 { f0 } else { 1.0 };
+    let f_scaled = |x: &DVector<f64>| f(x) / scale;
+    let grad_scaled = |x: &DVector<f64>| grad(x) / scale;
+    let inner = DescentParams {
+        normalize_objective: false,
+        progress: params.progress.as_ref().map(|p| {
+            let callback = p.callback.clone();
+            Progress {
+                every: p.every,
+                callback: Arc::new(move |iter, x, f_x| callback(iter, x, f_x * scale)),
+            }
+        }),
+        ..params.clone()
+    };
+    let mut result = gradient_descent(
+        initial_point,
+        &f_scaled,
+        &grad_scaled,
+        &inner,
+        line_search,
+        stop_flag,
+    );
+    result.unscale_objective(scale);
+    result.f_evals += 1;
+    if params.verbose {
+        result.log.insert(
+            0,
+            format!("нормировка: f / {:.6e}, значения в логе нормированы", scale),
+        );
+    }
+    result
 }
 
+/// Градиентный спуск из `initial_point`; шаг вдоль -∇f выбирает `line_search`.
+/// Запуск прерывается, если `stop_flag` установлен из другого потока.
 pub fn gradient_descent(
     initial_point: DVector<f64>,
     f: &ObjectiveFn,
     grad: &GradientFn,
-    initial_step: f64,
-    step_decay: f64,
-    step_increase: f64,
-    tolerance: f64,
-    max_iterations: usize,
+    params: &DescentParams,
+    line_search: &dyn LineSearch,
     stop_flag: Arc<AtomicBool>,
 ) -> OptimizerResult {
+    if params.normalize_objective {
+        return normalized_gradient_descent(initial_point, f, grad, params, line_search, stop_flag);
+    }
+    let counter = EvalCounter::new();
+    let f = &counter.objective(f);
+    let grad = &counter.gradient(grad);
     let mut x = initial_point;
+    if let Some(bounds) = &params.bounds {
+        bounds.project(&mut x);
+    }
     let mut f_x = f(&x);
     let mut iter = 0;
-    let mut step = initial_step;
 
     let mut history = Vec::new();
-    history.push((x[0], x[1], f_x));
+    history.push((x.clone(), f_x));
+    let mut grad_norms = Vec::new();
+    let mut step_sizes = Vec::new();
+    let mut termination = TerminationReason::MaxIterations;
+
+    // Подробный лог ведётся только по запросу, чтобы не тратить время на форматирование
+    let mut log = Vec::new();
+    if params.verbose {
+        log.push(format!("старт: f(x0) = {:.6e}", f_x));
+    }
+
+    let mut rng = params
+        .perturb
+        .as_ref()
+        .map(|p| StdRng::seed_from_u64(p.seed));
+    let mut perturbations = Vec::new();
+    let mut small_grad_iters = 0;
+    // Лучшая точка перед возмущением: возвращается, если возмущение не помогло
+    let mut best_before_perturb: Option<(DVector<f64>, f64)> = None;
+    let mut restart_period = params.restart_period;
+    let mut next_restart = restart_period;
+    // Порог для нормы градиента; при rel_grad уточняется на первой итерации
+    let mut grad_tolerance: Option<f64> = None;
 
-    while iter < max_iterations {
+    // Вызов из библиотеки мог не пройти validate: при step_decay >= 1 дробление
+    // увеличивало бы шаг, и поиск завершался бы непонятной неудачей
+    let invalid = params.validate().err();
+    if let Some(e) = &invalid {
+        log.push(e.to_string());
+        termination = TerminationReason::InvalidParams;
+    }
+
+    while invalid.is_none() && iter < params.max_iterations {
         if stop_flag.load(Ordering::SeqCst) {
+            if params.verbose {
+                log.push(format!("итер. {}: остановлено пользователем", iter + 1));
+            }
+            // Значение пересчитывается в возвращаемой точке, чтобы x и f_x
+            // в результате и последняя точка истории были согласованы
+            f_x = f(&x);
+            match history.last_mut() {
+                Some((last_x, last_f)) if *last_x == x => *last_f = f_x,
+                _ => history.push((x.clone(), f_x)),
+            }
             return OptimizerResult {
                 x,
                 f_x,
                 iterations: iter,
                 history,
-                terminated_early: true,
-            };
+                termination: TerminationReason::UserStopped,
+                log,
+                trust_radii: Vec::new(),
+                grad_norms,
+                step_sizes,
+                perturbations,
+                history_iterations: Vec::new(),
+                elapsed: counter.started.elapsed(),
+                f_evals: counter.f_evals.get(),
+                grad_evals: counter.grad_evals.get(),
+            }
+            .thin_history(params.max_history_points);
+        }
+
+        if !f_x.is_finite() {
+            if params.verbose {
+                log.push(format!(
+                    "итер. {}: f = {}, точка вне области определения, остановка",
+                    iter + 1,
+                    f_x
+                ));
+            }
+            termination = TerminationReason::NonFiniteValue;
+            break;
         }
 
         let g = grad(&x);
+        let g_norm = g.norm();
+        grad_norms.push(g_norm);
 
-        if g.norm() < tolerance {
+        if !g_norm.is_finite() {
+            if params.verbose {
+                log.push(format!(
+                    "итер. {}: градиент содержит NaN или бесконечность, остановка",
+                    iter + 1
+                ));
+            }
+            termination = TerminationReason::GradientNaN;
             break;
         }
 
-        let direction = -g;
+        let pg = stationarity_gradient(params, &x, &g);
+        let pg_norm = if params.scale_tolerance {
+            pg.norm() / (x.len() as f64).sqrt()
+        } else {
+            pg.norm()
+        };
+        let tolerance = *grad_tolerance.get_or_insert(if params.rel_grad && pg_norm > 0.0 {
+            params.tolerance * pg_norm
+        } else {
+            params.tolerance
+        });
+
+        if let (Some(pp), Some(rng)) = (&params.perturb, rng.as_mut()) {
+            if g_norm < pp.threshold {
+                small_grad_iters += 1;
+            } else {
+                small_grad_iters = 0;
+            }
+
+            let since_last = perturbations
+                .last()
+                .map_or(usize::MAX, |&k: &usize| iter - k);
+            let may_perturb =
+                perturbations.len() < pp.max_perturbations && since_last > pp.patience;
+            // Сошедшийся спуск возмущается, только если точка — седло: в минимуме
+            // возмущение лишь отодвинуло бы найденную точку
+            let stalled = small_grad_iters >= pp.patience
+                || (pg_norm < tolerance && has_negative_curvature(grad, &x, &g));
+
+            if may_perturb && stalled {
+                if best_before_perturb
+                    .as_ref()
+                    .is_none_or(|(_, best_f)| f_x < *best_f)
+                {
+                    best_before_perturb = Some((x.clone(), f_x));
+                }
 
-        // Адаптивный выбор шага
-        let mut found_step = false;
-        let mut trial_step = step;
+                let x_prev = x.clone();
+                x += random_in_ball(rng, x.len(), pp.radius);
+                wrap_periodic(&mut x, &params.periodic);
+                if let Some(bounds) = &params.bounds {
+                    bounds.project(&mut x);
+                }
+                step_sizes.push((&x - &x_prev).norm());
+                f_x = f(&x);
+                small_grad_iters = 0;
+                perturbations.push(iter);
+
+                if params.verbose {
+                    log.push(format!(
+                        "итер. {}: ||g|| = {:.3e}, возможная седловая точка, случайное возмущение радиуса {:.3e}, f = {:.6e}",
+                        iter + 1,
+                        g_norm,
+                        pp.radius,
+                        f_x
+                    ));
+                }
+
+                iter += 1;
+                history.push((x.clone(), f_x));
+                params.report(iter, &x, f_x);
+                continue;
+            }
+        }
 
-        for _ in 0..20 {
-            let x_trial = &x + trial_step * &direction;
-            let f_trial = f(&x_trial);
+        if pg_norm < tolerance {
+            if params.verbose {
+                log.push(format!(
+                    "итер. {}: {} = {:.3e} < {:.1e}{}, сходимость",
+                    iter + 1,
+                    if params.scale_tolerance {
+                        "||g||/√n"
+                    } else {
+                        "||g||"
+                    },
+                    pg_norm,
+                    tolerance,
+                    if params.rel_grad {
+                        " (относительно ||g0||)"
+                    } else {
+                        ""
+                    }
+                ));
+            }
+            termination = TerminationReason::Converged;
+            break;
+        }
 
-            if f_trial < f_x {
-                x = x_trial;
-                f_x = f_trial;
-                step = (step_increase * trial_step).min(1.0);
-                found_step = true;
+        if params.detect_flat {
+            let floor = fd_noise_floor(f, &x, f_x, params.fd_eps);
+            if g_norm <= floor {
+                if params.verbose {
+                    log.push(format!(
+                        "итер. {}: ||g|| = {:.3e} не превышает уровень шума {:.3e}, плоская область",
+                        iter + 1,
+                        g_norm,
+                        floor
+                    ));
+                }
+                termination = TerminationReason::FlatRegion;
                 break;
-            } else {
-                trial_step *= step_decay;
             }
         }
 
-        if !found_step {
+        // Вдоль активных границ шаг не делается, иначе проекция возвращала бы
+        // точку на место и поиск шага завершался бы неудачей
+        let clipped = params.max_grad_norm > 0.0 && pg.norm() > params.max_grad_norm;
+        let direction = if clipped {
+            -&pg * (params.max_grad_norm / pg.norm())
+        } else {
+            -&pg
+        };
+        let slope = g.dot(&direction);
+
+        if restart_period > 0 && iter >= next_restart {
+            line_search.reset();
+            if params.restart_doubling {
+                restart_period *= 2;
+            }
+            next_restart = iter + restart_period;
+            if params.verbose {
+                log.push(format!(
+                    "итер. {}: тёплый рестарт, шаг сброшен к {:.3e}",
+                    iter + 1,
+                    params.initial_step
+                ));
+            }
+        }
+
+        let Some(accepted) = search_step(line_search, f, &x, f_x, &direction, slope, params) else {
+            if params.verbose {
+                log.push(format!(
+                    "итер. {}: ||g|| = {:.3e}, шаг не найден, остановка",
+                    iter + 1,
+                    g_norm
+                ));
+            }
+            termination = TerminationReason::LineSearchFailed;
             break;
+        };
+        let step_norm = (&accepted.x - &x).norm();
+        step_sizes.push(step_norm);
+        let f_prev = f_x;
+        x = accepted.x;
+        f_x = accepted.f_x;
+
+        if params.verbose {
+            log.push(format!(
+                "итер. {}: ||g|| = {:.3e}, шаг = {:.3e}, вычислений f: {}, f = {:.6e}{}",
+                iter + 1,
+                g_norm,
+                accepted.step,
+                accepted.evaluations,
+                f_x,
+                accepted
+                    .note
+                    .into_iter()
+                    .chain(clipped.then_some("градиент укорочен до max_grad_norm"))
+                    .map(|note| format!(", {}", note))
+                    .collect::<String>()
+            ));
         }
 
         iter += 1;
-        history.push((x[0], x[1], f_x));
+        history.push((x.clone(), f_x));
+        params.report(iter, &x, f_x);
+
+        let f_change = (f_prev - f_x).abs() / (f_prev.abs() + f64::EPSILON);
+        if params.ftol > 0.0 && f_change < params.ftol {
+            if params.verbose {
+                log.push(format!(
+                    "итер. {}: относительное изменение f {:.3e} < {:.1e}, остановка",
+                    iter, f_change, params.ftol
+                ));
+            }
+            termination = TerminationReason::FunctionTolerance;
+            break;
+        }
+        if params.xtol > 0.0 && step_norm < params.xtol {
+            if params.verbose {
+                log.push(format!(
+                    "итер. {}: длина шага {:.3e} < {:.1e}, остановка",
+                    iter, step_norm, params.xtol
+                ));
+            }
+            termination = TerminationReason::StepTolerance;
+            break;
+        }
+    }
+
+    // После выхода по числу итераций градиент в последней точке ещё не вычислен
+    if grad_norms.len() < history.len() {
+        grad_norms.push(grad(&x).norm());
+    }
+
+    if let Some((best_x, best_f)) = best_before_perturb {
+        if best_f < f_x {
+            if params.verbose {
+                log.push(format!(
+                    "возмущения не улучшили результат, возврат к точке с f = {:.6e}",
+                    best_f
+                ));
+            }
+            x = best_x;
+            f_x = best_f;
+        }
     }
 
     OptimizerResult {
@@ -84,6 +956,3923 @@ pub fn gradient_descent(
         f_x,
         iterations: iter,
         history,
-        terminated_early: false,
+        termination,
+        log,
+        trust_radii: Vec::new(),
+        grad_norms,
+        step_sizes,
+        perturbations,
+        history_iterations: Vec::new(),
+        elapsed: counter.started.elapsed(),
+        f_evals: counter.f_evals.get(),
+        grad_evals: counter.grad_evals.get(),
+    }
+    .revert_non_finite()
+    .thin_history(params.max_history_points)
+}
+
+// Наискорейший спуск: шаг вдоль -g ищется золотым сечением на [0, step_max].
+// Для квадратичной функции это приближает точный шаг (g·g)/(g·H·g), поэтому
+// одномерный поиск ведётся с точностью много выше обычной
+pub fn steepest_descent_exact(
+    initial_point: DVector<f64>,
+    f: &ObjectiveFn,
+    grad: &GradientFn,
+    params: &DescentParams,
+    step_max: f64,
+    stop_flag: Arc<AtomicBool>,
+) -> OptimizerResult {
+    let line_search = GoldenSection {
+        initial_step: params.initial_step,
+        tol: 1e-10,
+        max_step: step_max,
+    };
+    gradient_descent(initial_point, f, grad, params, &line_search, stop_flag)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PenaltyParams {
+    pub initial_mu: f64,
+    // Множитель μ между внешними итерациями
+    pub growth: f64,
+    pub outer_iterations: usize,
+    // Внешние итерации прекращаются, когда max |g_i(x)| меньше этого порога
+    pub tolerance: f64,
+}
+
+impl Default for PenaltyParams {
+    fn default() -> Self {
+        Self {
+            initial_mu: 1.0,
+            growth: 10.0,
+            outer_iterations: 8,
+            tolerance: 1e-4,
+        }
+    }
+}
+
+pub struct PenaltyResult {
+    pub result: OptimizerResult,
+    // Значения g_i в итоговой точке
+    pub violations: Vec<f64>,
+}
+
+// Метод внешних штрафов для ограничений g_i(x) = 0: градиентным спуском
+// минимизируется f(x) + μ·Σ g_i(x)², μ растёт от итерации к итерации, каждая
+// задача начинается из решения предыдущей. История хранит значения исходной f
+#[allow(clippy::too_many_arguments)]
+pub fn penalty_minimize(
+    initial_point: DVector<f64>,
+    f: &ObjectiveFn,
+    grad: &GradientFn,
+    constraints: &[(&ObjectiveFn, &GradientFn)],
+    params: &DescentParams,
+    line_search: &dyn LineSearch,
+    pp: &PenaltyParams,
+    stop_flag: Arc<AtomicBool>,
+) -> PenaltyResult {
+    let violations =
+        |x: &DVector<f64>| -> Vec<f64> { constraints.iter().map(|(g, _)| g(x)).collect() };
+    let mut mu = pp.initial_mu;
+    let mut total: Option<OptimizerResult> = None;
+    let mut x = initial_point;
+
+    for k in 0..pp.outer_iterations.max(1) {
+        let penalized =
+            |y: &DVector<f64>| f(y) + mu * violations(y).iter().map(|v| v * v).sum::<f64>();
+        let penalized_grad = |y: &DVector<f64>| {
+            let mut g = grad(y);
+            for (value, gradient) in constraints {
+                g += 2.0 * mu * value(y) * gradient(y);
+            }
+            g
+        };
+        let inner = gradient_descent(
+            x.clone(),
+            &penalized,
+            &penalized_grad,
+            params,
+            line_search,
+            stop_flag.clone(),
+        );
+        x = inner.x.clone();
+        let max_violation = violations(&x).iter().fold(0.0_f64, |m, v| m.max(v.abs()));
+
+        total = Some(match total {
+            None => inner,
+            Some(mut acc) => {
+                acc.append(inner);
+                acc
+            }
+        });
+        if params.verbose {
+            if let Some(acc) = &mut total {
+                acc.log.push(format!(
+                    "внешняя итер. {}: μ = {:.1e}, max |g| = {:.3e}",
+                    k + 1,
+                    mu,
+                    max_violation
+                ));
+            }
+        }
+
+        let stopped = total
+            .as_ref()
+            .is_some_and(|acc| acc.termination == TerminationReason::UserStopped);
+        if stopped || max_violation < pp.tolerance || !max_violation.is_finite() {
+            break;
+        }
+        mu *= pp.growth;
+    }
+
+    let mut result = total.expect("хотя бы одна внешняя итерация");
+    for (point, f_point) in &mut result.history {
+        *f_point = f(point);
+    }
+    result.f_x = f(&result.x);
+    PenaltyResult {
+        violations: violations(&result.x),
+        result,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MomentumParams {
+    // Коэффициент инерции μ из [0, 0.99]
+    pub momentum: f64,
+    // Градиент вычисляется в точке x + μ·v (ускорение Нестерова)
+    pub nesterov: bool,
+}
+
+impl Default for MomentumParams {
+    fn default() -> Self {
+        Self {
+            momentum: 0.9,
+            nesterov: false,
+        }
+    }
+}
+
+// Спуск с инерцией: v = μ·v - шаг·g, x += v. Шаг постоянный (initial_step),
+// дробление не выполняется: рост f на отдельных итерациях для метода нормален.
+pub fn momentum_descent(
+    initial_point: DVector<f64>,
+    f: &ObjectiveFn,
+    grad: &GradientFn,
+    params: &DescentParams,
+    mp: &MomentumParams,
+    stop_flag: Arc<AtomicBool>,
+) -> OptimizerResult {
+    let counter = EvalCounter::new();
+    let f = &counter.objective(f);
+    let grad = &counter.gradient(grad);
+    let mut x = initial_point;
+    if let Some(bounds) = &params.bounds {
+        bounds.project(&mut x);
+    }
+    let mut f_x = f(&x);
+    let mut iter = 0;
+    let step = params.initial_step;
+    let mut v = DVector::zeros(x.len());
+
+    let mut history = Vec::new();
+    history.push((x.clone(), f_x));
+    let mut termination = TerminationReason::MaxIterations;
+
+    let mut log = Vec::new();
+    if params.verbose {
+        log.push(format!(
+            "старт: f(x0) = {:.6e}, шаг = {:.3e}, μ = {:.2}{}",
+            f_x,
+            step,
+            mp.momentum,
+            if mp.nesterov {
+                ", Нестеров"
+            } else {
+                ""
+            }
+        ));
+    }
+
+    while iter < params.max_iterations {
+        if stop_flag.load(Ordering::SeqCst) {
+            termination = TerminationReason::UserStopped;
+            break;
+        }
+
+        if !f_x.is_finite() {
+            if params.verbose {
+                log.push(format!(
+                    "итер. {}: f = {}, точка вне области определения, остановка",
+                    iter + 1,
+                    f_x
+                ));
+            }
+            termination = TerminationReason::NonFiniteValue;
+            break;
+        }
+
+        let g = grad(&x);
+        if g.iter().any(|v| !v.is_finite()) {
+            termination = TerminationReason::GradientNaN;
+            break;
+        }
+        let g_norm = stationarity_gradient(params, &x, &g).norm();
+        if g_norm < params.tolerance {
+            if params.verbose {
+                log.push(format!(
+                    "итер. {}: ||g|| = {:.3e} < {:.1e}, сходимость",
+                    iter + 1,
+                    g_norm,
+                    params.tolerance
+                ));
+            }
+            termination = TerminationReason::Converged;
+            break;
+        }
+
+        let g_step = if mp.nesterov {
+            grad(&(&x + mp.momentum * &v))
+        } else {
+            g
+        };
+        v = mp.momentum * v - step * g_step;
+
+        let x_prev = x.clone();
+        x += &v;
+        if let Some(bounds) = &params.bounds {
+            bounds.project(&mut x);
+            // Скорость согласуется с фактическим перемещением после проекции
+            v = &x - &x_prev;
+        }
+        wrap_periodic(&mut x, &params.periodic);
+        f_x = f(&x);
+
+        if params.verbose {
+            log.push(format!(
+                "итер. {}: ||g|| = {:.3e}, ||v|| = {:.3e}, f = {:.6e}",
+                iter + 1,
+                g_norm,
+                v.norm(),
+                f_x
+            ));
+        }
+
+        iter += 1;
+        history.push((x.clone(), f_x));
+        params.report(iter, &x, f_x);
+    }
+
+    OptimizerResult {
+        x,
+        f_x,
+        iterations: iter,
+        history,
+        termination,
+        log,
+        trust_radii: Vec::new(),
+        grad_norms: Vec::new(),
+        step_sizes: Vec::new(),
+        perturbations: Vec::new(),
+        history_iterations: Vec::new(),
+        elapsed: counter.started.elapsed(),
+        f_evals: counter.f_evals.get(),
+        grad_evals: counter.grad_evals.get(),
+    }
+    .revert_non_finite()
+    .thin_history(params.max_history_points)
+}
+
+// Формула шага Барзилая–Борвейна по s = x_k - x_{k-1}, y = g_k - g_{k-1}
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum BbVariant {
+    // (s·s) / (s·y)
+    Bb1,
+    // (s·y) / (y·y)
+    Bb2,
+}
+
+// Градиентный спуск с шагом Барзилая–Борвейна. Поиск вдоль направления не
+// выполняется, поэтому f может немонотонно расти. На первой итерации и при
+// s·y, близком к нулю или отрицательном (кривизна вдоль s не положительна),
+// берётся initial_step.
+pub fn bb_gradient_descent(
+    initial_point: DVector<f64>,
+    f: &ObjectiveFn,
+    grad: &GradientFn,
+    params: &DescentParams,
+    variant: BbVariant,
+    stop_flag: Arc<AtomicBool>,
+) -> OptimizerResult {
+    let counter = EvalCounter::new();
+    let f = &counter.objective(f);
+    let grad = &counter.gradient(grad);
+    let mut x = initial_point;
+    if let Some(bounds) = &params.bounds {
+        bounds.project(&mut x);
+    }
+    let mut f_x = f(&x);
+    let mut iter = 0;
+    let mut g = grad(&x);
+    let mut previous: Option<(DVector<f64>, DVector<f64>)> = None;
+
+    let mut history = Vec::new();
+    history.push((x.clone(), f_x));
+    let mut grad_norms = Vec::new();
+    let mut step_sizes = Vec::new();
+    let mut termination = TerminationReason::MaxIterations;
+
+    let mut log = Vec::new();
+    if params.verbose {
+        log.push(format!(
+            "старт: f(x0) = {:.6e}, шаг = {:.3e}, {:?}",
+            f_x, params.initial_step, variant
+        ));
+    }
+
+    while iter < params.max_iterations {
+        if stop_flag.load(Ordering::SeqCst) {
+            termination = TerminationReason::UserStopped;
+            break;
+        }
+
+        if !f_x.is_finite() {
+            if params.verbose {
+                log.push(format!(
+                    "итер. {}: f = {}, точка вне области определения, остановка",
+                    iter + 1,
+                    f_x
+                ));
+            }
+            termination = TerminationReason::NonFiniteValue;
+            break;
+        }
+
+        if g.iter().any(|v| !v.is_finite()) {
+            termination = TerminationReason::GradientNaN;
+            break;
+        }
+        let g_norm = stationarity_gradient(params, &x, &g).norm();
+        grad_norms.push(g_norm);
+        if g_norm < params.tolerance {
+            if params.verbose {
+                log.push(format!(
+                    "итер. {}: ||g|| = {:.3e} < {:.1e}, сходимость",
+                    iter + 1,
+                    g_norm,
+                    params.tolerance
+                ));
+            }
+            termination = TerminationReason::Converged;
+            break;
+        }
+
+        let mut step = params.initial_step;
+        if let Some((x_prev, g_prev)) = &previous {
+            let s = &x - x_prev;
+            let y = &g - g_prev;
+            let sy = s.dot(&y);
+            if sy > f64::EPSILON * s.norm() * y.norm() {
+                step = match variant {
+                    BbVariant::Bb1 => s.dot(&s) / sy,
+                    BbVariant::Bb2 => sy / y.dot(&y),
+                };
+            } else if params.verbose {
+                log.push(format!(
+                    "итер. {}: s·y = {:.3e}, используется начальный шаг",
+                    iter + 1,
+                    sy
+                ));
+            }
+        }
+
+        let x_prev = x.clone();
+        x -= step * &g;
+        if let Some(bounds) = &params.bounds {
+            bounds.project(&mut x);
+        }
+        wrap_periodic(&mut x, &params.periodic);
+        f_x = f(&x);
+        step_sizes.push((&x - &x_prev).norm());
+
+        if params.verbose {
+            log.push(format!(
+                "итер. {}: ||g|| = {:.3e}, шаг = {:.3e}, f = {:.6e}",
+                iter + 1,
+                g_norm,
+                step,
+                f_x
+            ));
+        }
+
+        previous = Some((x_prev, g));
+        g = grad(&x);
+        iter += 1;
+        history.push((x.clone(), f_x));
+        params.report(iter, &x, f_x);
+    }
+
+    OptimizerResult {
+        x,
+        f_x,
+        iterations: iter,
+        history,
+        termination,
+        log,
+        trust_radii: Vec::new(),
+        grad_norms,
+        step_sizes,
+        perturbations: Vec::new(),
+        history_iterations: Vec::new(),
+        elapsed: counter.started.elapsed(),
+        f_evals: counter.f_evals.get(),
+        grad_evals: counter.grad_evals.get(),
+    }
+    .revert_non_finite()
+    .thin_history(params.max_history_points)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdamParams {
+    pub learning_rate: f64,
+    pub beta1: f64,
+    pub beta2: f64,
+    pub epsilon: f64,
+}
+
+impl Default for AdamParams {
+    fn default() -> Self {
+        Self {
+            learning_rate: 0.1,
+            beta1: 0.9,
+            beta2: 0.999,
+            epsilon: 1e-8,
+        }
+    }
+}
+
+// Adam (Kingma, Ba): скользящие оценки первого и второго моментов градиента
+// с поправкой на смещение m̂ = m / (1 - β1^t), v̂ = v / (1 - β2^t)
+pub fn adam(
+    initial_point: DVector<f64>,
+    f: &ObjectiveFn,
+    grad: &GradientFn,
+    params: &DescentParams,
+    ap: &AdamParams,
+    stop_flag: Arc<AtomicBool>,
+) -> OptimizerResult {
+    let counter = EvalCounter::new();
+    let f = &counter.objective(f);
+    let grad = &counter.gradient(grad);
+    let mut x = initial_point;
+    if let Some(bounds) = &params.bounds {
+        bounds.project(&mut x);
+    }
+    let mut f_x = f(&x);
+    let mut iter = 0;
+    let n = x.len();
+    let mut m = DVector::zeros(n);
+    let mut v = DVector::zeros(n);
+
+    let mut history = Vec::new();
+    history.push((x.clone(), f_x));
+    let mut termination = TerminationReason::MaxIterations;
+
+    let mut log = Vec::new();
+    if params.verbose {
+        log.push(format!(
+            "старт: f(x0) = {:.6e}, скорость обучения = {:.3e}",
+            f_x, ap.learning_rate
+        ));
+    }
+
+    while iter < params.max_iterations {
+        if stop_flag.load(Ordering::SeqCst) {
+            termination = TerminationReason::UserStopped;
+            break;
+        }
+
+        if !f_x.is_finite() {
+            if params.verbose {
+                log.push(format!(
+                    "итер. {}: f = {}, точка вне области определения, остановка",
+                    iter + 1,
+                    f_x
+                ));
+            }
+            termination = TerminationReason::NonFiniteValue;
+            break;
+        }
+
+        let g = grad(&x);
+        if g.iter().any(|v| !v.is_finite()) {
+            termination = TerminationReason::GradientNaN;
+            break;
+        }
+        let g_norm = stationarity_gradient(params, &x, &g).norm();
+        if g_norm < params.tolerance {
+            if params.verbose {
+                log.push(format!(
+                    "итер. {}: ||g|| = {:.3e} < {:.1e}, сходимость",
+                    iter + 1,
+                    g_norm,
+                    params.tolerance
+                ));
+            }
+            termination = TerminationReason::Converged;
+            break;
+        }
+
+        let t = (iter + 1) as i32;
+        m = ap.beta1 * m + (1.0 - ap.beta1) * &g;
+        v = ap.beta2 * v + (1.0 - ap.beta2) * g.component_mul(&g);
+        let m_hat = &m / (1.0 - ap.beta1.powi(t));
+        let v_hat = &v / (1.0 - ap.beta2.powi(t));
+
+        let update = m_hat.zip_map(&v_hat, |mh, vh| {
+            ap.learning_rate * mh / (vh.sqrt() + ap.epsilon)
+        });
+        x -= &update;
+        if let Some(bounds) = &params.bounds {
+            bounds.project(&mut x);
+        }
+        wrap_periodic(&mut x, &params.periodic);
+        f_x = f(&x);
+
+        if params.verbose {
+            log.push(format!(
+                "итер. {}: ||g|| = {:.3e}, ||Δx|| = {:.3e}, f = {:.6e}",
+                iter + 1,
+                g_norm,
+                update.norm(),
+                f_x
+            ));
+        }
+
+        iter += 1;
+        history.push((x.clone(), f_x));
+        params.report(iter, &x, f_x);
+    }
+
+    OptimizerResult {
+        x,
+        f_x,
+        iterations: iter,
+        history,
+        termination,
+        log,
+        trust_radii: Vec::new(),
+        grad_norms: Vec::new(),
+        step_sizes: Vec::new(),
+        perturbations: Vec::new(),
+        history_iterations: Vec::new(),
+        elapsed: counter.started.elapsed(),
+        f_evals: counter.f_evals.get(),
+        grad_evals: counter.grad_evals.get(),
+    }
+    .revert_non_finite()
+    .thin_history(params.max_history_points)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RmsPropParams {
+    pub learning_rate: f64,
+    // Коэффициент затухания среднего квадратов градиента
+    pub rho: f64,
+    pub epsilon: f64,
+}
+
+impl Default for RmsPropParams {
+    fn default() -> Self {
+        Self {
+            learning_rate: 0.01,
+            rho: 0.9,
+            epsilon: 1e-8,
+        }
+    }
+}
+
+// RMSProp: s = ρ·s + (1-ρ)·g², x -= lr·g / (√s + ε) покоординатно, так что
+// координаты с разным масштабом градиента сдвигаются на сопоставимые шаги
+pub fn rmsprop(
+    initial_point: DVector<f64>,
+    f: &ObjectiveFn,
+    grad: &GradientFn,
+    params: &DescentParams,
+    rp: &RmsPropParams,
+    stop_flag: Arc<AtomicBool>,
+) -> OptimizerResult {
+    let counter = EvalCounter::new();
+    let f = &counter.objective(f);
+    let grad = &counter.gradient(grad);
+    let mut x = initial_point;
+    if let Some(bounds) = &params.bounds {
+        bounds.project(&mut x);
+    }
+    let mut f_x = f(&x);
+    let mut iter = 0;
+    let mut s = DVector::zeros(x.len());
+
+    let mut history = Vec::new();
+    history.push((x.clone(), f_x));
+    let mut termination = TerminationReason::MaxIterations;
+
+    let mut log = Vec::new();
+    if params.verbose {
+        log.push(format!(
+            "старт: f(x0) = {:.6e}, скорость обучения = {:.3e}, ρ = {:.3}",
+            f_x, rp.learning_rate, rp.rho
+        ));
+    }
+
+    while iter < params.max_iterations {
+        if stop_flag.load(Ordering::SeqCst) {
+            termination = TerminationReason::UserStopped;
+            break;
+        }
+
+        if !f_x.is_finite() {
+            if params.verbose {
+                log.push(format!(
+                    "итер. {}: f = {}, точка вне области определения, остановка",
+                    iter + 1,
+                    f_x
+                ));
+            }
+            termination = TerminationReason::NonFiniteValue;
+            break;
+        }
+
+        let g = grad(&x);
+        if g.iter().any(|v| !v.is_finite()) {
+            termination = TerminationReason::GradientNaN;
+            break;
+        }
+        let g_norm = stationarity_gradient(params, &x, &g).norm();
+        if g_norm < params.tolerance {
+            if params.verbose {
+                log.push(format!(
+                    "итер. {}: ||g|| = {:.3e} < {:.1e}, сходимость",
+                    iter + 1,
+                    g_norm,
+                    params.tolerance
+                ));
+            }
+            termination = TerminationReason::Converged;
+            break;
+        }
+
+        s = rp.rho * s + (1.0 - rp.rho) * g.component_mul(&g);
+        let update = g.zip_map(&s, |gi, si| {
+            rp.learning_rate * gi / (si.sqrt() + rp.epsilon)
+        });
+        x -= &update;
+        if let Some(bounds) = &params.bounds {
+            bounds.project(&mut x);
+        }
+        wrap_periodic(&mut x, &params.periodic);
+        f_x = f(&x);
+
+        if params.verbose {
+            log.push(format!(
+                "итер. {}: ||g|| = {:.3e}, ||Δx|| = {:.3e}, f = {:.6e}",
+                iter + 1,
+                g_norm,
+                update.norm(),
+                f_x
+            ));
+        }
+
+        iter += 1;
+        history.push((x.clone(), f_x));
+        params.report(iter, &x, f_x);
+    }
+
+    OptimizerResult {
+        x,
+        f_x,
+        iterations: iter,
+        history,
+        termination,
+        log,
+        trust_radii: Vec::new(),
+        grad_norms: Vec::new(),
+        step_sizes: Vec::new(),
+        perturbations: Vec::new(),
+        history_iterations: Vec::new(),
+        elapsed: counter.started.elapsed(),
+        f_evals: counter.f_evals.get(),
+        grad_evals: counter.grad_evals.get(),
+    }
+    .revert_non_finite()
+    .thin_history(params.max_history_points)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NelderMeadParams {
+    // Коэффициенты отражения, растяжения, сжатия и редукции
+    pub alpha: f64,
+    pub gamma: f64,
+    pub rho: f64,
+    pub sigma: f64,
+    // Смещение по каждой координате при построении начального симплекса
+    pub simplex_step: f64,
+}
+
+impl Default for NelderMeadParams {
+    fn default() -> Self {
+        Self {
+            alpha: 1.0,
+            gamma: 2.0,
+            rho: 0.5,
+            sigma: 0.5,
+            simplex_step: 1.0,
+        }
+    }
+}
+
+// Симплекс-метод Нелдера–Мида без производных. Останов — когда и разброс
+// значений f, и размер симплекса меньше tolerance. В историю записывается
+// лучшая вершина каждой итерации.
+pub fn nelder_mead(
+    initial_point: DVector<f64>,
+    f: &ObjectiveFn,
+    params: &DescentParams,
+    nm: &NelderMeadParams,
+    stop_flag: Arc<AtomicBool>,
+) -> OptimizerResult {
+    let counter = EvalCounter::new();
+    let f = &counter.objective(f);
+    let n = initial_point.len();
+    let mut simplex: Vec<(DVector<f64>, f64)> = Vec::with_capacity(n + 1);
+    simplex.push((initial_point.clone(), f(&initial_point)));
+    for i in 0..n {
+        let mut vertex = initial_point.clone();
+        vertex[i] += nm.simplex_step;
+        let f_v = f(&vertex);
+        simplex.push((vertex, f_v));
+    }
+    let by_value = |a: &(DVector<f64>, f64), b: &(DVector<f64>, f64)| a.1.total_cmp(&b.1);
+    simplex.sort_by(by_value);
+
+    let mut iter = 0;
+    let mut history = vec![simplex[0].clone()];
+    let mut termination = TerminationReason::MaxIterations;
+
+    let mut log = Vec::new();
+    if params.verbose {
+        log.push(format!("старт: f(x0) = {:.6e}", simplex[0].1));
+    }
+
+    while iter < params.max_iterations {
+        if stop_flag.load(Ordering::SeqCst) {
+            termination = TerminationReason::UserStopped;
+            break;
+        }
+
+        let best = &simplex[0];
+        let f_spread = simplex[n].1 - best.1;
+        let size = simplex[1..]
+            .iter()
+            .map(|(v, _)| (v - &best.0).norm())
+            .fold(0.0, f64::max);
+        if f_spread < params.tolerance && size < params.tolerance {
+            if params.verbose {
+                log.push(format!(
+                    "итер. {}: разброс f = {:.3e}, размер симплекса = {:.3e}, сходимость",
+                    iter + 1,
+                    f_spread,
+                    size
+                ));
+            }
+            termination = TerminationReason::Converged;
+            break;
+        }
+
+        // Центр тяжести всех вершин, кроме худшей
+        let centroid = simplex[..n]
+            .iter()
+            .fold(DVector::zeros(n), |acc, (v, _)| acc + v)
+            / n as f64;
+        let worst = simplex[n].clone();
+
+        let reflected = &centroid + nm.alpha * (&centroid - &worst.0);
+        let f_r = f(&reflected);
+
+        let operation = if f_r < simplex[0].1 {
+            let expanded = &centroid + nm.gamma * (&reflected - &centroid);
+            let f_e = f(&expanded);
+            if f_e < f_r {
+                simplex[n] = (expanded, f_e);
+                "растяжение"
+            } else {
+                simplex[n] = (reflected, f_r);
+                "отражение"
+            }
+        } else if f_r < simplex[n - 1].1 {
+            simplex[n] = (reflected, f_r);
+            "отражение"
+        } else {
+            // Сжатие к лучшей из точек: отражённой или худшей вершине
+            let (toward, f_toward) = if f_r < worst.1 {
+                (&reflected, f_r)
+            } else {
+                (&worst.0, worst.1)
+            };
+            let contracted = &centroid + nm.rho * (toward - &centroid);
+            let f_c = f(&contracted);
+            if f_c < f_toward {
+                simplex[n] = (contracted, f_c);
+                "сжатие"
+            } else {
+                let best = simplex[0].0.clone();
+                for vertex in simplex.iter_mut().skip(1) {
+                    vertex.0 = &best + nm.sigma * (&vertex.0 - &best);
+                    vertex.1 = f(&vertex.0);
+                }
+                "редукция"
+            }
+        };
+        simplex.sort_by(by_value);
+
+        if params.verbose {
+            log.push(format!(
+                "итер. {}: {}, f лучшей вершины = {:.6e}, разброс f = {:.3e}",
+                iter + 1,
+                operation,
+                simplex[0].1,
+                simplex[n].1 - simplex[0].1
+            ));
+        }
+
+        iter += 1;
+        history.push(simplex[0].clone());
+        params.report(iter, &simplex[0].0, simplex[0].1);
+    }
+
+    let (x, f_x) = simplex.swap_remove(0);
+    OptimizerResult {
+        x,
+        f_x,
+        iterations: iter,
+        history,
+        termination,
+        log,
+        trust_radii: Vec::new(),
+        grad_norms: Vec::new(),
+        step_sizes: Vec::new(),
+        perturbations: Vec::new(),
+        history_iterations: Vec::new(),
+        elapsed: counter.started.elapsed(),
+        f_evals: counter.f_evals.get(),
+        grad_evals: counter.grad_evals.get(),
+    }
+    .thin_history(params.max_history_points)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnnealingParams {
+    pub initial_temperature: f64,
+    // Множитель температуры на каждой итерации
+    pub cooling: f64,
+    // Стандартное отклонение шага при начальной температуре
+    pub step: f64,
+    pub min_temperature: f64,
+    pub seed: u64,
+}
+
+impl Default for AnnealingParams {
+    fn default() -> Self {
+        Self {
+            initial_temperature: 1.0,
+            cooling: 0.99,
+            step: 0.5,
+            min_temperature: 1e-6,
+            seed: 42,
+        }
+    }
+}
+
+// Имитация отжига: гауссово смещение с σ = step·√(T/T0), ухудшение на Δf
+// принимается с вероятностью exp(-Δf/T), температура убывает геометрически.
+// Останов — при T < min_temperature. В историю пишется лучшая найденная точка
+// при каждом её улучшении.
+pub fn simulated_annealing(
+    initial_point: DVector<f64>,
+    f: &ObjectiveFn,
+    params: &DescentParams,
+    sa: &AnnealingParams,
+    stop_flag: Arc<AtomicBool>,
+) -> OptimizerResult {
+    let counter = EvalCounter::new();
+    let f = &counter.objective(f);
+    let mut rng = StdRng::seed_from_u64(sa.seed);
+    let mut x = initial_point;
+    if let Some(bounds) = &params.bounds {
+        bounds.project(&mut x);
+    }
+    let mut f_x = f(&x);
+    let mut best = (x.clone(), f_x);
+    let mut temperature = sa.initial_temperature;
+    let mut iter = 0;
+
+    let mut history = Vec::new();
+    history.push(best.clone());
+    let mut termination = TerminationReason::MaxIterations;
+
+    let mut log = Vec::new();
+    if params.verbose {
+        log.push(format!(
+            "старт: f(x0) = {:.6e}, T0 = {:.3e}",
+            f_x, sa.initial_temperature
+        ));
+    }
+
+    while iter < params.max_iterations {
+        if stop_flag.load(Ordering::SeqCst) {
+            termination = TerminationReason::UserStopped;
+            break;
+        }
+        if !f_x.is_finite() {
+            termination = TerminationReason::NonFiniteValue;
+            break;
+        }
+        if temperature < sa.min_temperature {
+            if params.verbose {
+                log.push(format!(
+                    "итер. {}: T = {:.3e} < {:.1e}, остановка",
+                    iter + 1,
+                    temperature,
+                    sa.min_temperature
+                ));
+            }
+            termination = TerminationReason::Converged;
+            break;
+        }
+
+        let sigma = sa.step * (temperature / sa.initial_temperature).sqrt();
+        let mut candidate =
+            &x + DVector::from_fn(x.len(), |_, _| sigma * rng.sample::<f64, _>(StandardNormal));
+        if let Some(bounds) = &params.bounds {
+            bounds.project(&mut candidate);
+        }
+        wrap_periodic(&mut candidate, &params.periodic);
+        let f_c = f(&candidate);
+
+        // Точки вне области определения всегда отклоняются
+        let accepted = f_c.is_finite()
+            && (f_c <= f_x || rng.gen::<f64>() < (-(f_c - f_x) / temperature).exp());
+        if accepted {
+            x = candidate;
+            f_x = f_c;
+        }
+        if f_x < best.1 {
+            best = (x.clone(), f_x);
+            history.push(best.clone());
+        }
+
+        if params.verbose {
+            log.push(format!(
+                "итер. {}: T = {:.3e}, f кандидата = {:.6e}, {}, лучшее f = {:.6e}",
+                iter + 1,
+                temperature,
+                f_c,
+                if accepted {
+                    "принят"
+                } else {
+                    "отклонён"
+                },
+                best.1
+            ));
+        }
+
+        temperature *= sa.cooling;
+        iter += 1;
+        params.report(iter, &best.0, best.1);
+    }
+
+    let (x, f_x) = best;
+    OptimizerResult {
+        x,
+        f_x,
+        iterations: iter,
+        history,
+        termination,
+        log,
+        trust_radii: Vec::new(),
+        grad_norms: Vec::new(),
+        step_sizes: Vec::new(),
+        perturbations: Vec::new(),
+        history_iterations: Vec::new(),
+        elapsed: counter.started.elapsed(),
+        f_evals: counter.f_evals.get(),
+        grad_evals: counter.grad_evals.get(),
+    }
+    .thin_history(params.max_history_points)
+}
+
+// Выход из области определения или NaN/∞ в градиенте останавливают метод
+fn non_finite_termination(
+    f_x: f64,
+    g: &DVector<f64>,
+    iter: usize,
+    params: &DescentParams,
+    log: &mut Vec<String>,
+) -> Option<TerminationReason> {
+    let reason = if !f_x.is_finite() {
+        if params.verbose {
+            log.push(format!(
+                "итер. {}: f = {}, точка вне области определения, остановка",
+                iter + 1,
+                f_x
+            ));
+        }
+        TerminationReason::NonFiniteValue
+    } else if !g.iter().all(|v| v.is_finite()) {
+        if params.verbose {
+            log.push(format!(
+                "итер. {}: градиент содержит NaN или бесконечность, остановка",
+                iter + 1
+            ));
+        }
+        TerminationReason::GradientNaN
+    } else {
+        return None;
+    };
+    Some(reason)
+}
+
+// Метод сопряжённых градиентов Полака–Рибьера с рестартом: beta обнуляется
+// каждые n итераций, при beta < 0 и если направление перестаёт быть направлением
+// спуска. При заданном powell_threshold добавляется тест Пауэлла: рестарт, если
+// |g_{k+1}·g_k| / (g_k·g_k) >= порога, т. е. соседние градиенты далеки от
+// ортогональности. Шаг подбирается тем же адаптивным дроблением, что и в
+// градиентном спуске.
+pub fn conjugate_gradient(
+    initial_point: DVector<f64>,
+    f: &ObjectiveFn,
+    grad: &GradientFn,
+    params: &DescentParams,
+    powell_threshold: Option<f64>,
+    stop_flag: Arc<AtomicBool>,
+) -> OptimizerResult {
+    let counter = EvalCounter::new();
+    let f = &counter.objective(f);
+    let grad = &counter.gradient(grad);
+    let mut x = initial_point;
+    let mut f_x = f(&x);
+    let mut iter = 0;
+    let line_search = AdaptiveHalving::new(
+        params.initial_step,
+        params.step_decay,
+        params.step_increase,
+        params.max_step,
+    )
+    .with_max_backtracks(params.max_backtracks);
+    let n = x.len();
+
+    let mut history = Vec::new();
+    history.push((x.clone(), f_x));
+    let mut termination = TerminationReason::MaxIterations;
+
+    let mut log = Vec::new();
+    if params.verbose {
+        log.push(format!(
+            "старт: f(x0) = {:.6e}, шаг = {:.3e}",
+            f_x, params.initial_step
+        ));
+    }
+
+    let mut g = grad(&x);
+    let mut direction = -&g;
+
+    while iter < params.max_iterations {
+        if stop_flag.load(Ordering::SeqCst) {
+            termination = TerminationReason::UserStopped;
+            break;
+        }
+
+        if let Some(reason) = non_finite_termination(f_x, &g, iter, params, &mut log) {
+            termination = reason;
+            break;
+        }
+
+        let g_norm = g.norm();
+        if g_norm < params.tolerance {
+            if params.verbose {
+                log.push(format!(
+                    "итер. {}: ||g|| = {:.3e} < {:.1e}, сходимость",
+                    iter + 1,
+                    g_norm,
+                    params.tolerance
+                ));
+            }
+            termination = TerminationReason::Converged;
+            break;
+        }
+
+        let slope = g.dot(&direction);
+        let (trial_step, backtracks) =
+            match search_step(&line_search, f, &x, f_x, &direction, slope, params) {
+                Some(accepted) => {
+                    x = accepted.x;
+                    f_x = accepted.f_x;
+                    (accepted.step, accepted.evaluations - 1)
+                }
+                None => {
+                    if params.verbose {
+                        log.push(format!(
+                            "итер. {}: ||g|| = {:.3e}, шаг не найден за {} дроблений, остановка",
+                            iter + 1,
+                            g_norm,
+                            params.max_backtracks
+                        ));
+                    }
+                    termination = TerminationReason::LineSearchFailed;
+                    break;
+                }
+            };
+
+        let g_next = grad(&x);
+        let g_sq = g.dot(&g);
+        let mut beta = g_next.dot(&(&g_next - &g)) / g_sq;
+        let powell = powell_threshold.is_some_and(|t| g_next.dot(&g).abs() / g_sq >= t);
+        let restart = (iter + 1) % n == 0 || beta < 0.0 || powell;
+        if restart {
+            beta = 0.0;
+        }
+        direction = -&g_next + beta * direction;
+        // Направление, не являющееся направлением спуска, заменяется антиградиентом
+        let descent_lost = direction.dot(&g_next) >= 0.0;
+        if descent_lost {
+            direction = -&g_next;
+        }
+        g = g_next;
+
+        if params.verbose {
+            log.push(format!(
+                "итер. {}: ||g|| = {:.3e}, шаг = {:.3e}, дроблений: {}, beta = {:.3e}{}, f = {:.6e}",
+                iter + 1,
+                g_norm,
+                trial_step,
+                backtracks,
+                beta,
+                if powell {
+                    " (рестарт Пауэлла)"
+                } else if restart || descent_lost {
+                    " (рестарт)"
+                } else {
+                    ""
+                },
+                f_x
+            ));
+        }
+
+        iter += 1;
+        history.push((x.clone(), f_x));
+        params.report(iter, &x, f_x);
+    }
+
+    OptimizerResult {
+        x,
+        f_x,
+        iterations: iter,
+        history,
+        termination,
+        log,
+        trust_radii: Vec::new(),
+        grad_norms: Vec::new(),
+        step_sizes: Vec::new(),
+        perturbations: Vec::new(),
+        history_iterations: Vec::new(),
+        elapsed: counter.started.elapsed(),
+        f_evals: counter.f_evals.get(),
+        grad_evals: counter.grad_evals.get(),
+    }
+    .revert_non_finite()
+    .thin_history(params.max_history_points)
+}
+
+// Квазиньютоновский метод BFGS: приближение обратной матрицы Гессе H
+// (изначально единичной) обновляется по векторам s = x_{k+1} - x_k и
+// y = g_{k+1} - g_k. Обновление пропускается при y·s <= 0, чтобы H оставалась
+// положительно определённой. Шаг вдоль -H·g подбирается дроблением от единичного.
+pub fn bfgs(
+    initial_point: DVector<f64>,
+    f: &ObjectiveFn,
+    grad: &GradientFn,
+    params: &DescentParams,
+    stop_flag: Arc<AtomicBool>,
+) -> OptimizerResult {
+    let counter = EvalCounter::new();
+    let f = &counter.objective(f);
+    let grad = &counter.gradient(grad);
+    let mut x = initial_point;
+    let mut f_x = f(&x);
+    let mut iter = 0;
+    let n = x.len();
+    let mut h = DMatrix::<f64>::identity(n, n);
+
+    let mut history = Vec::new();
+    history.push((x.clone(), f_x));
+    let mut termination = TerminationReason::MaxIterations;
+
+    let mut log = Vec::new();
+    if params.verbose {
+        log.push(format!("старт: f(x0) = {:.6e}", f_x));
+    }
+
+    let mut g = grad(&x);
+
+    while iter < params.max_iterations {
+        if stop_flag.load(Ordering::SeqCst) {
+            termination = TerminationReason::UserStopped;
+            break;
+        }
+
+        if let Some(reason) = non_finite_termination(f_x, &g, iter, params, &mut log) {
+            termination = reason;
+            break;
+        }
+
+        let g_norm = g.norm();
+        if g_norm < params.tolerance {
+            if params.verbose {
+                log.push(format!(
+                    "итер. {}: ||g|| = {:.3e} < {:.1e}, сходимость",
+                    iter + 1,
+                    g_norm,
+                    params.tolerance
+                ));
+            }
+            termination = TerminationReason::Converged;
+            break;
+        }
+
+        let direction = -(&h * &g);
+        let line_search = AdaptiveHalving::new(1.0, params.step_decay, params.step_increase, 1.0)
+            .with_max_backtracks(params.max_backtracks);
+        let slope = g.dot(&direction);
+        let Some(accepted) = search_step(&line_search, f, &x, f_x, &direction, slope, params)
+        else {
+            if params.verbose {
+                log.push(format!(
+                    "итер. {}: ||g|| = {:.3e}, шаг не найден за {} дроблений, остановка",
+                    iter + 1,
+                    g_norm,
+                    params.max_backtracks
+                ));
+            }
+            termination = TerminationReason::LineSearchFailed;
+            break;
+        };
+
+        let s_vec = &accepted.x - &x;
+        x = accepted.x;
+        f_x = accepted.f_x;
+        let g_next = grad(&x);
+        let y = &g_next - &g;
+        g = g_next;
+
+        let ys = y.dot(&s_vec);
+        let updated = ys > 0.0;
+        if updated {
+            // H+ = (I - ρ s yᵀ) H (I - ρ y sᵀ) + ρ s sᵀ, ρ = 1 / (y·s)
+            let rho = 1.0 / ys;
+            let identity = DMatrix::<f64>::identity(n, n);
+            let left = &identity - rho * &s_vec * y.transpose();
+            let right = &identity - rho * &y * s_vec.transpose();
+            h = left * &h * right + rho * &s_vec * s_vec.transpose();
+        }
+
+        if params.verbose {
+            log.push(format!(
+                "итер. {}: ||g|| = {:.3e}, шаг = {:.3e}, дроблений: {}, f = {:.6e}{}",
+                iter + 1,
+                g_norm,
+                accepted.step,
+                accepted.evaluations - 1,
+                f_x,
+                if updated {
+                    ""
+                } else {
+                    ", y·s <= 0, обновление H пропущено"
+                }
+            ));
+        }
+
+        iter += 1;
+        history.push((x.clone(), f_x));
+        params.report(iter, &x, f_x);
+    }
+
+    OptimizerResult {
+        x,
+        f_x,
+        iterations: iter,
+        history,
+        termination,
+        log,
+        trust_radii: Vec::new(),
+        grad_norms: Vec::new(),
+        step_sizes: Vec::new(),
+        perturbations: Vec::new(),
+        history_iterations: Vec::new(),
+        elapsed: counter.started.elapsed(),
+        f_evals: counter.f_evals.get(),
+        grad_evals: counter.grad_evals.get(),
+    }
+    .revert_non_finite()
+    .thin_history(params.max_history_points)
+}
+
+// L-BFGS: произведение H·g вычисляется двухпроходной рекурсией по последним
+// memory парам (s, y) без хранения матрицы n×n; пары с y·s <= 0 не сохраняются.
+// Без пар направление — антиградиент, нормированный к шагу не длиннее единицы.
+pub fn lbfgs(
+    initial_point: DVector<f64>,
+    f: &ObjectiveFn,
+    grad: &GradientFn,
+    params: &DescentParams,
+    memory: usize,
+    stop_flag: Arc<AtomicBool>,
+) -> OptimizerResult {
+    let counter = EvalCounter::new();
+    let f = &counter.objective(f);
+    let grad = &counter.gradient(grad);
+    let mut x = initial_point;
+    let mut f_x = f(&x);
+    let mut iter = 0;
+    let mut pairs: VecDeque<(DVector<f64>, DVector<f64>, f64)> = VecDeque::with_capacity(memory);
+
+    let mut history = Vec::new();
+    history.push((x.clone(), f_x));
+    let mut termination = TerminationReason::MaxIterations;
+
+    let mut log = Vec::new();
+    if params.verbose {
+        log.push(format!(
+            "старт: f(x0) = {:.6e}, пар (s, y): {}",
+            f_x, memory
+        ));
+    }
+
+    let mut g = grad(&x);
+
+    while iter < params.max_iterations {
+        if stop_flag.load(Ordering::SeqCst) {
+            termination = TerminationReason::UserStopped;
+            break;
+        }
+
+        if let Some(reason) = non_finite_termination(f_x, &g, iter, params, &mut log) {
+            termination = reason;
+            break;
+        }
+
+        let g_norm = g.norm();
+        if g_norm < params.tolerance {
+            if params.verbose {
+                log.push(format!(
+                    "итер. {}: ||g|| = {:.3e} < {:.1e}, сходимость",
+                    iter + 1,
+                    g_norm,
+                    params.tolerance
+                ));
+            }
+            termination = TerminationReason::Converged;
+            break;
+        }
+
+        let direction = match pairs.back() {
+            None => -&g / g_norm.max(1.0),
+            Some((s_last, y_last, _)) => {
+                let mut q = g.clone();
+                let mut alphas = Vec::with_capacity(pairs.len());
+                for (s_vec, y, rho) in pairs.iter().rev() {
+                    let alpha = rho * s_vec.dot(&q);
+                    q -= alpha * y;
+                    alphas.push(alpha);
+                }
+                // Начальное приближение H0 = γI, γ = (s·y) / (y·y)
+                let mut r = s_last.dot(y_last) / y_last.dot(y_last) * q;
+                for ((s_vec, y, rho), alpha) in pairs.iter().zip(alphas.iter().rev()) {
+                    let beta = rho * y.dot(&r);
+                    r += (alpha - beta) * s_vec;
+                }
+                -r
+            }
+        };
+
+        let line_search = AdaptiveHalving::new(1.0, params.step_decay, params.step_increase, 1.0)
+            .with_max_backtracks(params.max_backtracks);
+        let slope = g.dot(&direction);
+        let Some(accepted) = search_step(&line_search, f, &x, f_x, &direction, slope, params)
+        else {
+            if params.verbose {
+                log.push(format!(
+                    "итер. {}: ||g|| = {:.3e}, шаг не найден за {} дроблений, остановка",
+                    iter + 1,
+                    g_norm,
+                    params.max_backtracks
+                ));
+            }
+            termination = TerminationReason::LineSearchFailed;
+            break;
+        };
+
+        let s_vec = &accepted.x - &x;
+        x = accepted.x;
+        f_x = accepted.f_x;
+        let g_next = grad(&x);
+        let y = &g_next - &g;
+        g = g_next;
+
+        let ys = y.dot(&s_vec);
+        let stored = ys > 0.0 && memory > 0;
+        if stored {
+            if pairs.len() == memory {
+                pairs.pop_front();
+            }
+            pairs.push_back((s_vec, y, 1.0 / ys));
+        }
+
+        if params.verbose {
+            log.push(format!(
+                "итер. {}: ||g|| = {:.3e}, шаг = {:.3e}, дроблений: {}, f = {:.6e}{}",
+                iter + 1,
+                g_norm,
+                accepted.step,
+                accepted.evaluations - 1,
+                f_x,
+                if ys > 0.0 {
+                    ""
+                } else {
+                    ", y·s <= 0, пара пропущена"
+                }
+            ));
+        }
+
+        iter += 1;
+        history.push((x.clone(), f_x));
+        params.report(iter, &x, f_x);
+    }
+
+    OptimizerResult {
+        x,
+        f_x,
+        iterations: iter,
+        history,
+        termination,
+        log,
+        trust_radii: Vec::new(),
+        grad_norms: Vec::new(),
+        step_sizes: Vec::new(),
+        perturbations: Vec::new(),
+        history_iterations: Vec::new(),
+        elapsed: counter.started.elapsed(),
+        f_evals: counter.f_evals.get(),
+        grad_evals: counter.grad_evals.get(),
+    }
+    .revert_non_finite()
+    .thin_history(params.max_history_points)
+}
+
+// Метод Ньютона: направление из H·d = -g (разложение Холецкого); если матрица
+// Гессе не положительно определена, используется антиградиент. Шаг вдоль
+// направления подбирается дроблением, начиная с единичного.
+pub fn newton_descent(
+    initial_point: DVector<f64>,
+    f: &ObjectiveFn,
+    grad: &GradientFn,
+    hess: &HessianFn,
+    params: &DescentParams,
+    stop_flag: Arc<AtomicBool>,
+) -> OptimizerResult {
+    let counter = EvalCounter::new();
+    let f = &counter.objective(f);
+    let grad = &counter.gradient(grad);
+    let mut x = initial_point;
+    let mut f_x = f(&x);
+    let mut iter = 0;
+
+    let mut history = Vec::new();
+    history.push((x.clone(), f_x));
+    let mut termination = TerminationReason::MaxIterations;
+
+    let mut log = Vec::new();
+    if params.verbose {
+        log.push(format!("старт: f(x0) = {:.6e}", f_x));
+    }
+
+    while iter < params.max_iterations {
+        if stop_flag.load(Ordering::SeqCst) {
+            termination = TerminationReason::UserStopped;
+            break;
+        }
+
+        let g = grad(&x);
+        if let Some(reason) = non_finite_termination(f_x, &g, iter, params, &mut log) {
+            termination = reason;
+            break;
+        }
+        let g_norm = g.norm();
+
+        if g_norm < params.tolerance {
+            if params.verbose {
+                log.push(format!(
+                    "итер. {}: ||g|| = {:.3e} < {:.1e}, сходимость",
+                    iter + 1,
+                    g_norm,
+                    params.tolerance
+                ));
+            }
+            termination = TerminationReason::Converged;
+            break;
+        }
+
+        let (direction, newton_step) = match hess(&x).cholesky() {
+            Some(chol) => (-chol.solve(&g), true),
+            None => (-&g, false),
+        };
+
+        let (initial_step, max_step) = if newton_step {
+            (1.0, 1.0)
+        } else {
+            (params.initial_step, params.max_step)
+        };
+        let line_search = AdaptiveHalving::new(
+            initial_step,
+            params.step_decay,
+            params.step_increase,
+            max_step,
+        )
+        .with_max_backtracks(params.max_backtracks);
+        let slope = g.dot(&direction);
+        let (trial_step, backtracks) =
+            match search_step(&line_search, f, &x, f_x, &direction, slope, params) {
+                Some(accepted) => {
+                    x = accepted.x;
+                    f_x = accepted.f_x;
+                    (accepted.step, accepted.evaluations - 1)
+                }
+                None => {
+                    if params.verbose {
+                        log.push(format!(
+                            "итер. {}: ||g|| = {:.3e}, шаг не найден за {} дроблений, остановка",
+                            iter + 1,
+                            g_norm,
+                            params.max_backtracks
+                        ));
+                    }
+                    termination = TerminationReason::LineSearchFailed;
+                    break;
+                }
+            };
+
+        if params.verbose {
+            log.push(format!(
+                "итер. {}: ||g|| = {:.3e}, {}, шаг = {:.3e}, дроблений: {}, f = {:.6e}",
+                iter + 1,
+                g_norm,
+                if newton_step {
+                    "шаг Ньютона"
+                } else {
+                    "H не положительно определена, антиградиент"
+                },
+                trial_step,
+                backtracks,
+                f_x
+            ));
+        }
+
+        iter += 1;
+        history.push((x.clone(), f_x));
+        params.report(iter, &x, f_x);
+    }
+
+    OptimizerResult {
+        x,
+        f_x,
+        iterations: iter,
+        history,
+        termination,
+        log,
+        trust_radii: Vec::new(),
+        grad_norms: Vec::new(),
+        step_sizes: Vec::new(),
+        perturbations: Vec::new(),
+        history_iterations: Vec::new(),
+        elapsed: counter.started.elapsed(),
+        f_evals: counter.f_evals.get(),
+        grad_evals: counter.grad_evals.get(),
+    }
+    .revert_non_finite()
+    .thin_history(params.max_history_points)
+}
+
+// Гаусс — Ньютон для f(x) = Σ r_i(x)²: шаг p — решение J·p ≈ -r по методу
+// наименьших квадратов через SVD, поэтому вырожденный якобиан допустим.
+// Вычисления невязок считаются вычислениями f, якобиана — градиента
+pub fn gauss_newton(
+    initial_point: DVector<f64>,
+    residuals: &ResidualFn,
+    jacobian: &JacobianFn,
+    params: &DescentParams,
+    stop_flag: Arc<AtomicBool>,
+) -> OptimizerResult {
+    let counter = EvalCounter::new();
+    let residuals = |x: &DVector<f64>| {
+        counter.f_evals.set(counter.f_evals.get() + 1);
+        residuals(x)
+    };
+    let jacobian = |x: &DVector<f64>| {
+        counter.grad_evals.set(counter.grad_evals.get() + 1);
+        jacobian(x)
+    };
+    let f = |x: &DVector<f64>| residuals(x).norm_squared();
+    let mut x = initial_point;
+    let mut r = residuals(&x);
+    let mut f_x = r.norm_squared();
+    let mut iter = 0;
+
+    let mut history = Vec::new();
+    history.push((x.clone(), f_x));
+    let mut grad_norms = Vec::new();
+    let mut step_sizes = Vec::new();
+    let mut termination = TerminationReason::MaxIterations;
+
+    let mut log = Vec::new();
+    if params.verbose {
+        log.push(format!("старт: f(x0) = {:.6e}", f_x));
+    }
+
+    while iter < params.max_iterations {
+        if stop_flag.load(Ordering::SeqCst) {
+            termination = TerminationReason::UserStopped;
+            break;
+        }
+
+        if !f_x.is_finite() {
+            termination = TerminationReason::NonFiniteValue;
+            break;
+        }
+
+        let j = jacobian(&x);
+        let g = 2.0 * j.transpose() * &r;
+        let g_norm = g.norm();
+        grad_norms.push(g_norm);
+
+        if !g_norm.is_finite() {
+            termination = TerminationReason::GradientNaN;
+            break;
+        }
+        if g_norm < params.tolerance {
+            if params.verbose {
+                log.push(format!(
+                    "итер. {}: ||g|| = {:.3e} < {:.1e}, сходимость",
+                    iter + 1,
+                    g_norm,
+                    params.tolerance
+                ));
+            }
+            termination = TerminationReason::Converged;
+            break;
+        }
+
+        let Ok(direction) = j.svd(true, true).solve(&-&r, 1e-12) else {
+            termination = TerminationReason::LineSearchFailed;
+            break;
+        };
+        // Полный шаг Гаусса — Ньютона дробится, если f не уменьшилась
+        let line_search = AdaptiveHalving::new(1.0, params.step_decay, 1.0, 1.0)
+            .with_max_backtracks(params.max_backtracks);
+        let slope = g.dot(&direction);
+        let Some(accepted) = search_step(&line_search, &f, &x, f_x, &direction, slope, params)
+        else {
+            if params.verbose {
+                log.push(format!(
+                    "итер. {}: ||g|| = {:.3e}, шаг не найден за {} дроблений, остановка",
+                    iter + 1,
+                    g_norm,
+                    params.max_backtracks
+                ));
+            }
+            termination = TerminationReason::LineSearchFailed;
+            break;
+        };
+        step_sizes.push((&accepted.x - &x).norm());
+        x = accepted.x;
+        f_x = accepted.f_x;
+        r = residuals(&x);
+
+        if params.verbose {
+            log.push(format!(
+                "итер. {}: ||g|| = {:.3e}, шаг = {:.3e}, f = {:.6e}",
+                iter + 1,
+                g_norm,
+                accepted.step,
+                f_x
+            ));
+        }
+
+        iter += 1;
+        history.push((x.clone(), f_x));
+        params.report(iter, &x, f_x);
+    }
+
+    if grad_norms.len() < history.len() {
+        grad_norms.push((2.0 * jacobian(&x).transpose() * &r).norm());
+    }
+
+    OptimizerResult {
+        x,
+        f_x,
+        iterations: iter,
+        history,
+        termination,
+        log,
+        trust_radii: Vec::new(),
+        grad_norms,
+        step_sizes,
+        perturbations: Vec::new(),
+        history_iterations: Vec::new(),
+        elapsed: counter.started.elapsed(),
+        f_evals: counter.f_evals.get(),
+        grad_evals: counter.grad_evals.get(),
+    }
+    .thin_history(params.max_history_points)
+}
+
+#[derive(Debug, Clone)]
+pub struct TrustRegionParams {
+    pub initial_radius: f64,
+    pub max_radius: f64,
+    // Минимальное отношение фактического уменьшения к прогнозу для принятия шага
+    pub eta: f64,
+}
+
+impl Default for TrustRegionParams {
+    fn default() -> Self {
+        Self {
+            initial_radius: 1.0,
+            max_radius: 100.0,
+            eta: 0.1,
+        }
+    }
+}
+
+// Произведение матрицы Гессе на вектор без формирования самой матрицы:
+// H·v ≈ (∇f(x + h·v) - ∇f(x)) / h
+fn hessian_vector_product(
+    grad: &GradientFn,
+    x: &DVector<f64>,
+    g: &DVector<f64>,
+    v: &DVector<f64>,
+) -> DVector<f64> {
+    let v_norm = v.norm();
+    if v_norm == 0.0 {
+        return DVector::zeros(v.len());
+    }
+    let h = f64::EPSILON.sqrt() * (1.0 + x.norm()) / v_norm;
+    (grad(&(x + h * v)) - g) / h
+}
+
+// Разностная матрица Гессе, собранная по столбцам H·e_i, имеет отрицательное
+// собственное значение, заметное на фоне погрешности разностей
+fn has_negative_curvature(grad: &GradientFn, x: &DVector<f64>, g: &DVector<f64>) -> bool {
+    let n = x.len();
+    let mut h = DMatrix::zeros(n, n);
+    for i in 0..n {
+        let e = DVector::from_fn(n, |j, _| if j == i { 1.0 } else { 0.0 });
+        h.set_column(i, &hessian_vector_product(grad, x, g, &e));
+    }
+    let eigenvalues = ((&h + h.transpose()) * 0.5).symmetric_eigenvalues();
+    eigenvalues.min() < -1e-6 * (1.0 + eigenvalues.amax())
+}
+
+// Положительный корень ||z + tau·d|| = delta
+fn boundary_tau(z: &DVector<f64>, d: &DVector<f64>, delta: f64) -> f64 {
+    let a = d.dot(d);
+    let b = 2.0 * z.dot(d);
+    let c = z.dot(z) - delta * delta;
+    (-b + (b * b - 4.0 * a * c).max(0.0).sqrt()) / (2.0 * a)
+}
+
+struct SteihaugStep {
+    p: DVector<f64>,
+    cg_iterations: usize,
+    // Причина выхода на границу области, если она была достигнута
+    boundary: Option<&'static str>,
+}
+
+// Усечённый метод сопряжённых градиентов Штайхауга–Тоинта для подзадачи
+// min g·p + ½ p·H·p при ||p|| <= delta.
+fn steihaug_cg(grad: &GradientFn, x: &DVector<f64>, g: &DVector<f64>, delta: f64) -> SteihaugStep {
+    let n = x.len();
+    let g_norm = g.norm();
+    let cg_tol = g_norm * g_norm.sqrt().min(0.5);
+
+    let mut z = DVector::zeros(n);
+    let mut r = g.clone();
+    let mut d = -g;
+
+    for j in 0..2 * n {
+        let bd = hessian_vector_product(grad, x, g, &d);
+        let d_bd = d.dot(&bd);
+
+        if d_bd <= 0.0 {
+            let tau = boundary_tau(&z, &d, delta);
+            return SteihaugStep {
+                p: z + tau * d,
+                cg_iterations: j + 1,
+                boundary: Some("отрицательная кривизна"),
+            };
+        }
+
+        let r_sq = r.dot(&r);
+        let alpha = r_sq / d_bd;
+        let z_next = &z + alpha * &d;
+
+        if z_next.norm() >= delta {
+            let tau = boundary_tau(&z, &d, delta);
+            return SteihaugStep {
+                p: z + tau * d,
+                cg_iterations: j + 1,
+                boundary: Some("граница области"),
+            };
+        }
+
+        let r_next = r + alpha * bd;
+        if r_next.norm() < cg_tol {
+            return SteihaugStep {
+                p: z_next,
+                cg_iterations: j + 1,
+                boundary: None,
+            };
+        }
+
+        let beta = r_next.dot(&r_next) / r_sq;
+        d = -&r_next + beta * d;
+        r = r_next;
+        z = z_next;
+    }
+
+    SteihaugStep {
+        p: z,
+        cg_iterations: 2 * n,
+        boundary: None,
+    }
+}
+
+// Отношение фактического уменьшения f к прогнозу модели. Пробная точка вне
+// области определения (f = NaN или ±∞) считается неудачной: иначе rho = NaN
+// не проходит ни одну проверку, радиус не меняется и шаг повторяется
+fn trust_ratio(f_x: f64, f_trial: f64, predicted: f64) -> f64 {
+    let rho = (f_x - f_trial) / predicted;
+    if predicted > 0.0 && rho.is_finite() {
+        rho
+    } else {
+        -1.0
+    }
+}
+
+pub fn trust_region_cg(
+    initial_point: DVector<f64>,
+    f: &ObjectiveFn,
+    grad: &GradientFn,
+    params: &DescentParams,
+    tr: &TrustRegionParams,
+    stop_flag: Arc<AtomicBool>,
+) -> OptimizerResult {
+    let counter = EvalCounter::new();
+    let f = &counter.objective(f);
+    let grad = &counter.gradient(grad);
+    let mut x = initial_point;
+    let mut f_x = f(&x);
+    let mut iter = 0;
+    let mut delta = tr.initial_radius;
+
+    let mut history = Vec::new();
+    history.push((x.clone(), f_x));
+    let mut trust_radii = vec![delta];
+    let mut termination = TerminationReason::MaxIterations;
+
+    let mut log = Vec::new();
+    if params.verbose {
+        log.push(format!(
+            "старт: f(x0) = {:.6e}, радиус = {:.3e}",
+            f_x, delta
+        ));
+    }
+
+    while iter < params.max_iterations {
+        if stop_flag.load(Ordering::SeqCst) {
+            termination = TerminationReason::UserStopped;
+            break;
+        }
+
+        if !f_x.is_finite() {
+            termination = TerminationReason::NonFiniteValue;
+            break;
+        }
+
+        let g = grad(&x);
+        if g.iter().any(|v| !v.is_finite()) {
+            termination = TerminationReason::GradientNaN;
+            break;
+        }
+        let g_norm = g.norm();
+
+        if g_norm < params.tolerance {
+            if params.verbose {
+                log.push(format!(
+                    "итер. {}: ||g|| = {:.3e} < {:.1e}, сходимость",
+                    iter + 1,
+                    g_norm,
+                    params.tolerance
+                ));
+            }
+            termination = TerminationReason::Converged;
+            break;
+        }
+
+        // Радиус сжался до машинной точности: улучшающий шаг не находится
+        if delta < f64::EPSILON * (1.0 + x.norm()) {
+            termination = TerminationReason::LineSearchFailed;
+            break;
+        }
+
+        let step = steihaug_cg(grad, &x, &g, delta);
+        let p = step.p;
+        let bp = hessian_vector_product(grad, &x, &g, &p);
+        let predicted = -(g.dot(&p) + 0.5 * p.dot(&bp));
+
+        let x_trial = &x + &p;
+        let f_trial = f(&x_trial);
+        let rho = trust_ratio(f_x, f_trial, predicted);
+
+        let p_norm = p.norm();
+        if rho < 0.25 {
+            delta *= 0.25;
+        } else if rho > 0.75 && (p_norm - delta).abs() <= 1e-8 * delta.max(1.0) {
+            delta = (2.0 * delta).min(tr.max_radius);
+        }
+
+        let accepted = rho > tr.eta && f_trial.is_finite();
+        if params.verbose {
+            log.push(format!(
+                "итер. {}: ||g|| = {:.3e}, ||p|| = {:.3e}, rho = {:.3}, КГ-итераций: {}{}, {}, новый радиус = {:.3e}",
+                iter + 1,
+                g_norm,
+                p_norm,
+                rho,
+                step.cg_iterations,
+                step.boundary.map(|b| format!(" ({})", b)).unwrap_or_default(),
+                if accepted { "шаг принят" } else { "шаг отклонён" },
+                delta
+            ));
+        }
+
+        if accepted {
+            x = x_trial;
+            wrap_periodic(&mut x, &params.periodic);
+            f_x = f_trial;
+        }
+
+        iter += 1;
+        history.push((x.clone(), f_x));
+        params.report(iter, &x, f_x);
+        trust_radii.push(delta);
+    }
+
+    OptimizerResult {
+        x,
+        f_x,
+        iterations: iter,
+        history,
+        termination,
+        log,
+        trust_radii,
+        grad_norms: Vec::new(),
+        step_sizes: Vec::new(),
+        perturbations: Vec::new(),
+        history_iterations: Vec::new(),
+        elapsed: counter.started.elapsed(),
+        f_evals: counter.f_evals.get(),
+        grad_evals: counter.grad_evals.get(),
+    }
+    .revert_non_finite()
+    .thin_history(params.max_history_points)
+}
+
+// Точка Коши: минимум модели вдоль -g в пределах радиуса
+fn cauchy_point(g: &DVector<f64>, bg: &DVector<f64>, delta: f64) -> DVector<f64> {
+    let g_norm = g.norm();
+    let curvature = g.dot(bg);
+    let tau = if curvature <= 0.0 {
+        1.0
+    } else {
+        (g_norm.powi(3) / (delta * curvature)).min(1.0)
+    };
+    -(tau * delta / g_norm) * g
+}
+
+// Шаг dogleg: ломаная от минимума модели вдоль антиградиента к ньютоновскому
+// шагу, обрезанная радиусом. При не положительно определённой H — точка Коши
+fn dogleg_step(g: &DVector<f64>, h: &DMatrix<f64>, delta: f64) -> (DVector<f64>, &'static str) {
+    let bg = h * g;
+    let curvature = g.dot(&bg);
+    let Some(newton) = h.clone().cholesky().map(|c| -c.solve(g)) else {
+        return (
+            cauchy_point(g, &bg, delta),
+            "точка Коши, H не положительно определена",
+        );
+    };
+    if newton.norm() <= delta {
+        return (newton, "ньютоновский шаг");
+    }
+    let p_u = -(g.dot(g) / curvature) * g;
+    if p_u.norm() >= delta {
+        return (delta / p_u.norm() * p_u, "антиградиент до границы");
+    }
+    let d = &newton - &p_u;
+    let tau = boundary_tau(&p_u, &d, delta);
+    (p_u + tau * d, "ломаная до границы")
+}
+
+// Доверительная область с шагом dogleg по разностной матрице Гессе;
+// радиус меняется по отношению rho фактического уменьшения f к прогнозу модели
+pub fn trust_region_dogleg(
+    initial_point: DVector<f64>,
+    f: &ObjectiveFn,
+    grad: &GradientFn,
+    hess: &HessianFn,
+    params: &DescentParams,
+    tr: &TrustRegionParams,
+    stop_flag: Arc<AtomicBool>,
+) -> OptimizerResult {
+    let counter = EvalCounter::new();
+    let f = &counter.objective(f);
+    let grad = &counter.gradient(grad);
+    let mut x = initial_point;
+    let mut f_x = f(&x);
+    let mut iter = 0;
+    let mut delta = tr.initial_radius;
+
+    let mut history = Vec::new();
+    history.push((x.clone(), f_x));
+    let mut trust_radii = vec![delta];
+    let mut termination = TerminationReason::MaxIterations;
+
+    let mut log = Vec::new();
+    if params.verbose {
+        log.push(format!(
+            "старт: f(x0) = {:.6e}, радиус = {:.3e}",
+            f_x, delta
+        ));
+    }
+
+    while iter < params.max_iterations {
+        if stop_flag.load(Ordering::SeqCst) {
+            termination = TerminationReason::UserStopped;
+            break;
+        }
+        if !f_x.is_finite() {
+            termination = TerminationReason::NonFiniteValue;
+            break;
+        }
+
+        let g = grad(&x);
+        if g.iter().any(|v| !v.is_finite()) {
+            termination = TerminationReason::GradientNaN;
+            break;
+        }
+        let g_norm = g.norm();
+        if g_norm < params.tolerance {
+            if params.verbose {
+                log.push(format!(
+                    "итер. {}: ||g|| = {:.3e} < {:.1e}, сходимость",
+                    iter + 1,
+                    g_norm,
+                    params.tolerance
+                ));
+            }
+            termination = TerminationReason::Converged;
+            break;
+        }
+
+        // Радиус сжался до машинной точности: улучшающий шаг не находится
+        if delta < f64::EPSILON * (1.0 + x.norm()) {
+            termination = TerminationReason::LineSearchFailed;
+            break;
+        }
+
+        let h = hess(&x);
+        if h.iter().any(|v| !v.is_finite()) {
+            termination = TerminationReason::GradientNaN;
+            break;
+        }
+        let (p, kind) = dogleg_step(&g, &h, delta);
+        let predicted = -(g.dot(&p) + 0.5 * p.dot(&(&h * &p)));
+
+        let x_trial = &x + &p;
+        let f_trial = f(&x_trial);
+        let rho = trust_ratio(f_x, f_trial, predicted);
+
+        let p_norm = p.norm();
+        if rho < 0.25 {
+            delta *= 0.25;
+        } else if rho > 0.75 && (p_norm - delta).abs() <= 1e-8 * delta.max(1.0) {
+            delta = (2.0 * delta).min(tr.max_radius);
+        }
+
+        let accepted = rho > tr.eta && f_trial.is_finite();
+        if params.verbose {
+            log.push(format!(
+                "итер. {}: ||g|| = {:.3e}, ||p|| = {:.3e}, rho = {:.3}, {}, {}, новый радиус = {:.3e}",
+                iter + 1,
+                g_norm,
+                p_norm,
+                rho,
+                kind,
+                if accepted { "шаг принят" } else { "шаг отклонён" },
+                delta
+            ));
+        }
+
+        if accepted {
+            x = x_trial;
+            wrap_periodic(&mut x, &params.periodic);
+            f_x = f_trial;
+        }
+
+        iter += 1;
+        history.push((x.clone(), f_x));
+        params.report(iter, &x, f_x);
+        trust_radii.push(delta);
+    }
+
+    OptimizerResult {
+        x,
+        f_x,
+        iterations: iter,
+        history,
+        termination,
+        log,
+        trust_radii,
+        grad_norms: Vec::new(),
+        step_sizes: Vec::new(),
+        perturbations: Vec::new(),
+        history_iterations: Vec::new(),
+        elapsed: counter.started.elapsed(),
+        f_evals: counter.f_evals.get(),
+        grad_evals: counter.grad_evals.get(),
+    }
+    .thin_history(params.max_history_points)
+}
+
+#[derive(Debug)]
+pub struct ParetoPoint {
+    pub weight: f64,
+    pub x: DVector<f64>,
+    pub f1: f64,
+    pub f2: f64,
+    pub iterations: usize,
+}
+
+// w·a + (1-w)·b; слагаемое с нулевым весом не вычисляется, чтобы при w = 0
+// и w = 1 вторая функция не давала NaN там, где она не определена
+pub fn weighted_sum<T>(w: f64, a: impl FnOnce() -> T, b: impl FnOnce() -> T) -> T
+where
+    T: std::ops::Mul<f64, Output = T> + std::ops::Add<Output = T>,
+{
+    if w == 1.0 {
+        a()
+    } else if w == 0.0 {
+        b()
+    } else {
+        a() * w + b() * (1.0 - w)
+    }
+}
+
+// Скаляризация взвешенной суммой: для каждого w решается задача
+// min w·f1 + (1-w)·f2, получаемые пары (f1, f2) приближают фронт Парето.
+#[allow(clippy::too_many_arguments)]
+pub fn weighted_sum_sweep(
+    initial_point: DVector<f64>,
+    f1: &ObjectiveFn,
+    f2: &ObjectiveFn,
+    grad1: &GradientFn,
+    grad2: &GradientFn,
+    num_weights: usize,
+    params: &DescentParams,
+    make_line_search: &LineSearchFactory,
+    stop_flag: Arc<AtomicBool>,
+) -> Vec<ParetoPoint> {
+    let mut front = Vec::with_capacity(num_weights);
+    let denom = (num_weights.max(2) - 1) as f64;
+
+    for k in 0..num_weights.max(2) {
+        if stop_flag.load(Ordering::SeqCst) {
+            break;
+        }
+
+        let w = k as f64 / denom;
+        let f = |x: &DVector<f64>| weighted_sum(w, || f1(x), || f2(x));
+        let grad = |x: &DVector<f64>| weighted_sum(w, || grad1(x), || grad2(x));
+
+        let line_search = make_line_search(params);
+        let result = gradient_descent(
+            initial_point.clone(),
+            &f,
+            &grad,
+            params,
+            &*line_search,
+            stop_flag.clone(),
+        );
+
+        front.push(ParetoPoint {
+            weight: w,
+            f1: f1(&result.x),
+            f2: f2(&result.x),
+            x: result.x,
+            iterations: result.iterations,
+        });
+    }
+
+    front
+}
+
+// Результаты запусков из узлов равномерной сетки начальных точек (2D)
+#[derive(Debug)]
+pub struct SensitivityMap {
+    pub x_range: (f64, f64),
+    pub y_range: (f64, f64),
+    pub resolution: usize,
+    // Значения по строкам: индекс j * resolution + i соответствует узлу (x_i, y_j)
+    pub final_f: Vec<f64>,
+    pub iterations: Vec<usize>,
+}
+
+impl SensitivityMap {
+    pub fn node(&self, i: usize, j: usize) -> (f64, f64) {
+        let t = |k: usize| {
+            if self.resolution > 1 {
+                k as f64 / (self.resolution - 1) as f64
+            } else {
+                0.5
+            }
+        };
+        (
+            self.x_range.0 + t(i) * (self.x_range.1 - self.x_range.0),
+            self.y_range.0 + t(j) * (self.y_range.1 - self.y_range.0),
+        )
+    }
+}
+
+pub fn start_point_sensitivity(
+    x_range: (f64, f64),
+    y_range: (f64, f64),
+    resolution: usize,
+    solve: &dyn Fn(DVector<f64>) -> OptimizerResult,
+    stop_flag: Arc<AtomicBool>,
+) -> SensitivityMap {
+    let mut map = SensitivityMap {
+        x_range,
+        y_range,
+        resolution,
+        final_f: vec![f64::NAN; resolution * resolution],
+        iterations: vec![0; resolution * resolution],
+    };
+
+    for j in 0..resolution {
+        for i in 0..resolution {
+            if stop_flag.load(Ordering::SeqCst) {
+                return map;
+            }
+            let (x, y) = map.node(i, j);
+            let result = solve(DVector::from_vec(vec![x, y]));
+            map.final_f[j * resolution + i] = result.f_x;
+            map.iterations[j * resolution + i] = result.iterations;
+        }
+    }
+
+    map
+}
+
+// Независимые запуски из нескольких начальных точек, параллельно.
+// init создаёт для каждого потока свою копию целевой функции; результаты
+// идут в порядке точек, точки после остановки пропускаются
+pub fn multi_start<T, I, S>(
+    starts: Vec<DVector<f64>>,
+    init: I,
+    solve: S,
+    stop_flag: &AtomicBool,
+) -> Vec<OptimizerResult>
+where
+    I: Fn() -> T + Sync + Send,
+    S: Fn(&mut T, DVector<f64>) -> OptimizerResult + Sync + Send,
+{
+    starts
+        .into_par_iter()
+        .filter(|_| !stop_flag.load(Ordering::SeqCst))
+        .map_init(init, solve)
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestartParams {
+    pub count: usize,
+    // Все координаты начальных точек берутся из [lower, upper]
+    pub lower: f64,
+    pub upper: f64,
+    pub seed: u64,
+}
+
+impl Default for RestartParams {
+    fn default() -> Self {
+        Self {
+            count: 20,
+            lower: -3.0,
+            upper: 3.0,
+            seed: 42,
+        }
+    }
+}
+
+pub struct RestartReport {
+    pub best: OptimizerResult,
+    // Итоговые точки и значения всех запусков в порядке начальных точек
+    pub finals: Vec<(DVector<f64>, f64)>,
+}
+
+// Случайные перезапуски: count начальных точек, распределённых равномерно в
+// [lower, upper]^n, решаются через multi_start; лучшим считается запуск с
+// наименьшим f(x*), NaN — худшее значение. None, если ни один запуск не выполнен
+pub fn random_restart<T, I, S>(
+    n: usize,
+    rp: &RestartParams,
+    init: I,
+    solve: S,
+    stop_flag: &AtomicBool,
+) -> Option<RestartReport>
+where
+    I: Fn() -> T + Sync + Send,
+    S: Fn(&mut T, DVector<f64>) -> OptimizerResult + Sync + Send,
+{
+    let mut rng = StdRng::seed_from_u64(rp.seed);
+    let (lower, upper) = (rp.lower.min(rp.upper), rp.lower.max(rp.upper));
+    let starts: Vec<DVector<f64>> = (0..rp.count)
+        .map(|_| DVector::from_fn(n, |_, _| rng.gen_range(lower..=upper)))
+        .collect();
+    let results = multi_start(starts, init, solve, stop_flag);
+    let finals = results.iter().map(|r| (r.x.clone(), r.f_x)).collect();
+    let best = results.into_iter().min_by(|a, b| {
+        a.f_x
+            .is_nan()
+            .cmp(&b.f_x.is_nan())
+            .then(a.f_x.total_cmp(&b.f_x))
+    })?;
+    Some(RestartReport { best, finals })
+}
+
+#[derive(Debug)]
+pub struct ErrorRatio {
+    pub iteration: usize,
+    pub error: f64,
+    pub linear: Option<f64>,
+    pub quadratic: Option<f64>,
+}
+
+// Отношения последовательных ошибок e_{k+1}/e_k и e_{k+1}/e_k²: стабилизация
+// первого указывает на линейную сходимость, второго — на квадратичную
+pub fn error_ratios(history: &[(DVector<f64>, f64)], reference: &[f64]) -> Vec<ErrorRatio> {
+    let errors: Vec<f64> = history
+        .iter()
+        .map(|(x, _)| {
+            x.iter()
+                .zip(reference)
+                .map(|(a, b)| (a - b).powi(2))
+                .sum::<f64>()
+                .sqrt()
+        })
+        .collect();
+
+    errors
+        .iter()
+        .enumerate()
+        .map(|(k, &e)| {
+            let prev = if k > 0 { Some(errors[k - 1]) } else { None };
+            let valid = prev.filter(|&p| p > 0.0);
+            ErrorRatio {
+                iteration: k,
+                error: e,
+                linear: valid.map(|p| e / p),
+                quadratic: valid.map(|p| e / (p * p)),
+            }
+        })
+        .collect()
+}
+
+// Собственные значения матрицы Гессе в найденной точке (по возрастанию)
+// и число обусловленности max|λ| / min|λ|
+#[derive(Debug)]
+pub struct HessianDiagnostics {
+    pub eigenvalues: Vec<f64>,
+    pub condition: f64,
+}
+
+impl HessianDiagnostics {
+    // Отрицательное собственное значение: точка седловая, а не минимум.
+    // Значения, малые относительно наибольшего, считаются шумом разностей
+    pub fn is_saddle(&self) -> bool {
+        let scale = self.eigenvalues.iter().fold(0.0_f64, |m, l| m.max(l.abs()));
+        self.eigenvalues.first().is_some_and(|&l| l < -1e-6 * scale)
+    }
+}
+
+// Конечные разности дают почти симметричную матрицу, она симметризуется.
+// None, если в матрице есть NaN или бесконечности
+pub fn hessian_diagnostics(hessian: &DMatrix<f64>) -> Option<HessianDiagnostics> {
+    if hessian.is_empty() || hessian.iter().any(|v| !v.is_finite()) {
+        return None;
+    }
+    let symmetric = (hessian + hessian.transpose()) / 2.0;
+    let mut eigenvalues: Vec<f64> = symmetric.symmetric_eigenvalues().iter().copied().collect();
+    eigenvalues.sort_by(f64::total_cmp);
+    let (min_abs, max_abs) = eigenvalues
+        .iter()
+        .fold((f64::INFINITY, 0.0_f64), |(lo, hi), l| {
+            (lo.min(l.abs()), hi.max(l.abs()))
+        });
+    Some(HessianDiagnostics {
+        eigenvalues,
+        condition: max_abs / min_abs,
+    })
+}
+
+// Сетка значений для авто-настройки шага
+pub const TUNE_INITIAL_STEPS: [f64; 3] = [0.01, 0.1, 1.0];
+pub const TUNE_STEP_DECAYS: [f64; 3] = [0.3, 0.5, 0.8];
+pub const TUNE_STEP_INCREASES: [f64; 3] = [1.0, 1.2, 2.0];
+
+#[derive(Debug)]
+pub struct TuneTrial {
+    pub initial_step: f64,
+    pub step_decay: f64,
+    pub step_increase: f64,
+    pub f_x: f64,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+#[derive(Debug)]
+pub struct TuneReport {
+    pub trials: Vec<TuneTrial>,
+    pub best: usize,
+}
+
+// Лучше то значение f, что меньше; при практически равных f — меньше итераций
+fn better_trial(a: &TuneTrial, b: &TuneTrial) -> bool {
+    let scale = 1.0 + a.f_x.abs().max(b.f_x.abs());
+    if (a.f_x - b.f_x).abs() <= 1e-6 * scale {
+        a.iterations < b.iterations
+    } else {
+        a.f_x < b.f_x
+    }
+}
+
+// Перебор по сетке параметров шага для одномерного поиска make_line_search;
+// каждый запуск ограничен бюджетом итераций base.max_iterations. Возвращает
+// отчёт и результат лучшего запуска
+pub fn tune_step_params(
+    initial_point: DVector<f64>,
+    f: &ObjectiveFn,
+    grad: &GradientFn,
+    base: &DescentParams,
+    make_line_search: &LineSearchFactory,
+    stop_flag: Arc<AtomicBool>,
+) -> Option<(TuneReport, OptimizerResult)> {
+    let mut trials = Vec::new();
+    let mut best: Option<(usize, OptimizerResult)> = None;
+    let params = DescentParams {
+        verbose: false,
+        ..base.clone()
+    };
+
+    for &initial_step in &TUNE_INITIAL_STEPS {
+        for &step_decay in &TUNE_STEP_DECAYS {
+            for &step_increase in &TUNE_STEP_INCREASES {
+                if stop_flag.load(Ordering::SeqCst) {
+                    break;
+                }
+                let params = DescentParams {
+                    initial_step,
+                    step_decay,
+                    step_increase,
+                    ..params.clone()
+                };
+                let line_search = make_line_search(&params);
+                let result = gradient_descent(
+                    initial_point.clone(),
+                    f,
+                    grad,
+                    &params,
+                    &*line_search,
+                    stop_flag.clone(),
+                );
+                if result.termination == TerminationReason::UserStopped {
+                    break;
+                }
+                let trial = TuneTrial {
+                    initial_step,
+                    step_decay,
+                    step_increase,
+                    f_x: result.f_x,
+                    iterations: result.iterations,
+                    converged: result.termination.is_converged(),
+                };
+                let is_better = match &best {
+                    Some((k, _)) => !trial.f_x.is_nan() && better_trial(&trial, &trials[*k]),
+                    None => !trial.f_x.is_nan(),
+                };
+                trials.push(trial);
+                if is_better {
+                    best = Some((trials.len() - 1, result));
+                }
+            }
+        }
+    }
+
+    best.map(|(best, best_result)| (TuneReport { trials, best }, best_result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::line_search::BacktrackingArmijo;
+
+    fn params(max_iterations: usize) -> DescentParams {
+        DescentParams {
+            max_iterations,
+            ..DescentParams::default()
+        }
+    }
+
+    type Problem = (Box<ObjectiveFn<'static>>, Box<GradientFn<'static>>);
+
+    fn no_stop() -> Arc<AtomicBool> {
+        Arc::new(AtomicBool::new(false))
+    }
+
+    fn sqrt_1d() -> Problem {
+        (
+            Box::new(|x: &DVector<f64>| x[0].sqrt()),
+            Box::new(|x: &DVector<f64>| DVector::from_element(1, 0.5 / x[0].sqrt())),
+        )
+    }
+
+    fn sqrt_plus_square() -> Problem {
+        (
+            Box::new(|x: &DVector<f64>| x[0].sqrt() + x[1] * x[1]),
+            Box::new(|x: &DVector<f64>| DVector::from_vec(vec![0.5 / x[0].sqrt(), 2.0 * x[1]])),
+        )
+    }
+
+    fn rosenbrock() -> Problem {
+        (
+            Box::new(|x: &DVector<f64>| {
+                (1.0 - x[0]).powi(2) + 100.0 * (x[1] - x[0] * x[0]).powi(2)
+            }),
+            Box::new(|x: &DVector<f64>| {
+                DVector::from_vec(vec![
+                    -2.0 * (1.0 - x[0]) - 400.0 * x[0] * (x[1] - x[0] * x[0]),
+                    200.0 * (x[1] - x[0] * x[0]),
+                ])
+            }),
+        )
+    }
+
+    #[test]
+    fn thinned_history_keeps_final_point_and_iteration_numbers() {
+        let (f, grad) = rosenbrock();
+        let params = DescentParams {
+            max_iterations: 5000,
+            tolerance: 1e-12,
+            max_history_points: 100,
+            ..DescentParams::default()
+        };
+        let res = gradient_descent(
+            DVector::from_vec(vec![-1.2, 1.0]),
+            &*f,
+            &*grad,
+            &params,
+            &AdaptiveHalving::new(1e-4, 0.5, 1.0, 1e-4),
+            no_stop(),
+        );
+        assert_eq!(res.iterations, 5000);
+        assert!(res.history.len() <= 101, "len = {}", res.history.len());
+        assert_eq!(res.history.last().unwrap().0, res.x);
+        assert_eq!(res.history_iterations.len(), res.history.len());
+        assert_eq!(res.iteration_at(res.history.len() - 1), res.iterations);
+        assert_eq!(res.grad_norms.len(), res.history.len());
+        assert_eq!(*res.grad_norms.last().unwrap(), grad(&res.x).norm());
+        assert!(res.step_sizes.len() < res.history.len());
+    }
+
+    #[test]
+    fn tuning_runs_the_given_line_search() {
+        let f = |x: &DVector<f64>| x.dot(x);
+        let grad = |x: &DVector<f64>| 2.0 * x;
+        let built = Cell::new(0);
+        let make_line_search = |p: &DescentParams| -> Box<dyn LineSearch> {
+            built.set(built.get() + 1);
+            Box::new(GoldenSection {
+                initial_step: p.initial_step,
+                tol: 1e-8,
+                max_step: f64::INFINITY,
+            })
+        };
+        let (report, best) = tune_step_params(
+            DVector::from_vec(vec![3.0, -1.0]),
+            &f,
+            &grad,
+            &params(50),
+            &make_line_search,
+            no_stop(),
+        )
+        .unwrap();
+        assert_eq!(built.get(), report.trials.len());
+        // Точный поиск на квадратичной функции сходится за несколько итераций
+        assert!(best.iterations <= 3, "iterations = {}", best.iterations);
+    }
+
+    #[test]
+    fn weighted_sum_endpoints_match_single_objectives() {
+        let f1 = |x: &DVector<f64>| (x[0] - 1.0).powi(2) + x[1] * x[1];
+        let grad1 = |x: &DVector<f64>| DVector::from_vec(vec![2.0 * (x[0] - 1.0), 2.0 * x[1]]);
+        // Вторая функция не определена при x1 < 0 и при w = 1 не вычисляется
+        let f2 = |x: &DVector<f64>| x[0].sqrt() + x[1] * x[1];
+        let grad2 = |x: &DVector<f64>| DVector::from_vec(vec![0.5 / x[0].sqrt(), 2.0 * x[1]]);
+        let params = params(500);
+        let make_line_search = |p: &DescentParams| -> Box<dyn LineSearch> {
+            Box::new(BacktrackingArmijo::new(
+                p.initial_step,
+                p.armijo_c1,
+                p.step_decay,
+            ))
+        };
+        let start = DVector::from_vec(vec![-2.0, 1.0]);
+        let front = weighted_sum_sweep(
+            start.clone(),
+            &f1,
+            &f2,
+            &grad1,
+            &grad2,
+            2,
+            &params,
+            &make_line_search,
+            no_stop(),
+        );
+        let alone = gradient_descent(
+            start,
+            &f1,
+            &grad1,
+            &params,
+            &*make_line_search(&params),
+            no_stop(),
+        );
+        assert_eq!(front.len(), 2);
+        assert_eq!(front[1].weight, 1.0);
+        assert_eq!(front[1].x, alone.x);
+        assert_eq!(front[1].iterations, alone.iterations);
+    }
+
+    #[test]
+    fn second_order_methods_stop_on_nan_gradient() {
+        let (f, grad) = sqrt_plus_square();
+        let hess = |x: &DVector<f64>| {
+            DMatrix::from_row_slice(2, 2, &[-0.25 * x[0].powf(-1.5), 0.0, 0.0, 2.0])
+        };
+        let start = || DVector::from_vec(vec![0.0, 1.0]);
+        let p = params(100);
+        let results = [
+            conjugate_gradient(start(), &*f, &*grad, &p, None, no_stop()),
+            bfgs(start(), &*f, &*grad, &p, no_stop()),
+            lbfgs(start(), &*f, &*grad, &p, 5, no_stop()),
+            newton_descent(start(), &*f, &*grad, &hess, &p, no_stop()),
+        ];
+        for res in results {
+            assert_eq!(res.termination, TerminationReason::GradientNaN);
+            assert_eq!(res.x, start());
+        }
+    }
+
+    fn perturbed(max_iterations: usize) -> DescentParams {
+        DescentParams {
+            perturb: Some(PerturbParams::default()),
+            ..params(max_iterations)
+        }
+    }
+
+    #[test]
+    fn perturbation_skips_converged_minimum() {
+        // Шаг 0.5 приводит в минимум за одну итерацию, до срабатывания patience
+        let f = |x: &DVector<f64>| x.dot(x);
+        let grad = |x: &DVector<f64>| 2.0 * x;
+        let res = gradient_descent(
+            DVector::from_vec(vec![1.0, -2.0]),
+            &f,
+            &grad,
+            &perturbed(1000),
+            &AdaptiveHalving::new(0.5, 0.5, 1.0, 1.0),
+            no_stop(),
+        );
+        assert!(res.perturbations.is_empty());
+        assert_eq!(res.termination, TerminationReason::Converged);
+    }
+
+    #[test]
+    fn perturbation_escapes_saddle() {
+        // Вдоль x2 = 0 градиент по x2 равен нулю, и спуск приходит в седло (0, 0)
+        let f = |x: &DVector<f64>| x[0] * x[0] + x[1].powi(4) / 4.0 - x[1] * x[1] / 2.0;
+        let grad = |x: &DVector<f64>| DVector::from_vec(vec![2.0 * x[0], x[1].powi(3) - x[1]]);
+        let res = gradient_descent(
+            DVector::from_vec(vec![1.0, 0.0]),
+            &f,
+            &grad,
+            &perturbed(5000),
+            &AdaptiveHalving::new(0.25, 0.5, 1.0, 1.0),
+            no_stop(),
+        );
+        assert!(!res.perturbations.is_empty());
+        assert!((res.f_x + 0.25).abs() < 1e-6, "f = {}", res.f_x);
+    }
+
+    #[test]
+    fn trust_region_cg_shrinks_radius_outside_domain() {
+        let (f, grad) = sqrt_1d();
+        let res = trust_region_cg(
+            DVector::from_element(1, 0.5),
+            &*f,
+            &*grad,
+            &params(200),
+            &TrustRegionParams::default(),
+            no_stop(),
+        );
+        assert!(res.x[0] < 1e-3, "x = {}", res.x[0]);
+        assert_ne!(res.termination, TerminationReason::MaxIterations);
+    }
+
+    #[test]
+    fn trust_region_dogleg_shrinks_radius_outside_domain() {
+        let (f, grad) = sqrt_1d();
+        let hess = |x: &DVector<f64>| DMatrix::from_element(1, 1, -0.25 * x[0].powf(-1.5));
+        let res = trust_region_dogleg(
+            DVector::from_element(1, 0.5),
+            &*f,
+            &*grad,
+            &hess,
+            &params(200),
+            &TrustRegionParams::default(),
+            no_stop(),
+        );
+        assert!(res.x[0] < 1e-3, "x = {}", res.x[0]);
+        assert_ne!(res.termination, TerminationReason::MaxIterations);
+    }
+
+    #[test]
+    fn trust_region_cg_stops_on_nan_gradient() {
+        let (f, grad) = sqrt_plus_square();
+        let res = trust_region_cg(
+            DVector::from_vec(vec![0.0, 1.0]),
+            &*f,
+            &*grad,
+            &params(200),
+            &TrustRegionParams::default(),
+            no_stop(),
+        );
+        assert_eq!(res.termination, TerminationReason::GradientNaN);
+        assert_eq!(res.iterations, 0);
+    }
+
+    fn parsed(expr: &str, num_vars: usize) -> Problem {
+        let func = crate::parser::ParsedFunction::new(expr, num_vars).unwrap();
+        let grad_func = func.clone();
+        (
+            Box::new(move |x: &DVector<f64>| func.eval(x).unwrap_or(f64::NAN)),
+            Box::new(move |x: &DVector<f64>| {
+                grad_func
+                    .gradient(x, 1e-6, crate::parser::GradientMode::Central)
+                    .unwrap_or_else(|_| DVector::from_element(x.len(), f64::NAN))
+            }),
+        )
+    }
+
+    fn default_search(params: &DescentParams) -> AdaptiveHalving {
+        AdaptiveHalving::new(
+            params.initial_step,
+            params.step_decay,
+            params.step_increase,
+            params.max_step,
+        )
+        .with_max_backtracks(params.max_backtracks)
+    }
+
+    #[test]
+    fn one_dimensional_problem_converges() {
+        let (f, grad) = parsed("x1^2", 1);
+        let p = params(1000);
+        let res = gradient_descent(
+            DVector::from_element(1, 5.0),
+            &*f,
+            &*grad,
+            &p,
+            &default_search(&p),
+            no_stop(),
+        );
+        assert!(res.termination.is_converged());
+        assert!(res.x[0].abs() < 1e-5, "x = {}", res.x[0]);
+        assert!(res.history.iter().all(|(x, _)| x.len() == 1));
+    }
+
+    fn parsed_hessian(expr: &str, num_vars: usize) -> Box<HessianFn<'static>> {
+        let func = crate::parser::ParsedFunction::new(expr, num_vars).unwrap();
+        Box::new(move |x: &DVector<f64>| {
+            func.hessian(x, 1e-4)
+                .unwrap_or_else(|_| DMatrix::from_element(x.len(), x.len(), f64::NAN))
+        })
+    }
+
+    #[test]
+    fn newton_needs_far_fewer_iterations_than_gradient_descent() {
+        let expr = "x1^2 + 3*x2^2";
+        let (f, grad) = parsed(expr, 2);
+        let hess = parsed_hessian(expr, 2);
+        let p = params(1000);
+        let start = DVector::from_vec(vec![4.0, -3.0]);
+        let newton = newton_descent(start.clone(), &*f, &*grad, &*hess, &p, no_stop());
+        let gd = gradient_descent(start, &*f, &*grad, &p, &default_search(&p), no_stop());
+        assert!(newton.termination.is_converged());
+        assert!(gd.termination.is_converged());
+        assert!(newton.iterations <= 3, "Ньютон: {}", newton.iterations);
+        assert!(
+            5 * newton.iterations < gd.iterations,
+            "спуск: {}",
+            gd.iterations
+        );
+    }
+
+    #[test]
+    fn conjugate_gradient_beats_gradient_descent_on_rosenbrock() {
+        let (f, grad) = parsed("100*(x2 - x1^2)^2 + (1 - x1)^2", 2);
+        let p = DescentParams {
+            tolerance: 1e-5,
+            ..params(50_000)
+        };
+        // Из (-1, 1) первый же шаг вдоль антиградиента (4, 0) длины 0.5 попадает
+        // точно в минимум, поэтому берётся классическая точка (-1.2, 1)
+        let start = DVector::from_vec(vec![-1.2, 1.0]);
+        let cg = conjugate_gradient(start.clone(), &*f, &*grad, &p, None, no_stop());
+        let gd = gradient_descent(start, &*f, &*grad, &p, &default_search(&p), no_stop());
+        assert!(cg.termination.is_converged(), "{:?}", cg.termination);
+        assert!((&cg.x - DVector::from_vec(vec![1.0, 1.0])).norm() < 1e-3);
+        assert!(
+            cg.iterations < gd.iterations,
+            "КГ {} / спуск {}",
+            cg.iterations,
+            gd.iterations
+        );
+    }
+
+    #[test]
+    fn bfgs_converges_on_4d_quadratic() {
+        let scales = [1.0, 4.0, 9.0, 25.0];
+        let f = move |x: &DVector<f64>| (0..4).map(|i| scales[i] * x[i] * x[i]).sum::<f64>();
+        let grad = move |x: &DVector<f64>| DVector::from_fn(4, |i, _| 2.0 * scales[i] * x[i]);
+        let res = bfgs(
+            DVector::from_vec(vec![1.0, -1.0, 2.0, 0.5]),
+            &f,
+            &grad,
+            &params(200),
+            no_stop(),
+        );
+        assert!(res.termination.is_converged());
+        assert!(res.x.amax() < 1e-6);
+        // На квадратичной функции BFGS восстанавливает H⁻¹ примерно за n шагов
+        assert!(res.iterations <= 20, "iterations = {}", res.iterations);
+    }
+
+    #[test]
+    fn momentum_needs_fewer_iterations_on_ravine() {
+        let f = |x: &DVector<f64>| x[0] * x[0] + 50.0 * x[1] * x[1];
+        let grad = |x: &DVector<f64>| DVector::from_vec(vec![2.0 * x[0], 100.0 * x[1]]);
+        let params = DescentParams {
+            max_iterations: 5000,
+            initial_step: 0.01,
+            ..DescentParams::default()
+        };
+        let run = |momentum: f64| {
+            momentum_descent(
+                DVector::from_vec(vec![5.0, 1.0]),
+                &f,
+                &grad,
+                &params,
+                &MomentumParams {
+                    momentum,
+                    nesterov: false,
+                },
+                no_stop(),
+            )
+        };
+        let plain = run(0.0);
+        let heavy = run(0.9);
+        assert_eq!(plain.termination, TerminationReason::Converged);
+        assert_eq!(heavy.termination, TerminationReason::Converged);
+        assert!(
+            heavy.iterations < plain.iterations,
+            "{} >= {}",
+            heavy.iterations,
+            plain.iterations
+        );
+        assert!(heavy.x.norm() < 1e-5);
+    }
+
+    #[test]
+    fn adam_first_step_is_bias_corrected() {
+        let f = |x: &DVector<f64>| x.dot(x);
+        let grad = |x: &DVector<f64>| 2.0 * x;
+        let ap = AdamParams::default();
+        let res = adam(
+            DVector::from_vec(vec![3.0, -1.0]),
+            &f,
+            &grad,
+            &params(1),
+            &ap,
+            no_stop(),
+        );
+        // С поправкой m̂ = g, v̂ = g², поэтому первый шаг равен learning_rate по каждой координате
+        let first = &res.history[1].0;
+        assert!((first[0] - (3.0 - ap.learning_rate)).abs() < 1e-6);
+        assert!((first[1] - (-1.0 + ap.learning_rate)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn adam_converges_on_quadratic() {
+        let f = |x: &DVector<f64>| (x[0] - 1.0).powi(2) + 10.0 * (x[1] + 2.0).powi(2);
+        let grad =
+            |x: &DVector<f64>| DVector::from_vec(vec![2.0 * (x[0] - 1.0), 20.0 * (x[1] + 2.0)]);
+        let res = adam(
+            DVector::from_vec(vec![-3.0, 3.0]),
+            &f,
+            &grad,
+            &params(20000),
+            &AdamParams::default(),
+            no_stop(),
+        );
+        assert_eq!(res.termination, TerminationReason::Converged);
+        assert!((res.x[0] - 1.0).abs() < 1e-4 && (res.x[1] + 2.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn nelder_mead_minimizes_nonsmooth_function() {
+        // Градиент |x1| + |x2| не определён в минимуме, симплексу он не нужен
+        let f = |x: &DVector<f64>| x[0].abs() + x[1].abs();
+        let res = nelder_mead(
+            DVector::from_vec(vec![2.0, -3.0]),
+            &f,
+            &params(2000),
+            &NelderMeadParams::default(),
+            no_stop(),
+        );
+        assert_eq!(res.termination, TerminationReason::Converged);
+        assert!(res.f_x < 1e-4, "f = {}", res.f_x);
+        assert_eq!(res.grad_evals, 0);
+    }
+
+    #[test]
+    fn gradient_descent_reports_termination_reason() {
+        let f = |x: &DVector<f64>| x.dot(x);
+        let grad = |x: &DVector<f64>| 2.0 * x;
+        // Градиент с неверным знаком: вдоль «антиградиента» f только растёт
+        let wrong_grad = |x: &DVector<f64>| -2.0 * x;
+        let start = || DVector::from_vec(vec![3.0, -1.0]);
+        let run = |grad: &GradientFn, max_iterations| {
+            gradient_descent(
+                start(),
+                &f,
+                grad,
+                &params(max_iterations),
+                &AdaptiveHalving::new(0.1, 0.5, 1.0, 1.0),
+                no_stop(),
+            )
+            .termination
+        };
+        assert_eq!(run(&grad, 1000), TerminationReason::Converged);
+        assert_eq!(run(&grad, 3), TerminationReason::MaxIterations);
+        assert_eq!(run(&wrong_grad, 1000), TerminationReason::LineSearchFailed);
+    }
+
+    #[test]
+    fn recorded_grad_norm_matches_final_point() {
+        let (f, grad) = rosenbrock();
+        let res = gradient_descent(
+            DVector::from_vec(vec![-1.2, 1.0]),
+            &*f,
+            &*grad,
+            &params(200),
+            &AdaptiveHalving::new(1e-3, 0.5, 1.0, 1e-3),
+            no_stop(),
+        );
+        assert_eq!(res.grad_norms.len(), res.history.len());
+        assert!((res.grad_norms.last().unwrap() - grad(&res.x).norm()).abs() < 1e-12);
+        for (k, step) in res.step_sizes.iter().enumerate() {
+            let moved = (&res.history[k + 1].0 - &res.history[k].0).norm();
+            assert!((step - moved).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn bounded_descent_stops_on_active_bound() {
+        let f = |x: &DVector<f64>| (x[0] - 10.0).powi(2);
+        let grad = |x: &DVector<f64>| DVector::from_element(1, 2.0 * (x[0] - 10.0));
+        let params = DescentParams {
+            bounds: Some(Bounds {
+                lower: DVector::from_element(1, f64::NEG_INFINITY),
+                upper: DVector::from_element(1, 3.0),
+            }),
+            ..params(1000)
+        };
+        let res = gradient_descent(
+            DVector::from_element(1, 0.0),
+            &f,
+            &grad,
+            &params,
+            &AdaptiveHalving::new(0.1, 0.5, 1.2, 1.0),
+            no_stop(),
+        );
+        // Градиент на границе равен -14, но проектированный градиент нулевой
+        assert_eq!(res.termination, TerminationReason::Converged);
+        assert_eq!(res.x[0], 3.0);
+        assert!(res.iterations < 100, "iterations = {}", res.iterations);
+    }
+
+    #[test]
+    fn parallel_multi_start_matches_sequential() {
+        let expr = "(x1 - 1)^2 + 2*(x2 + 0.5)^2";
+        let p = params(1000);
+        let solve = |(f, grad): &mut Problem, start: DVector<f64>| {
+            gradient_descent(start, &**f, &**grad, &p, &default_search(&p), no_stop())
+        };
+        let starts: Vec<DVector<f64>> = (0..8)
+            .map(|k| DVector::from_vec(vec![k as f64 - 4.0, 3.0 - k as f64]))
+            .collect();
+        let parallel = multi_start(
+            starts.clone(),
+            || parsed(expr, 2),
+            solve,
+            &AtomicBool::new(false),
+        );
+        let mut problem = parsed(expr, 2);
+        let sequential: Vec<OptimizerResult> = starts
+            .into_iter()
+            .map(|start| solve(&mut problem, start))
+            .collect();
+        assert_eq!(parallel.len(), sequential.len());
+        for (a, b) in parallel.iter().zip(&sequential) {
+            assert_eq!(a.history[0].0, b.history[0].0);
+            assert_eq!(a.x, b.x);
+            assert_eq!(a.iterations, b.iterations);
+        }
+    }
+
+    #[test]
+    fn ftol_stops_where_gradient_test_cannot() {
+        // ||∇f|| = 1 во всех точках, кроме x1 = 0, и критерий по градиенту не срабатывает
+        let f = |x: &DVector<f64>| 1.0 + x[0].abs();
+        let grad = |x: &DVector<f64>| DVector::from_element(1, x[0].signum());
+        let start = || DVector::from_element(1, 0.7);
+        let run = |ftol| {
+            let p = DescentParams {
+                ftol,
+                ..params(2000)
+            };
+            gradient_descent(start(), &f, &grad, &p, &default_search(&p), no_stop())
+        };
+        let without = run(0.0);
+        assert_ne!(without.termination, TerminationReason::Converged);
+        let with = run(1e-6);
+        assert_eq!(with.termination, TerminationReason::FunctionTolerance);
+        assert!(with.grad_norms.iter().all(|&g| g == 1.0));
+        assert!((with.f_x - 1.0).abs() < 1e-3, "f = {}", with.f_x);
+    }
+
+    #[test]
+    fn barzilai_borwein_beats_fixed_step() {
+        let f = |x: &DVector<f64>| x[0] * x[0] + 10.0 * x[1] * x[1];
+        let grad = |x: &DVector<f64>| DVector::from_vec(vec![2.0 * x[0], 20.0 * x[1]]);
+        let p = DescentParams {
+            initial_step: 0.09,
+            ..params(5000)
+        };
+        let start = || DVector::from_vec(vec![5.0, 1.0]);
+        // Шаг 0.09 близок к пределу устойчивости 2/L = 0.1 и не меняется
+        let fixed = gradient_descent(
+            start(),
+            &f,
+            &grad,
+            &p,
+            &AdaptiveHalving::new(0.09, 0.5, 1.0, 0.09),
+            no_stop(),
+        );
+        for variant in [BbVariant::Bb1, BbVariant::Bb2] {
+            let bb = bb_gradient_descent(start(), &f, &grad, &p, variant, no_stop());
+            assert_eq!(bb.termination, TerminationReason::Converged);
+            assert!(
+                bb.iterations < fixed.iterations,
+                "{:?}: {} >= {}",
+                variant,
+                bb.iterations,
+                fixed.iterations
+            );
+        }
+    }
+
+    #[test]
+    fn start_outside_domain_reports_non_finite_value() {
+        let (f, grad) = parsed("ln(x1)", 1);
+        let p = params(100);
+        let start = || DVector::from_element(1, -1.0);
+        let results = [
+            gradient_descent(start(), &*f, &*grad, &p, &default_search(&p), no_stop()),
+            conjugate_gradient(start(), &*f, &*grad, &p, None, no_stop()),
+            bfgs(start(), &*f, &*grad, &p, no_stop()),
+        ];
+        for res in results {
+            assert_eq!(res.termination, TerminationReason::NonFiniteValue);
+            assert_eq!(res.iterations, 0);
+            assert_eq!(res.x[0], -1.0);
+        }
+    }
+
+    #[test]
+    fn rmsprop_progresses_evenly_across_scales() {
+        let f = |x: &DVector<f64>| x[0] * x[0] + 100.0 * x[1] * x[1];
+        let grad = |x: &DVector<f64>| DVector::from_vec(vec![2.0 * x[0], 200.0 * x[1]]);
+        let start = || DVector::from_vec(vec![1.0, 1.0]);
+        let p = params(20);
+        // Постоянный шаг ограничен крутой координатой: 2/L = 0.01
+        let fixed = gradient_descent(
+            start(),
+            &f,
+            &grad,
+            &p,
+            &AdaptiveHalving::new(0.009, 0.5, 1.0, 0.009),
+            no_stop(),
+        );
+        let rms = rmsprop(start(), &f, &grad, &p, &RmsPropParams::default(), no_stop());
+        let moved = |x: &DVector<f64>| (1.0 - x[0].abs(), 1.0 - x[1].abs());
+        let (fixed_x1, fixed_x2) = moved(&fixed.x);
+        let (rms_x1, rms_x2) = moved(&rms.x);
+        assert!(fixed_x2 > 3.0 * fixed_x1, "{} {}", fixed_x1, fixed_x2);
+        // Деление на sqrt(s) выравнивает шаги: координаты сдвигаются одинаково
+        assert!((rms_x1 / rms_x2 - 1.0).abs() < 0.1, "{} {}", rms_x1, rms_x2);
+    }
+
+    #[test]
+    fn exact_steepest_descent_solves_round_quadratic_in_one_step() {
+        let f = |x: &DVector<f64>| x.dot(x);
+        let grad = |x: &DVector<f64>| 2.0 * x;
+        let res = steepest_descent_exact(
+            DVector::from_vec(vec![3.0, -4.0]),
+            &f,
+            &grad,
+            &params(100),
+            10.0,
+            no_stop(),
+        );
+        // Антиградиент ведёт прямо в минимум, и сразу после первого шага g = 0
+        assert_eq!(res.termination, TerminationReason::Converged);
+        assert_eq!(res.iterations, 1);
+        assert!(res.x.norm() < 1e-6);
+    }
+
+    #[test]
+    fn line_search_evaluations_are_counted() {
+        let (f, grad) = rosenbrock();
+        let p = params(100);
+        let res = gradient_descent(
+            DVector::from_vec(vec![-1.2, 1.0]),
+            &*f,
+            &*grad,
+            &p,
+            &default_search(&p),
+            no_stop(),
+        );
+        // Каждая итерация вычисляет f хотя бы раз, а при дроблении шага — несколько
+        assert!(
+            res.f_evals > res.iterations,
+            "{} <= {}",
+            res.f_evals,
+            res.iterations
+        );
+        assert!(res.grad_evals >= res.iterations);
+    }
+
+    #[test]
+    fn penalty_method_finds_constrained_minimum() {
+        let f = |x: &DVector<f64>| x.dot(x);
+        let grad = |x: &DVector<f64>| 2.0 * x;
+        let g = |x: &DVector<f64>| x[0] + x[1] - 1.0;
+        let grad_g = |x: &DVector<f64>| DVector::from_element(x.len(), 1.0);
+        let constraints: [(&ObjectiveFn, &GradientFn); 1] = [(&g, &grad_g)];
+        let p = params(10_000);
+        let output = penalty_minimize(
+            DVector::from_vec(vec![2.0, -1.0]),
+            &f,
+            &grad,
+            &constraints,
+            &p,
+            &default_search(&p),
+            &PenaltyParams::default(),
+            no_stop(),
+        );
+        let x = &output.result.x;
+        assert!(
+            (x[0] - 0.5).abs() < 1e-3 && (x[1] - 0.5).abs() < 1e-3,
+            "x = {}",
+            x
+        );
+        assert_eq!(output.violations.len(), 1);
+        assert!(output.violations[0].abs() < 1e-3);
+        // История хранит исходную f без штрафа
+        assert!((output.result.f_x - f(x)).abs() < 1e-12);
+    }
+
+    fn double_well(x: &DVector<f64>) -> f64 {
+        // Минимумы около x1 = -1 (f ≈ -0.3) и x1 = 1 (f ≈ 0.3)
+        (x[0] * x[0] - 1.0).powi(2) + 0.3 * x[0] + x[1] * x[1]
+    }
+
+    #[test]
+    fn annealing_escapes_worse_basin() {
+        let res = simulated_annealing(
+            DVector::from_vec(vec![1.0, 0.0]),
+            &double_well,
+            &params(5000),
+            &AnnealingParams::default(),
+            no_stop(),
+        );
+        assert!(res.x[0] < -0.8, "x = {}", res.x);
+        assert!(res.f_x < -0.2, "f = {}", res.f_x);
+    }
+
+    #[test]
+    fn same_seed_reproduces_history() {
+        let run = |seed| {
+            simulated_annealing(
+                DVector::from_vec(vec![1.0, 0.0]),
+                &double_well,
+                &params(500),
+                &AnnealingParams {
+                    seed,
+                    ..AnnealingParams::default()
+                },
+                no_stop(),
+            )
+        };
+        let bits = |res: &OptimizerResult| -> Vec<u64> {
+            res.history
+                .iter()
+                .flat_map(|(x, f)| x.iter().chain(std::iter::once(f)).map(|v| v.to_bits()))
+                .collect()
+        };
+        assert_eq!(bits(&run(7)), bits(&run(7)));
+        assert_ne!(bits(&run(7)), bits(&run(8)));
+    }
+
+    #[test]
+    fn hessian_condition_number_of_scaled_quadratic() {
+        let func = crate::parser::ParsedFunction::new("x1^2 + 100*x2^2", 2).unwrap();
+        let h = func
+            .hessian(&DVector::from_vec(vec![0.3, -0.2]), 1e-4)
+            .unwrap();
+        let diag = hessian_diagnostics(&h).unwrap();
+        assert!(
+            (diag.condition - 100.0).abs() < 1e-3,
+            "cond = {}",
+            diag.condition
+        );
+        assert!(!diag.is_saddle());
+        let saddle =
+            hessian_diagnostics(&DMatrix::from_diagonal(&DVector::from_vec(vec![2.0, -2.0])))
+                .unwrap();
+        assert!(saddle.is_saddle());
+    }
+
+    #[test]
+    fn dogleg_converges_on_rosenbrock_where_descent_stalls() {
+        let (f, grad) = rosenbrock();
+        let hess = parsed_hessian("(1-x1)^2 + 100*(x2-x1^2)^2", 2);
+        let p = params(500);
+        let start = || DVector::from_vec(vec![-1.2, 1.0]);
+        let dogleg = trust_region_dogleg(
+            start(),
+            &*f,
+            &*grad,
+            &*hess,
+            &p,
+            &TrustRegionParams::default(),
+            no_stop(),
+        );
+        let descent = gradient_descent(start(), &*f, &*grad, &p, &default_search(&p), no_stop());
+        assert!(
+            dogleg.termination.is_converged(),
+            "{:?}",
+            dogleg.termination
+        );
+        assert!((&dogleg.x - DVector::from_vec(vec![1.0, 1.0])).norm() < 1e-4);
+        // За те же 500 итераций спуск остаётся в изогнутом овраге
+        assert_eq!(descent.termination, TerminationReason::MaxIterations);
+        assert!(descent.f_x > 1e3 * dogleg.f_x.max(1e-12));
+    }
+
+    #[test]
+    fn progress_callback_fires_every_k_iterations() {
+        let (f, grad) = rosenbrock();
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        let p = DescentParams {
+            progress: Some(Progress {
+                every: 10,
+                callback: Arc::new(move |iter: usize, _: &DVector<f64>, _: f64| {
+                    sink.lock().unwrap().push(iter)
+                }),
+            }),
+            ..params(95)
+        };
+        let res = gradient_descent(
+            DVector::from_vec(vec![-1.2, 1.0]),
+            &*f,
+            &*grad,
+            &p,
+            &default_search(&p),
+            no_stop(),
+        );
+        assert_eq!(res.iterations, 95);
+        let expected: Vec<usize> = (1..=9).map(|k| 10 * k).collect();
+        assert_eq!(*seen.lock().unwrap(), expected);
+    }
+
+    #[test]
+    fn lbfgs_beats_gradient_descent_in_10d() {
+        // Σ i·(x_i - 1)², собственные значения от 2 до 20
+        let f = |x: &DVector<f64>| {
+            x.iter()
+                .enumerate()
+                .map(|(i, v)| (i + 1) as f64 * (v - 1.0).powi(2))
+                .sum::<f64>()
+        };
+        let grad =
+            |x: &DVector<f64>| DVector::from_fn(10, |i, _| 2.0 * (i + 1) as f64 * (x[i] - 1.0));
+        let p = params(10_000);
+        let start = || DVector::zeros(10);
+        let quasi = lbfgs(start(), &f, &grad, &p, 5, no_stop());
+        let descent = gradient_descent(start(), &f, &grad, &p, &default_search(&p), no_stop());
+        assert_eq!(quasi.termination, TerminationReason::Converged);
+        assert!((&quasi.x - DVector::from_element(10, 1.0)).amax() < 1e-6);
+        assert!(
+            quasi.iterations < descent.iterations,
+            "{} >= {}",
+            quasi.iterations,
+            descent.iterations
+        );
+    }
+
+    #[test]
+    fn larger_max_step_speeds_up_shallow_quadratic() {
+        let f = |x: &DVector<f64>| 0.01 * x.dot(x);
+        let grad = |x: &DVector<f64>| 0.02 * x;
+        let run = |max_step| {
+            let p = DescentParams {
+                max_step,
+                ..params(10_000)
+            };
+            gradient_descent(
+                DVector::from_vec(vec![3.0, -4.0]),
+                &f,
+                &grad,
+                &p,
+                &default_search(&p),
+                no_stop(),
+            )
+        };
+        let capped = run(1.0);
+        let free = run(100.0);
+        assert_eq!(free.termination, TerminationReason::Converged);
+        assert!(
+            free.iterations * 5 < capped.iterations,
+            "{} vs {}",
+            free.iterations,
+            capped.iterations
+        );
+    }
+
+    #[test]
+    fn powell_restart_converges_on_non_quadratic() {
+        let (f, grad) = parsed("x1^4 + (x1 - 2*x2)^2 + exp(x3) - x3 + (x3 - x1)^2", 3);
+        let p = DescentParams {
+            verbose: true,
+            ..params(2000)
+        };
+        let start = || DVector::from_vec(vec![2.0, -1.0, 3.0]);
+        let plain = conjugate_gradient(start(), &*f, &*grad, &p, None, no_stop());
+        let powell = conjugate_gradient(start(), &*f, &*grad, &p, Some(0.2), no_stop());
+        assert!(
+            powell.termination.is_converged(),
+            "{:?}",
+            powell.termination
+        );
+        assert!(plain.termination.is_converged(), "{:?}", plain.termination);
+        assert!((&powell.x - &plain.x).amax() < 1e-4);
+        // Рестарты Пауэлла случаются только при заданном пороге
+        assert!(powell
+            .log
+            .iter()
+            .any(|line| line.contains("рестарт Пауэлла")));
+        assert!(!plain
+            .log
+            .iter()
+            .any(|line| line.contains("рестарт Пауэлла")));
+    }
+
+    #[test]
+    fn scaled_tolerance_is_independent_of_dimension() {
+        let f = |x: &DVector<f64>| x.dot(x);
+        let grad = |x: &DVector<f64>| 2.0 * x;
+        let run = |n: usize, scale_tolerance| {
+            let p = DescentParams {
+                scale_tolerance,
+                initial_step: 0.1,
+                step_increase: 1.0,
+                ..params(10_000)
+            };
+            gradient_descent(
+                DVector::from_element(n, 1.0),
+                &f,
+                &grad,
+                &p,
+                &default_search(&p),
+                no_stop(),
+            )
+        };
+        // Из точки (1, ..., 1) все координаты убывают одинаково, и RMS градиента
+        // от n не зависит: останов на той же итерации с той же точностью
+        let scaled = [1, 2, 10].map(|n| run(n, true));
+        for res in &scaled {
+            assert_eq!(res.termination, TerminationReason::Converged);
+            assert_eq!(res.iterations, scaled[0].iterations);
+            assert_eq!(res.x.amax(), scaled[0].x.amax());
+        }
+        let raw = run(10, false);
+        assert!(raw.iterations > scaled[2].iterations);
+    }
+
+    #[test]
+    fn warm_restarts_recover_step_on_flat_region() {
+        // Крутая стенка при x1 > 5 и пологая чаша за ней
+        let f = |x: &DVector<f64>| 100.0 * (x[0] - 5.0).max(0.0).powi(2) + 0.01 * x[0] * x[0];
+        let grad = |x: &DVector<f64>| {
+            DVector::from_element(1, 200.0 * (x[0] - 5.0).max(0.0) + 0.02 * x[0])
+        };
+        let run = |restart_period| {
+            let p = DescentParams {
+                restart_period,
+                ..params(300)
+            };
+            // Без роста шага: уменьшенный на стенке шаг остаётся малым
+            gradient_descent(
+                DVector::from_element(1, 7.0),
+                &f,
+                &grad,
+                &p,
+                &AdaptiveHalving::new(1.0, 0.5, 1.0, 1.0),
+                no_stop(),
+            )
+        };
+        let plain = run(0);
+        let restarted = run(20);
+        assert!(
+            restarted.f_x < 0.1 * plain.f_x,
+            "{} vs {}",
+            restarted.f_x,
+            plain.f_x
+        );
+    }
+
+    #[test]
+    fn step_decay_must_lie_strictly_inside_unit_interval() {
+        for (step_decay, valid) in [(0.0, false), (1e-9, true), (0.999, true), (1.0, false)] {
+            let p = DescentParams {
+                step_decay,
+                ..DescentParams::default()
+            };
+            assert_eq!(p.validate().is_ok(), valid, "step_decay = {}", step_decay);
+        }
+    }
+
+    #[test]
+    fn gradient_clipping_saves_backtracking() {
+        // Слагаемое -2·x1 даёт минимум при x1 = ln 2, иначе спуск уходит в -∞ за один шаг
+        let f = |x: &DVector<f64>| x[0].exp() - 2.0 * x[0] + x[1] * x[1];
+        let grad = |x: &DVector<f64>| DVector::from_vec(vec![x[0].exp() - 2.0, 2.0 * x[1]]);
+        let run = |max_grad_norm, max_iterations| {
+            let p = DescentParams {
+                max_grad_norm,
+                ..params(max_iterations)
+            };
+            gradient_descent(
+                DVector::from_vec(vec![10.0, 1.0]),
+                &f,
+                &grad,
+                &p,
+                &default_search(&p),
+                no_stop(),
+            )
+        };
+        // ||∇f|| ≈ e^10: без ограничения первый пробный шаг дробится много раз
+        let raw = run(0.0, 1);
+        let clipped = run(10.0, 1);
+        assert!(
+            clipped.f_evals < raw.f_evals,
+            "{} >= {}",
+            clipped.f_evals,
+            raw.f_evals
+        );
+        let clipped = run(10.0, 1000);
+        assert!(clipped.termination.is_converged());
+        assert!((clipped.x[0] - 2f64.ln()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn newton_steps_agree_with_analytic_and_numeric_hessian() {
+        let expr = "x1^4 + x2^2";
+        let func = crate::parser::ParsedFunction::new(expr, 2).unwrap();
+        assert!(func.has_analytic_hessian());
+        let analytic = |x: &DVector<f64>| func.eval_hessian(x).unwrap();
+        assert_eq!(
+            analytic(&DVector::from_vec(vec![1.0, 3.0])),
+            DMatrix::from_row_slice(2, 2, &[12.0, 0.0, 0.0, 2.0])
+        );
+        let numeric = parsed_hessian(expr, 2);
+        let (f, grad) = parsed(expr, 2);
+        let p = params(100);
+        let start = || DVector::from_vec(vec![2.0, -1.0]);
+        let exact = newton_descent(start(), &*f, &*grad, &analytic, &p, no_stop());
+        let approx = newton_descent(start(), &*f, &*grad, &*numeric, &p, no_stop());
+        assert!(exact.termination.is_converged());
+        assert!(approx.termination.is_converged());
+        // Первые шаги совпадают с точностью конечных разностей
+        for k in 1..4 {
+            let gap = (&exact.history[k].0 - &approx.history[k].0).amax();
+            assert!(gap < 1e-5, "итерация {}: {}", k, gap);
+        }
+    }
+
+    #[test]
+    fn verbose_log_has_one_line_per_iteration_plus_start() {
+        let (f, grad) = rosenbrock();
+        let p = DescentParams {
+            verbose: true,
+            ..params(50)
+        };
+        let res = gradient_descent(
+            DVector::from_vec(vec![-1.2, 1.0]),
+            &*f,
+            &*grad,
+            &p,
+            &default_search(&p),
+            no_stop(),
+        );
+        assert_eq!(res.iterations, 50);
+        assert_eq!(res.log.len(), res.iterations + 1);
+        assert!(res.log[0].starts_with("старт"));
+        let quiet = gradient_descent(
+            DVector::from_vec(vec![-1.2, 1.0]),
+            &*f,
+            &*grad,
+            &params(50),
+            &default_search(&p),
+            no_stop(),
+        );
+        assert!(quiet.log.is_empty());
+    }
+
+    #[test]
+    fn normalization_keeps_trajectory_of_well_scaled_function() {
+        let f = |x: &DVector<f64>| x[0] * x[0] + 2.0 * x[1] * x[1];
+        let grad = |x: &DVector<f64>| DVector::from_vec(vec![2.0 * x[0], 4.0 * x[1]]);
+        let run = |start: &[f64], normalize_objective| {
+            let p = DescentParams {
+                normalize_objective,
+                ..params(1000)
+            };
+            gradient_descent(
+                DVector::from_column_slice(start),
+                &f,
+                &grad,
+                &p,
+                &default_search(&p),
+                no_stop(),
+            )
+        };
+        // f(x0) = 1: нормировка ничего не меняет
+        let start = [0.6, 0.8 / 2f64.sqrt()];
+        let plain = run(&start, false);
+        let normalized = run(&start, true);
+        assert_eq!(plain.history, normalized.history);
+        assert_eq!(plain.termination, normalized.termination);
+        // При f(x0) = 27 в истории остаются значения исходной функции
+        let scaled = run(&[3.0, 3.0], true);
+        assert_eq!(scaled.history[0].1, 27.0);
+        for (x, f_x) in &scaled.history {
+            assert!((f(x) - f_x).abs() <= 1e-12 * (1.0 + f_x.abs()));
+        }
+    }
+
+    #[test]
+    fn stopped_run_reports_consistent_point_and_value() {
+        let (f, grad) = rosenbrock();
+        for stop_at in 1..=10 {
+            let stop = no_stop();
+            let flag = Arc::clone(&stop);
+            let p = DescentParams {
+                progress: Some(Progress {
+                    every: 1,
+                    callback: Arc::new(move |iter: usize, _: &DVector<f64>, _: f64| {
+                        if iter == stop_at {
+                            flag.store(true, Ordering::SeqCst);
+                        }
+                    }),
+                }),
+                ..params(1000)
+            };
+            let res = gradient_descent(
+                DVector::from_vec(vec![-1.2, 1.0]),
+                &*f,
+                &*grad,
+                &p,
+                &default_search(&p),
+                stop,
+            );
+            assert_eq!(res.termination, TerminationReason::UserStopped);
+            assert_eq!(res.iterations, stop_at);
+            assert_eq!(res.f_x, f(&res.x));
+            assert_eq!(res.history.last().unwrap(), &(res.x.clone(), res.f_x));
+        }
+    }
+
+    #[test]
+    fn eval_cache_skips_repeated_points() {
+        let calls = Cell::new(0);
+        let raw = |x: &DVector<f64>| {
+            calls.set(calls.get() + 1);
+            x.dot(x)
+        };
+        let counter = EvalCounter::new();
+        let f = counter.objective(&raw);
+        let a = DVector::from_vec(vec![1.0, 2.0]);
+        let b = DVector::from_vec(vec![3.0, 4.0]);
+        assert_eq!(f(&a), 5.0);
+        assert_eq!(f(&b), 25.0);
+        // Сдвиг в пределах машинной точности считается той же точкой
+        assert_eq!(f(&(&a * (1.0 + f64::EPSILON / 2.0))), 5.0);
+        assert_eq!(calls.get(), 2);
+        assert_eq!(counter.f_evals.get(), 2);
+        // Старые точки вытесняются
+        for k in 0..EVAL_CACHE_SIZE {
+            f(&DVector::from_vec(vec![k as f64 + 10.0, 0.0]));
+        }
+        f(&a);
+        assert_eq!(calls.get(), 3 + EVAL_CACHE_SIZE);
+    }
+
+    #[test]
+    fn relative_gradient_test_is_scale_invariant() {
+        let run = |scale: f64, rel_grad, tolerance| {
+            let f = move |x: &DVector<f64>| scale * x.dot(x);
+            let grad = move |x: &DVector<f64>| 2.0 * scale * x;
+            let p = DescentParams {
+                rel_grad,
+                tolerance,
+                ..params(10_000)
+            };
+            // Шаг согласован с масштабом, чтобы траектории были подобны
+            let ls = AdaptiveHalving::new(0.1 / scale, 0.5, 1.0, 0.1 / scale);
+            gradient_descent(
+                DVector::from_vec(vec![3.0, -4.0]),
+                &f,
+                &grad,
+                &p,
+                &ls,
+                no_stop(),
+            )
+        };
+        // При масштабе 1 ||g0|| = 10: относительный порог 1e-6 соответствует абсолютному 1e-5
+        let unit = run(1.0, false, 1e-5);
+        let steep = run(1000.0, true, 1e-6);
+        assert_eq!(steep.termination, TerminationReason::Converged);
+        assert_eq!(steep.iterations, unit.iterations);
+        assert!((&steep.x - &unit.x).amax() < 1e-12);
+        // Абсолютный порог при масштабе 1000 требует в 1000 раз большей точности по x
+        let steep_abs = run(1000.0, false, 1e-5);
+        assert!(steep_abs.iterations > steep.iterations);
+    }
+
+    #[test]
+    fn estimated_initial_step_converges_without_tuning() {
+        for scale in [1.0, 0.001] {
+            let f = move |x: &DVector<f64>| scale * x.dot(x);
+            let grad = move |x: &DVector<f64>| 2.0 * scale * x;
+            let start = DVector::from_vec(vec![3.0, -4.0]);
+            let step = estimate_initial_step(&start, &f, &grad).unwrap();
+            // Для квадратичной функции оценка точна: t = 1 / (2·scale)
+            assert!(
+                (step - 0.5 / scale).abs() < 1e-6 / scale,
+                "{}: {}",
+                scale,
+                step
+            );
+            let p = DescentParams {
+                initial_step: step,
+                max_step: DescentParams::default().max_step.max(step),
+                ..params(1000)
+            };
+            let res = gradient_descent(start, &f, &grad, &p, &default_search(&p), no_stop());
+            assert_eq!(res.termination, TerminationReason::Converged, "{}", scale);
+            assert!(res.iterations <= 3, "{}: {}", scale, res.iterations);
+        }
+        let zero_grad = |x: &DVector<f64>| DVector::zeros(x.len());
+        let f = |x: &DVector<f64>| x.dot(x);
+        assert_eq!(
+            estimate_initial_step(&DVector::zeros(2), &f, &zero_grad),
+            None
+        );
+    }
+
+    #[test]
+    fn backtrack_cap_decides_slow_decay_on_steep_function() {
+        let f = |x: &DVector<f64>| 1e6 * x.dot(x);
+        let grad = |x: &DVector<f64>| 2e6 * x;
+        let run = |max_backtracks| {
+            let p = DescentParams {
+                initial_step: 1.0,
+                step_decay: 0.9,
+                max_backtracks,
+                ..params(1000)
+            };
+            gradient_descent(
+                DVector::from_vec(vec![1.0, -1.0]),
+                &f,
+                &grad,
+                &p,
+                &default_search(&p),
+                no_stop(),
+            )
+        };
+        // Уменьшение f даёт только шаг меньше 1e-6, а 0.9^k < 1e-6 лишь при k >= 132
+        let short = run(20);
+        assert_eq!(short.termination, TerminationReason::LineSearchFailed);
+        assert_eq!(short.iterations, 0);
+        let long = run(200);
+        assert_eq!(long.termination, TerminationReason::Converged);
+    }
+
+    #[test]
+    fn perturbed_start_is_reproducible_and_feasible() {
+        let x = DVector::from_vec(vec![0.9, -0.5, 2.0]);
+        let a = perturb_point(&x, 0.5, 42, None);
+        assert_eq!(a, perturb_point(&x, 0.5, 42, None));
+        assert_ne!(a, perturb_point(&x, 0.5, 43, None));
+        assert_ne!(a, x);
+        assert_eq!(perturb_point(&x, 0.0, 42, None), x);
+
+        let bounds = Bounds {
+            lower: DVector::from_element(3, -1.0),
+            upper: DVector::from_element(3, 1.0),
+        };
+        for seed in 0..50 {
+            let y = perturb_point(&x, 2.0, seed, Some(&bounds));
+            assert!(y.iter().all(|v| (-1.0..=1.0).contains(v)), "{}", y);
+        }
+    }
+
+    #[test]
+    fn random_restart_finds_deeper_well() {
+        let grad = |x: &DVector<f64>| {
+            DVector::from_vec(vec![4.0 * x[0] * (x[0] * x[0] - 1.0) + 0.3, 2.0 * x[1]])
+        };
+        let p = params(1000);
+        let rp = RestartParams {
+            count: 12,
+            lower: -2.0,
+            upper: 2.0,
+            seed: 7,
+        };
+        let solve = |_: &mut (), start: DVector<f64>| {
+            gradient_descent(
+                start,
+                &double_well,
+                &grad,
+                &p,
+                &default_search(&p),
+                no_stop(),
+            )
+        };
+        let stop = AtomicBool::new(false);
+        let report = random_restart(2, &rp, || (), solve, &stop).unwrap();
+        assert_eq!(report.finals.len(), rp.count);
+        assert!(report.best.x[0] < -0.9, "x = {}", report.best.x);
+        assert!(report.finals.iter().all(|(_, f)| *f >= report.best.f_x));
+        assert!(report.finals.iter().any(|(x, _)| x[0] > 0.9));
+        let again = random_restart(2, &rp, || (), solve, &stop).unwrap();
+        assert_eq!(again.finals, report.finals);
+
+        // Запуск с f = NaN не выбирается лучшим, даже если остальные хуже
+        let solve_nan = |state: &mut (), start: DVector<f64>| {
+            let mut res = solve(state, start);
+            if res.x[0] < 0.0 {
+                res.f_x = f64::NAN;
+            }
+            res
+        };
+        let report = random_restart(2, &rp, || (), solve_nan, &stop).unwrap();
+        assert!(report.best.x[0] > 0.9 && report.best.f_x.is_finite());
+    }
+
+    #[test]
+    fn flat_region_detection_depends_on_round_off_only() {
+        let flat = |x: &DVector<f64>| 1e-12 * x[0] * x[0] + 1.0;
+        let flat_grad = |x: &DVector<f64>| DVector::from_element(1, 2e-12 * x[0]);
+        let p = DescentParams {
+            detect_flat: true,
+            tolerance: 1e-15,
+            ..params(100)
+        };
+        let res = gradient_descent(
+            DVector::from_element(1, 1.0),
+            &flat,
+            &flat_grad,
+            &p,
+            &default_search(&p),
+            no_stop(),
+        );
+        assert_eq!(res.termination, TerminationReason::FlatRegion);
+
+        // Вдали от минимума крутой квадратичной функции уровень шума не зависит
+        // от градиента и критерий не срабатывает и при большом n
+        for n in [10, 200] {
+            let f = |x: &DVector<f64>| 100.0 * x.dot(x);
+            let grad = |x: &DVector<f64>| 200.0 * x;
+            let x = DVector::from_element(n, 10.0);
+            let floor = fd_noise_floor(&f, &x, f(&x), p.fd_eps);
+            assert!(floor < 1e-3 * grad(&x).norm(), "n = {}: {}", n, floor);
+            let res = gradient_descent(
+                x,
+                &f,
+                &grad,
+                &DescentParams {
+                    max_iterations: 3,
+                    ..p.clone()
+                },
+                &default_search(&p),
+                no_stop(),
+            );
+            assert_ne!(res.termination, TerminationReason::FlatRegion, "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn gradient_descent_rejects_invalid_step_decay() {
+        let f = |x: &DVector<f64>| x.dot(x);
+        let grad = |x: &DVector<f64>| 2.0 * x;
+        for step_decay in [1.0, 1.5] {
+            let p = DescentParams {
+                step_decay,
+                ..params(100)
+            };
+            let start = DVector::from_vec(vec![3.0, -1.0]);
+            let res =
+                gradient_descent(start.clone(), &f, &grad, &p, &default_search(&p), no_stop());
+            assert_eq!(res.termination, TerminationReason::InvalidParams);
+            assert_eq!(res.iterations, 0);
+            assert_eq!(res.x, start);
+            assert_eq!(res.f_x, f(&start));
+            assert_eq!(
+                res.log,
+                vec![OptimizerError::InvalidStepDecay(step_decay).to_string()]
+            );
+        }
     }
 }
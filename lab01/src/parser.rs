@@ -1,5 +1,9 @@
+use crate::ast::Node;
+use meval::tokenizer::Operation;
 use meval::{Context, Expr};
-use nalgebra::DVector;
+use nalgebra::{DMatrix, DVector};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -10,51 +14,388 @@ pub enum ParserError {
     EvalError(String),
     #[error("Выражение содержит недопустимые символы")]
     InvalidExpression,
+    #[error("Символьное дифференцирование не поддерживает: {0}")]
+    UnsupportedDerivative(String),
 }
 
+// Схема конечных разностей для численного градиента
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum GradientMode {
+    // (f(x + eps*e_i) - f(x)) / eps, погрешность O(eps)
+    Forward,
+    // (f(x + eps*e_i) - f(x - eps*e_i)) / (2*eps), погрешность O(eps^2)
+    Central,
+    // Символьные производные; для неподдерживаемых выражений — центральная разность
+    Analytic,
+}
+
+impl GradientMode {
+    pub const ALL: [GradientMode; 3] = [
+        GradientMode::Central,
+        GradientMode::Forward,
+        GradientMode::Analytic,
+    ];
+
+    // Ключ строки интерфейса в i18n
+    pub fn label(self) -> &'static str {
+        match self {
+            GradientMode::Forward => "grad_forward",
+            GradientMode::Central => "grad_central",
+            GradientMode::Analytic => "grad_analytic",
+        }
+    }
+}
+
+// Короткие имена x, y, z для размерностей 1–3 заменяются на x1, x2, x3 ещё
+// до разбора: так выражение, где смешаны x и x1, остаётся функцией одной
+// переменной и для вычисления, и для символьного дифференцирования
+const ALIASES: [&str; 3] = ["x", "y", "z"];
+
+fn expand_aliases(expr_str: &str, num_vars: usize) -> String {
+    let chars: Vec<char> = expr_str.chars().collect();
+    let mut out = String::with_capacity(expr_str.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_ascii_digit() || c == '.' {
+            // Число целиком, включая показатель 1e-3, чтобы 'e' не приняли за имя
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                i += 1;
+                if i < chars.len() && (chars[i] == '+' || chars[i] == '-') {
+                    i += 1;
+                }
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+            }
+            out.extend(&chars[start..i]);
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let name: String = chars[start..i].iter().collect();
+            match ALIASES[..num_vars.min(ALIASES.len())]
+                .iter()
+                .position(|alias| *alias == name)
+            {
+                Some(k) => out.push_str(&format!("x{}", k + 1)),
+                None => out.push_str(&name),
+            }
+        } else {
+            out.push(c);
+            i += 1;
+        }
+    }
+    out
+}
+
+// Выражение, заранее связанное с именами переменных
+type CompiledFn = Arc<dyn Fn(&[f64]) -> f64 + Send + Sync>;
+
+// Встроенные функции и константы meval (pi, e, abs, а также max и min от любого
+// числа аргументов) и дополнительно tau = 2π
+fn builtin_context() -> Context<'static> {
+    let mut ctx = Context::new();
+    ctx.var("tau", std::f64::consts::TAU);
+    ctx
+}
+
+thread_local! {
+    // Контекст со встроенными функциями строится один раз на поток,
+    // а не при каждом вычислении (Context не Send и не может храниться в замыкании)
+    static BUILTIN: Context<'static> = builtin_context();
+}
+
+// Набор переменных проверен при разборе, поэтому ошибка вычисления
+// здесь невозможна; на всякий случай она превращается в NaN
+fn compile(expr: Expr, var_names: &[String]) -> CompiledFn {
+    let var_names = var_names.to_vec();
+    Arc::new(move |x: &[f64]| {
+        let vars: Vec<(&str, f64)> = var_names
+            .iter()
+            .map(String::as_str)
+            .zip(x.iter().copied())
+            .collect();
+        BUILTIN.with(|builtin| expr.eval_with_context((vars, builtin)).unwrap_or(f64::NAN))
+    })
+}
+
+/// Функция, заданная выражением от x1..xn (при n <= 3 также x, y, z).
 #[derive(Clone)]
 pub struct ParsedFunction {
     expr: Expr,
+    compiled: CompiledFn,
     num_vars: usize,
+    var_names: Vec<String>,
+    domain_warnings: Vec<String>,
+    // Частные производные, если выражение удалось продифференцировать
+    symbolic_gradient: Option<Vec<CompiledFn>>,
+    // Вторые производные по строкам матрицы Гессе
+    symbolic_hessian: Option<Vec<CompiledFn>>,
 }
 
 impl ParsedFunction {
     pub fn new(expr_str: &str, num_vars: usize) -> Result<Self, ParserError> {
+        let names: Vec<String> = (1..=num_vars).map(|i| format!("x{}", i)).collect();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        Self::with_vars(&expand_aliases(expr_str, num_vars), &names)
+    }
+
+    // Функция от переменных с произвольными именами, например (r, theta)
+    pub fn with_vars(expr_str: &str, var_names: &[&str]) -> Result<Self, ParserError> {
         let expr: Expr = expr_str
             .parse()
             .map_err(|e: meval::Error| ParserError::ParseError(e.to_string()))?;
 
-        let mut ctx = Context::new();
-        for i in 1..=num_vars {
-            ctx.var(&format!("x{}", i), 0.0);
+        let mut ctx = builtin_context();
+        for name in var_names {
+            ctx.var(*name, 0.0);
         }
 
         if expr.clone().eval_with_context(ctx).is_err() {
             return Err(ParserError::InvalidExpression);
         }
 
-        Ok(ParsedFunction { expr, num_vars })
+        let domain_warnings = Node::from_expr(&expr)
+            .map(|tree| domain_warnings(&tree))
+            .unwrap_or_default();
+
+        let var_names: Vec<String> = var_names.iter().map(|s| s.to_string()).collect();
+        let mut parsed = ParsedFunction {
+            compiled: compile(expr.clone(), &var_names),
+            expr,
+            num_vars: var_names.len(),
+            var_names,
+            domain_warnings,
+            symbolic_gradient: None,
+            symbolic_hessian: None,
+        };
+        parsed.symbolic_gradient = parsed.analytic_gradient().ok().map(|derivatives| {
+            derivatives
+                .into_iter()
+                .map(|expr| compile(expr, &parsed.var_names))
+                .collect()
+        });
+        parsed.symbolic_hessian = parsed.analytic_hessian().ok().map(|entries| {
+            entries
+                .into_iter()
+                .map(|expr| compile(expr, &parsed.var_names))
+                .collect()
+        });
+        Ok(parsed)
+    }
+
+    // Символьное дифференцирование по каждой переменной
+    pub fn analytic_gradient(&self) -> Result<Vec<Expr>, ParserError> {
+        let tree = Node::from_expr(&self.expr).ok_or(ParserError::InvalidExpression)?;
+        self.var_names
+            .iter()
+            .map(|name| {
+                let derivative = tree
+                    .derivative(name)
+                    .map_err(ParserError::UnsupportedDerivative)?;
+                derivative
+                    .to_string()
+                    .parse()
+                    .map_err(|e: meval::Error| ParserError::ParseError(e.to_string()))
+            })
+            .collect()
+    }
+
+    // Вторые производные ∂²f/∂x_i∂x_j по строкам; матрица симметрична,
+    // поэтому дифференцируется только верхний треугольник
+    pub fn analytic_hessian(&self) -> Result<Vec<Expr>, ParserError> {
+        let tree = Node::from_expr(&self.expr).ok_or(ParserError::InvalidExpression)?;
+        let n = self.var_names.len();
+        let mut entries: Vec<Option<Expr>> = vec![None; n * n];
+        for (i, xi) in self.var_names.iter().enumerate() {
+            let di = tree
+                .derivative(xi)
+                .map_err(ParserError::UnsupportedDerivative)?;
+            for (j, xj) in self.var_names.iter().enumerate().skip(i) {
+                let dij: Expr = di
+                    .derivative(xj)
+                    .map_err(ParserError::UnsupportedDerivative)?
+                    .to_string()
+                    .parse()
+                    .map_err(|e: meval::Error| ParserError::ParseError(e.to_string()))?;
+                entries[j * n + i] = Some(dij.clone());
+                entries[i * n + j] = Some(dij);
+            }
+        }
+        Ok(entries.into_iter().flatten().collect())
+    }
+
+    pub fn has_analytic_hessian(&self) -> bool {
+        self.symbolic_hessian.is_some()
+    }
+
+    // Матрица Гессе по заранее вычисленным вторым производным
+    pub fn eval_hessian(&self, point: &DVector<f64>) -> Result<DMatrix<f64>, ParserError> {
+        let entries = self.symbolic_hessian.as_ref().ok_or_else(|| {
+            ParserError::UnsupportedDerivative(
+                "выражение не удалось продифференцировать дважды".to_string(),
+            )
+        })?;
+        let values = entries
+            .iter()
+            .map(|f| self.eval_compiled(f, point))
+            .collect::<Result<Vec<f64>, ParserError>>()?;
+        Ok(DMatrix::from_row_slice(
+            self.num_vars,
+            self.num_vars,
+            &values,
+        ))
+    }
+
+    // Значение градиента по заранее вычисленным символьным производным
+    pub fn eval_gradient(&self, point: &DVector<f64>) -> Result<DVector<f64>, ParserError> {
+        let derivatives = self.symbolic_gradient.as_ref().ok_or_else(|| {
+            ParserError::UnsupportedDerivative(
+                "выражение не удалось продифференцировать".to_string(),
+            )
+        })?;
+        let values = derivatives
+            .iter()
+            .map(|f| self.eval_compiled(f, point))
+            .collect::<Result<Vec<f64>, ParserError>>()?;
+        Ok(DVector::from_vec(values))
+    }
+
+    // Запись производных для отображения; None, если дифференцирование не удалось
+    pub fn gradient_strings(&self) -> Option<Vec<String>> {
+        let tree = Node::from_expr(&self.expr)?;
+        self.var_names
+            .iter()
+            .map(|name| tree.derivative(name).ok().map(|d| d.to_string()))
+            .collect()
+    }
+
+    // Операции, сужающие область определения, и требуемые условия на их аргументы
+    pub fn domain_warnings(&self) -> &[String] {
+        &self.domain_warnings
+    }
+
+    // Есть ли abs, max или min от переменных. В точках излома разностный
+    // градиент не является производной: прямая разность даёт одностороннюю
+    // производную справа, центральная — среднее левой и правой
+    pub fn has_kinks(&self) -> bool {
+        let Some(tree) = Node::from_expr(&self.expr) else {
+            return false;
+        };
+        let mut found = false;
+        tree.visit(&mut |node| {
+            if let Node::Func(name, args) = node {
+                found |= matches!(name.as_str(), "abs" | "max" | "min")
+                    && args.iter().any(Node::has_vars);
+            }
+        });
+        found
     }
 
     pub fn eval(&self, point: &DVector<f64>) -> Result<f64, ParserError> {
+        self.eval_compiled(&self.compiled, point)
+    }
+
+    // Значения в нескольких точках: встроенный контекст берётся один раз,
+    // а список переменных не строится заново для каждой точки
+    pub fn eval_batch(&self, points: &[DVector<f64>]) -> Vec<Result<f64, ParserError>> {
+        BUILTIN.with(|builtin| {
+            let mut vars: Vec<(&str, f64)> = self
+                .var_names
+                .iter()
+                .map(|name| (name.as_str(), 0.0))
+                .collect();
+            points
+                .iter()
+                .map(|point| {
+                    if point.len() != self.num_vars {
+                        return Err(ParserError::EvalError(
+                            "Неверная размерность точки".to_string(),
+                        ));
+                    }
+                    for (var, &x) in vars.iter_mut().zip(point.iter()) {
+                        var.1 = x;
+                    }
+                    Ok(self
+                        .expr
+                        .eval_with_context((&vars, builtin))
+                        .unwrap_or(f64::NAN))
+                })
+                .collect()
+        })
+    }
+
+    fn eval_compiled(&self, f: &CompiledFn, point: &DVector<f64>) -> Result<f64, ParserError> {
         if point.len() != self.num_vars {
             return Err(ParserError::EvalError(
                 "Неверная размерность точки".to_string(),
             ));
         }
 
-        let mut ctx = Context::new();
-        for i in 0..self.num_vars {
-            ctx.var(&format!("x{}", i + 1), point[i]);
+        Ok(f(point.as_slice()))
+    }
+
+    pub fn gradient(
+        &self,
+        point: &DVector<f64>,
+        eps: f64,
+        mode: GradientMode,
+    ) -> Result<DVector<f64>, ParserError> {
+        let n = point.len();
+        if n != self.num_vars {
+            return Err(ParserError::EvalError(
+                "Неверная размерность точки".to_string(),
+            ));
+        }
+
+        if mode == GradientMode::Analytic && self.symbolic_gradient.is_some() {
+            return self.eval_gradient(point);
+        }
+
+        let mut grad = DVector::zeros(n);
+
+        match mode {
+            GradientMode::Forward => {
+                let f0 = self.eval(point)?;
+                for i in 0..n {
+                    let mut point_plus = point.clone();
+                    point_plus[i] += eps;
+                    let f_plus = self.eval(&point_plus)?;
+                    grad[i] = (f_plus - f0) / eps;
+                }
+            }
+            GradientMode::Central | GradientMode::Analytic => {
+                for i in 0..n {
+                    let mut point_plus = point.clone();
+                    let mut point_minus = point.clone();
+                    point_plus[i] += eps;
+                    point_minus[i] -= eps;
+                    let f_plus = self.eval(&point_plus)?;
+                    let f_minus = self.eval(&point_minus)?;
+                    grad[i] = (f_plus - f_minus) / (2.0 * eps);
+                }
+            }
         }
+        Ok(grad)
+    }
 
-        self.expr
-            .clone()
-            .eval_with_context(ctx)
-            .map_err(|e: meval::Error| ParserError::EvalError(e.to_string()))
+    // Наибольшее по модулю расхождение аналитического градиента с центральной
+    // разностью в точке: проверка символьного дифференцирования
+    pub fn check_gradient(&self, point: &DVector<f64>, eps: f64) -> Result<f64, ParserError> {
+        let analytic = self.eval_gradient(point)?;
+        let numeric = self.gradient(point, eps, GradientMode::Central)?;
+        Ok((analytic - numeric).amax())
     }
 
-    pub fn gradient(&self, point: &DVector<f64>, eps: f64) -> Result<DVector<f64>, ParserError> {
+    // Матрица Гессе по значениям функции:
+    // H_ij ≈ (f(x+h·e_i+h·e_j) - f(x+h·e_i-h·e_j) - f(x-h·e_i+h·e_j) + f(x-h·e_i-h·e_j)) / 4h²
+    pub fn hessian(&self, point: &DVector<f64>, eps: f64) -> Result<DMatrix<f64>, ParserError> {
         let n = point.len();
         if n != self.num_vars {
             return Err(ParserError::EvalError(
@@ -62,15 +403,385 @@ impl ParsedFunction {
             ));
         }
 
-        let mut grad = DVector::zeros(n);
-        let f0 = self.eval(point)?;
+        let shifted = |i: usize, si: f64, j: usize, sj: f64| -> Result<f64, ParserError> {
+            let mut p = point.clone();
+            p[i] += si * eps;
+            p[j] += sj * eps;
+            self.eval(&p)
+        };
 
+        let mut hess = DMatrix::zeros(n, n);
         for i in 0..n {
-            let mut point_plus = point.clone();
-            point_plus[i] += eps;
-            let f_plus = self.eval(&point_plus)?;
-            grad[i] = (f_plus - f0) / eps;
+            for j in i..n {
+                let value = (shifted(i, 1.0, j, 1.0)?
+                    - shifted(i, 1.0, j, -1.0)?
+                    - shifted(i, -1.0, j, 1.0)?
+                    + shifted(i, -1.0, j, -1.0)?)
+                    / (4.0 * eps * eps);
+                hess[(i, j)] = value;
+                hess[(j, i)] = value;
+            }
         }
-        Ok(grad)
+        Ok(hess)
+    }
+}
+
+// Обход дерева выражения: для каждой операции с ограниченной областью
+// определения записывается условие, при котором результат вещественный
+fn domain_warnings(tree: &Node) -> Vec<String> {
+    let mut warnings: Vec<String> = Vec::new();
+    tree.visit(&mut |node| {
+        let warning = match node {
+            Node::Func(name, args) if args.len() == 1 && args[0].has_vars() => {
+                let a = &args[0];
+                match name.as_str() {
+                    "sqrt" => Some(format!("sqrt({}): требуется {} >= 0", a, a)),
+                    "ln" => Some(format!("ln({}): требуется {} > 0", a, a)),
+                    "asin" | "acos" => Some(format!("{}({}): требуется -1 <= {} <= 1", name, a, a)),
+                    "acosh" => Some(format!("acosh({}): требуется {} >= 1", a, a)),
+                    "atanh" => Some(format!("atanh({}): требуется -1 < {} < 1", a, a)),
+                    _ => None,
+                }
+            }
+            Node::Binary(Operation::Pow, base, exp) if base.has_vars() => {
+                match exp.constant_value() {
+                    Some(p) if p.fract() != 0.0 && p < 0.0 => Some(format!(
+                        "{}: дробная отрицательная степень, требуется {} > 0",
+                        node, base
+                    )),
+                    Some(p) if p.fract() != 0.0 => Some(format!(
+                        "{}: дробная степень, требуется {} >= 0",
+                        node, base
+                    )),
+                    Some(_) => None,
+                    None => Some(format!(
+                        "{}: степень с переменным показателем, требуется {} > 0",
+                        node, base
+                    )),
+                }
+            }
+            _ => None,
+        };
+        if let Some(w) = warning {
+            if !warnings.contains(&w) {
+                warnings.push(w);
+            }
+        }
+    });
+    warnings
+}
+
+// Ограничение вида a <= x_i <= b, записанное в строке функции
+#[derive(Debug, Clone, PartialEq)]
+pub struct InlineBound {
+    pub var_index: usize,
+    pub lower: Option<f64>,
+    pub upper: Option<f64>,
+}
+
+// Разбирает строку вида "x1^2 + x2^2; 0<=x1<=5; -2<=x2<=2": первый сегмент —
+// целевая функция, остальные — границы переменных. Ошибки собираются по сегментам.
+pub fn split_inline_bounds(
+    input: &str,
+    var_names: &[String],
+) -> (String, Vec<InlineBound>, Vec<String>) {
+    let mut segments = input.split(';');
+    let objective = segments.next().unwrap_or("").trim().to_string();
+
+    let mut bounds = Vec::new();
+    let mut errors = Vec::new();
+    for (k, segment) in segments.enumerate() {
+        let segment = segment.trim();
+        if segment.is_empty() {
+            continue;
+        }
+        match parse_bound_segment(segment, var_names) {
+            Ok(bound) => bounds.push(bound),
+            Err(e) => errors.push(format!("сегмент {} ('{}'): {}", k + 2, segment, e)),
+        }
+    }
+
+    (objective, bounds, errors)
+}
+
+fn parse_bound_segment(segment: &str, var_names: &[String]) -> Result<InlineBound, String> {
+    // Разбиение на чередующиеся операнды и знаки сравнения
+    let mut operands = Vec::new();
+    let mut ops = Vec::new();
+    let mut rest = segment;
+    while let Some(pos) = rest.find(['<', '>']) {
+        operands.push(rest[..pos].trim());
+        let le = rest[pos..].starts_with("<");
+        let len = if rest[pos + 1..].starts_with('=') {
+            2
+        } else {
+            1
+        };
+        ops.push(le);
+        rest = &rest[pos + len..];
+    }
+    operands.push(rest.trim());
+
+    if ops.is_empty() {
+        return Err("ожидается ограничение вида a <= x <= b".to_string());
+    }
+    if ops.len() > 2 || (ops.len() == 2 && ops[0] != ops[1]) {
+        return Err("допускается только двойное неравенство одного направления".to_string());
+    }
+
+    let var_pos = operands
+        .iter()
+        .position(|op| var_names.iter().any(|name| name == op))
+        .ok_or_else(|| "не найдена переменная".to_string())?;
+    let var_index = var_names
+        .iter()
+        .position(|name| name == operands[var_pos])
+        .unwrap();
+
+    let mut bound = InlineBound {
+        var_index,
+        lower: None,
+        upper: None,
+    };
+    for (k, operand) in operands.iter().enumerate() {
+        if k == var_pos {
+            continue;
+        }
+        let value = meval::eval_str(operand)
+            .map_err(|_| format!("не удалось разобрать число '{}'", operand))?;
+        // Операнд слева от переменной при знаке "<" — нижняя граница
+        let is_lower = (k < var_pos) == ops[0];
+        if is_lower {
+            bound.lower = Some(value);
+        } else {
+            bound.upper = Some(value);
+        }
+    }
+
+    if let (Some(lo), Some(hi)) = (bound.lower, bound.upper) {
+        if lo > hi {
+            return Err(format!("нижняя граница {} больше верхней {}", lo, hi));
+        }
+    }
+
+    Ok(bound)
+}
+
+// Текст файла с функцией: строки, начинающиеся с '#', — комментарии,
+// "# vars: 3" задаёт число переменных, остальные строки склеиваются в выражение
+pub fn parse_function_file(text: &str) -> Result<(String, Option<usize>), String> {
+    let mut num_vars = None;
+    let mut lines = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if let Some(comment) = line.strip_prefix('#') {
+            if let Some(value) = comment.trim().strip_prefix("vars:") {
+                let n = value
+                    .trim()
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|n| (1..=10).contains(n))
+                    .ok_or_else(|| {
+                        format!("число переменных '{}' должно быть от 1 до 10", value.trim())
+                    })?;
+                num_vars = Some(n);
+            }
+        } else if !line.is_empty() {
+            lines.push(line);
+        }
+    }
+    if lines.is_empty() {
+        return Err("файл не содержит выражения".to_string());
+    }
+    Ok((lines.join(" "), num_vars))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(values: &[f64]) -> DVector<f64> {
+        DVector::from_vec(values.to_vec())
+    }
+
+    #[test]
+    fn forward_and_central_gradients_match_analytic() {
+        let func = ParsedFunction::new("x1^2 + x2^2", 2).unwrap();
+        let eps = 1e-5;
+        for p in [[0.0, 0.0], [1.0, -2.0], [3.5, 0.25], [-10.0, 7.0]] {
+            let x = point(&p);
+            let exact = 2.0 * &x;
+            let forward = func.gradient(&x, eps, GradientMode::Forward).unwrap();
+            let central = func.gradient(&x, eps, GradientMode::Central).unwrap();
+            // У прямой разности погрешность eps, у центральной — eps² (для квадрата ноль)
+            assert!((forward - &exact).amax() <= 2.0 * eps, "{:?}", p);
+            assert!((central - &exact).amax() <= 1e-6, "{:?}", p);
+        }
+    }
+
+    #[test]
+    fn finite_difference_hessian_of_quadratic() {
+        let func = ParsedFunction::new("x1^2 + 3*x2^2 + x1*x2", 2).unwrap();
+        let h = func.hessian(&point(&[1.0, -2.0]), 1e-4).unwrap();
+        let exact = DMatrix::from_row_slice(2, 2, &[2.0, 1.0, 1.0, 6.0]);
+        assert!((h - exact).amax() < 1e-6);
+    }
+
+    #[test]
+    fn analytic_gradient_matches_numeric() {
+        let func = ParsedFunction::new("x1^2 * x2 + sin(x1)", 2).unwrap();
+        for p in [[0.0, 0.0], [1.0, 2.0], [-1.5, 0.5], [3.0, -4.0]] {
+            let x = point(&p);
+            let exact = point(&[2.0 * p[0] * p[1] + p[0].cos(), p[0] * p[0]]);
+            let analytic = func.gradient(&x, 1e-6, GradientMode::Analytic).unwrap();
+            assert!((analytic - exact).amax() < 1e-12, "{:?}", p);
+        }
+    }
+
+    #[test]
+    fn analytic_mode_falls_back_to_central_difference() {
+        // Производная floor не поддерживается — берётся центральная разность
+        let func = ParsedFunction::new("floor(x1) + x2^2", 2).unwrap();
+        assert!(func.analytic_gradient().is_err());
+        let x = point(&[0.5, 3.0]);
+        let analytic = func.gradient(&x, 1e-6, GradientMode::Analytic).unwrap();
+        let central = func.gradient(&x, 1e-6, GradientMode::Central).unwrap();
+        assert_eq!(analytic, central);
+    }
+
+    #[test]
+    fn single_letter_aliases_match_indexed_names() {
+        let short = ParsedFunction::new("x^2 + y^2", 2).unwrap();
+        let indexed = ParsedFunction::new("x1^2 + x2^2", 2).unwrap();
+        for p in [[1.0, -2.0], [0.5, 3.0]] {
+            let x = point(&p);
+            for mode in [
+                GradientMode::Forward,
+                GradientMode::Central,
+                GradientMode::Analytic,
+            ] {
+                assert_eq!(
+                    short.gradient(&x, 1e-6, mode).unwrap(),
+                    indexed.gradient(&x, 1e-6, mode).unwrap()
+                );
+            }
+        }
+        // x и x1 обозначают одну переменную
+        let mixed = ParsedFunction::new("x*x1 + y - x2", 2).unwrap();
+        let x = point(&[3.0, 5.0]);
+        assert_eq!(mixed.eval(&x).unwrap(), 9.0);
+        let g = mixed.gradient(&x, 1e-6, GradientMode::Analytic).unwrap();
+        assert_eq!(g, point(&[6.0, 0.0]));
+    }
+
+    #[test]
+    fn compiled_eval_rejects_wrong_dimension() {
+        let func = ParsedFunction::new("x1 + x2", 2).unwrap();
+        assert!(matches!(
+            func.eval(&point(&[1.0])),
+            Err(ParserError::EvalError(_))
+        ));
+        assert_eq!(func.eval(&point(&[1.0, 2.0])).unwrap(), 3.0);
+    }
+
+    #[test]
+    fn check_gradient_is_small_for_polynomials_and_trig() {
+        let cases: [(&str, &[f64]); 4] = [
+            ("x1^3 - 2*x1*x2 + 4*x2^2", &[1.5, -0.5]),
+            ("x1^4 + x2^4 + x3^2*x1", &[0.3, -1.2, 2.0]),
+            ("sin(x1)*cos(x2)", &[0.7, -1.1]),
+            ("tan(x1) + sin(2*x1*x2)", &[0.4, 0.9]),
+        ];
+        for (expr, p) in cases {
+            let func = ParsedFunction::new(expr, p.len()).unwrap();
+            let error = func.check_gradient(&point(p), 1e-6).unwrap();
+            assert!(error < 1e-6, "{}: {}", expr, error);
+        }
+    }
+
+    #[test]
+    fn poorly_chosen_eps_is_less_accurate() {
+        let func = ParsedFunction::new("exp(x1) + sin(x2)", 2).unwrap();
+        let x = point(&[1.0, 0.5]);
+        let exact = point(&[1f64.exp(), 0.5f64.cos()]);
+        let error =
+            |eps: f64| (func.gradient(&x, eps, GradientMode::Central).unwrap() - &exact).amax();
+        let good = error(1e-6);
+        // Слишком большой шаг: ошибка отбрасывания, слишком малый: ошибки округления
+        assert!(error(1e-1) > 100.0 * good, "{} vs {}", error(1e-1), good);
+        assert!(error(1e-13) > 100.0 * good, "{} vs {}", error(1e-13), good);
+    }
+
+    #[test]
+    fn pi_constant_in_expressions() {
+        let func = ParsedFunction::new("sin(pi*x1)", 1).unwrap();
+        let x = point(&[1.0]);
+        assert!(func.eval(&x).unwrap().abs() < 1e-12);
+        let pi = std::f64::consts::PI;
+        for mode in [GradientMode::Central, GradientMode::Analytic] {
+            let g = func.gradient(&x, 1e-6, mode).unwrap();
+            assert!((g[0] + pi).abs() < 1e-6, "{:?}: {}", mode, g[0]);
+        }
+        let e = ParsedFunction::new("exp(x1) - e", 1).unwrap();
+        assert!(e.eval(&point(&[1.0])).unwrap().abs() < 1e-12);
+    }
+
+    #[test]
+    fn max_min_and_abs_with_gradients_away_from_kinks() {
+        let max = ParsedFunction::new("max(x1, x2)", 2).unwrap();
+        let dist = ParsedFunction::new("abs(x1 - x2)", 2).unwrap();
+        let squared = ParsedFunction::new("max(x1, x2)^2 + min(x1, x2)", 2).unwrap();
+        for (p, m, d) in [
+            ([1.0, 2.0], 2.0, 1.0),
+            ([3.0, -1.0], 3.0, 4.0),
+            ([-0.5, -0.25], -0.25, 0.25),
+        ] {
+            assert_eq!(max.eval(&point(&p)).unwrap(), m);
+            assert_eq!(dist.eval(&point(&p)).unwrap(), d);
+            assert_eq!(squared.eval(&point(&p)).unwrap(), m * m + p[0].min(p[1]));
+        }
+        let central = |func: &ParsedFunction, p: &[f64]| {
+            func.gradient(&point(p), 1e-6, GradientMode::Central)
+                .unwrap()
+        };
+        assert!((central(&max, &[1.0, 2.0]) - point(&[0.0, 1.0])).amax() < 1e-8);
+        assert!((central(&dist, &[3.0, -1.0]) - point(&[1.0, -1.0])).amax() < 1e-8);
+        assert!((central(&dist, &[0.0, 1.0]) - point(&[-1.0, 1.0])).amax() < 1e-8);
+    }
+
+    #[test]
+    fn analytic_gradient_handles_fractional_and_negative_powers() {
+        let func = ParsedFunction::new("x1^0.5 + x1^-2 + x1^x2", 2).unwrap();
+        assert!(func.gradient_strings().is_some());
+        // Дробные степени определены только при x1 > 0
+        for p in [[0.5, 1.5], [1.0, 2.0], [4.0, -0.5]] {
+            let error = func.check_gradient(&point(&p), 1e-6).unwrap();
+            assert!(error < 1e-6, "{:?}: {}", p, error);
+        }
+        let x = point(&[4.0, 0.0]);
+        let g = func.gradient(&x, 1e-6, GradientMode::Analytic).unwrap();
+        // d/dx1: 0.5·x1^-0.5 - 2·x1^-3 + x2·x1^(x2-1)
+        assert!((g[0] - (0.25 - 2.0 / 64.0)).abs() < 1e-12);
+        // d/dx2: x1^x2·ln(x1)
+        assert!((g[1] - 4f64.ln()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn eval_batch_matches_single_evaluations() {
+        let func = ParsedFunction::new("sin(x1)*x2 + max(x1, x2)^2 - ln(x2)", 2).unwrap();
+        let points: Vec<DVector<f64>> = (0..50)
+            .map(|k| point(&[k as f64 * 0.3 - 7.0, 3.0 - k as f64 * 0.1]))
+            .chain([point(&[1.0])])
+            .collect();
+        let batch = func.eval_batch(&points);
+        assert_eq!(batch.len(), points.len());
+        for (p, value) in points.iter().zip(&batch) {
+            match (func.eval(p), value) {
+                (Ok(a), Ok(b)) => assert!(a == *b || (a.is_nan() && b.is_nan()), "{}", p),
+                (Err(_), Err(_)) => {}
+                (a, b) => panic!("{}: {:?} vs {:?}", p, a, b),
+            }
+        }
+        // Точка неверной размерности даёт ошибку, а не панику
+        assert!(batch.last().unwrap().is_err());
     }
 }
@@ -0,0 +1,79 @@
+// Классические тестовые функции двух переменных с известным минимумом
+pub struct Preset {
+    // Ключ названия в i18n
+    pub name: &'static str,
+    pub expr: &'static str,
+    pub num_vars: usize,
+    pub start: &'static [f64],
+    // Точка минимума; у функции Химмельблау их четыре, указана одна
+    pub minimum: &'static [f64],
+}
+
+pub const PRESETS: [Preset; 6] = [
+    Preset {
+        name: "preset_sphere",
+        expr: "x1^2 + x2^2",
+        num_vars: 2,
+        start: &[2.0, 2.0],
+        minimum: &[0.0, 0.0],
+    },
+    Preset {
+        name: "preset_rosenbrock",
+        expr: "(1 - x1)^2 + 100*(x2 - x1^2)^2",
+        num_vars: 2,
+        start: &[-1.2, 1.0],
+        minimum: &[1.0, 1.0],
+    },
+    Preset {
+        name: "preset_rastrigin",
+        expr: "20 + x1^2 - 10*cos(2*pi*x1) + x2^2 - 10*cos(2*pi*x2)",
+        num_vars: 2,
+        start: &[2.2, -1.7],
+        minimum: &[0.0, 0.0],
+    },
+    Preset {
+        name: "preset_himmelblau",
+        expr: "(x1^2 + x2 - 11)^2 + (x1 + x2^2 - 7)^2",
+        num_vars: 2,
+        start: &[0.0, 0.0],
+        minimum: &[3.0, 2.0],
+    },
+    Preset {
+        name: "preset_booth",
+        expr: "(x1 + 2*x2 - 7)^2 + (2*x1 + x2 - 5)^2",
+        num_vars: 2,
+        start: &[-3.0, -3.0],
+        minimum: &[1.0, 3.0],
+    },
+    Preset {
+        name: "preset_beale",
+        expr: "(1.5 - x1 + x1*x2)^2 + (2.25 - x1 + x1*x2^2)^2 + (2.625 - x1 + x1*x2^3)^2",
+        num_vars: 2,
+        start: &[1.0, 1.0],
+        minimum: &[3.0, 0.5],
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{GradientMode, ParsedFunction};
+    use nalgebra::DVector;
+
+    #[test]
+    fn every_preset_parses_and_has_stationary_minimum() {
+        for preset in &PRESETS {
+            let func = ParsedFunction::new(preset.expr, preset.num_vars)
+                .unwrap_or_else(|e| panic!("{}: {}", preset.name, e));
+            assert_eq!(preset.start.len(), preset.num_vars, "{}", preset.name);
+            assert_eq!(preset.minimum.len(), preset.num_vars, "{}", preset.name);
+            let minimum = DVector::from_column_slice(preset.minimum);
+            let start = DVector::from_column_slice(preset.start);
+            let g = func
+                .gradient(&minimum, 1e-6, GradientMode::Analytic)
+                .unwrap();
+            assert!(g.amax() < 1e-9, "{}: ∇f = {}", preset.name, g);
+            assert!(func.eval(&minimum).unwrap() < func.eval(&start).unwrap());
+        }
+    }
+}
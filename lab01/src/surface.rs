@@ -0,0 +1,104 @@
+// Каркасная поверхность z = f(x, y) в простой аксонометрической проекции
+use crate::contour::Grid;
+
+// Отрезок каркаса в проекции и относительная высота его середины (0..1)
+pub struct ProjectedLine {
+    pub height: f64,
+    pub points: Vec<[f64; 2]>,
+}
+
+pub struct Surface {
+    pub grid: Grid,
+    z_range: (f64, f64),
+}
+
+impl Surface {
+    pub fn new(grid: Grid) -> Self {
+        let (mut z_min, mut z_max) = (f64::INFINITY, f64::NEG_INFINITY);
+        for &z in grid.values.iter().flatten() {
+            z_min = z_min.min(z);
+            z_max = z_max.max(z);
+        }
+        Self {
+            grid,
+            z_range: (z_min, z_max),
+        }
+    }
+
+    // Координаты сводятся к кубу [-1, 1]×[-1, 1]×[0, 1], чтобы масштаб
+    // функции не влиял на вид поверхности
+    pub fn normalize(&self, x: f64, y: f64, z: f64) -> [f64; 3] {
+        let scale = |v: f64, (lo, hi): (f64, f64)| {
+            if hi > lo {
+                (v - lo) / (hi - lo)
+            } else {
+                0.5
+            }
+        };
+        [
+            2.0 * scale(x, self.grid.x_range) - 1.0,
+            2.0 * scale(y, self.grid.y_range) - 1.0,
+            scale(z, self.z_range).clamp(0.0, 1.0),
+        ]
+    }
+
+    // Линии сетки вдоль x и вдоль y; линия разрывается в неопределённых узлах,
+    // а соседние рёбра одной цветовой полосы объединяются в одну ломаную
+    pub fn wireframe(&self, view: &View, bands: usize) -> Vec<ProjectedLine> {
+        let n = self.grid.n;
+        let mut lines = Vec::new();
+        for along_x in [true, false] {
+            for a in 0..n {
+                let node = |b: usize| if along_x { (b, a) } else { (a, b) };
+                let mut current: Option<(usize, ProjectedLine)> = None;
+                for b in 0..n.saturating_sub(1) {
+                    let (p, q) = (self.point(node(b)), self.point(node(b + 1)));
+                    let (Some(p), Some(q)) = (p, q) else {
+                        lines.extend(current.take().map(|(_, line)| line));
+                        continue;
+                    };
+                    let height = (p[2] + q[2]) / 2.0;
+                    let band = ((height * bands as f64) as usize).min(bands.saturating_sub(1));
+                    match &mut current {
+                        Some((k, line)) if *k == band => line.points.push(view.project(q)),
+                        _ => {
+                            lines.extend(current.take().map(|(_, line)| line));
+                            let height = (band as f64 + 0.5) / bands as f64;
+                            current = Some((
+                                band,
+                                ProjectedLine {
+                                    height,
+                                    points: vec![view.project(p), view.project(q)],
+                                },
+                            ));
+                        }
+                    }
+                }
+                lines.extend(current.map(|(_, line)| line));
+            }
+        }
+        lines
+    }
+
+    fn point(&self, (i, j): (usize, usize)) -> Option<[f64; 3]> {
+        let z = self.grid.values[j * self.grid.n + i]?;
+        let (x, y) = self.grid.node(i, j);
+        Some(self.normalize(x, y, z))
+    }
+}
+
+// Поворот вокруг вертикальной оси (yaw) и наклон к наблюдателю (pitch), в градусах
+pub struct View {
+    pub yaw: f64,
+    pub pitch: f64,
+}
+
+impl View {
+    pub fn project(&self, [x, y, z]: [f64; 3]) -> [f64; 2] {
+        let (sin_yaw, cos_yaw) = self.yaw.to_radians().sin_cos();
+        let (sin_pitch, cos_pitch) = self.pitch.to_radians().sin_cos();
+        let u = x * cos_yaw - y * sin_yaw;
+        let depth = x * sin_yaw + y * cos_yaw;
+        [u, z * cos_pitch + depth * sin_pitch]
+    }
+}